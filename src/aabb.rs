@@ -0,0 +1,116 @@
+use crate::{
+    ray::Ray,
+    vec3::{Point, Vec3},
+};
+
+/// Axis-aligned bounding box.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    min: Point,
+    max: Point,
+}
+
+impl Aabb {
+    pub fn new(a: Point, b: Point) -> Self {
+        Self {
+            min: Point::new(a.x().min(b.x()), a.y().min(b.y()), a.z().min(b.z())),
+            max: Point::new(a.x().max(b.x()), a.y().max(b.y()), a.z().max(b.z())),
+        }
+    }
+
+    pub fn min(&self) -> Point {
+        self.min
+    }
+
+    pub fn max(&self) -> Point {
+        self.max
+    }
+
+    pub fn size(&self) -> Vec3 {
+        self.max - self.min
+    }
+
+    pub fn centroid(&self) -> Point {
+        0.5 * (self.min + self.max)
+    }
+
+    pub fn contains(&self, p: Point) -> bool {
+        (0..3).all(|axis| p[axis] >= self.min[axis] && p[axis] <= self.max[axis])
+    }
+
+    /// Returns the parametric interval of `ray` inside the box, clipped to
+    /// `[t_min, t_max]`.
+    pub fn intersect(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<(f64, f64)> {
+        let mut t_min = t_min;
+        let mut t_max = t_max;
+        for axis in 0..3 {
+            let inv_d = 1.0 / ray.direction()[axis];
+            let mut t0 = (self.min[axis] - ray.origin()[axis]) * inv_d;
+            let mut t1 = (self.max[axis] - ray.origin()[axis]) * inv_d;
+            if inv_d < 0.0 {
+                std::mem::swap(&mut t0, &mut t1);
+            }
+            // NaN (origin on a slab with a parallel ray) leaves the interval alone.
+            if t0 > t_min {
+                t_min = t0;
+            }
+            if t1 < t_max {
+                t_max = t1;
+            }
+            if t_max < t_min {
+                return None;
+            }
+        }
+        Some((t_min, t_max))
+    }
+
+    pub fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> bool {
+        self.intersect(ray, t_min, t_max).is_some()
+    }
+}
+
+pub fn surrounding_box(box0: Aabb, box1: Aabb) -> Aabb {
+    Aabb {
+        min: Point::new(
+            box0.min.x().min(box1.min.x()),
+            box0.min.y().min(box1.min.y()),
+            box0.min.z().min(box1.min.z()),
+        ),
+        max: Point::new(
+            box0.max.x().max(box1.max.x()),
+            box0.max.y().max(box1.max.y()),
+            box0.max.z().max(box1.max.z()),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ray::Ray,
+        vec3::{Point, Vec3},
+    };
+
+    use super::{surrounding_box, Aabb};
+
+    #[test]
+    fn intersect() {
+        let b = Aabb::new(Point::new(-1.0, -1.0, -1.0), Point::new(1.0, 1.0, 1.0));
+        let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vec3::new(0.0, 0.0, 1.0));
+        assert_eq!(b.intersect(&ray, 0.0, f64::INFINITY), Some((4.0, 6.0)));
+        assert_eq!(b.intersect(&ray, 5.0, 5.5), Some((5.0, 5.5)));
+        assert!(!b.hit(&ray, 0.0, 3.0));
+
+        let ray = Ray::new(Point::new(0.0, 2.0, -5.0), Vec3::new(0.0, 0.0, 1.0));
+        assert!(!b.hit(&ray, 0.0, f64::INFINITY));
+    }
+
+    #[test]
+    fn surrounding() {
+        let b0 = Aabb::new(Point::new(0.0, 0.0, 0.0), Point::new(1.0, 1.0, 1.0));
+        let b1 = Aabb::new(Point::new(-1.0, 0.5, 0.5), Point::new(0.5, 2.0, 0.75));
+        let b = surrounding_box(b0, b1);
+        assert_eq!(b.min(), Point::new(-1.0, 0.0, 0.0));
+        assert_eq!(b.max(), Point::new(1.0, 2.0, 1.0));
+    }
+}
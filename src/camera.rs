@@ -1,27 +1,62 @@
+pub mod aperture;
+pub mod cube_map;
+pub mod equirectangular;
+pub mod fisheye;
+pub mod realistic;
+pub mod stereo;
+
+use std::sync::Arc;
+
+use aperture::{Aperture, CircularAperture};
+
 use crate::{
     ray::Ray,
-    vec3::{Point, Vec3, unit_vector, cross, random_in_unit_disk},
+    utils::random_double,
+    vec3::{Point, Vec3, unit_vector, cross},
 };
 
+pub trait Camera {
+    /// Ray through the image at `(s, t)`, both running from 0 to 1 from the
+    /// lower left corner. `None` where the point lies outside the camera's
+    /// picture, such as the corners around a circular fisheye; those samples
+    /// stay black.
+    fn get_ray(&self, s: f64, t: f64) -> Option<Ray>;
+}
+
 fn degrees_to_radians(degrees: f64) -> f64{
     return degrees * std::f64::consts::PI / 180.0;
 }
 
-pub struct Camera {
+/// How rays leave the image plane.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Projection {
+    /// Rays diverge from the eye through a thin lens.
+    Perspective,
+    /// Rays run parallel to the view direction from points on the image
+    /// plane, so sizes do not change with distance.
+    Orthographic,
+}
+
+/// Perspective or orthographic camera projecting onto a flat image plane.
+pub struct ProjectiveCamera {
+    projection: Projection,
     origin: Point,
     lower_left_corner: Point,
     horizontal: Vec3,
     vertical: Vec3,
 
-    #[allow(dead_code)]
     w: Vec3,
     u: Vec3,
     v: Vec3,
 
     lens_radius: f64,
+    aperture: Arc<dyn Aperture + Sync + Send>,
+
+    time0: f64,
+    time1: f64,
 }
 
-impl Camera {
+impl ProjectiveCamera {
     pub fn new(
         look_from: Point,
         look_at: Point,
@@ -46,7 +81,8 @@ impl Camera {
         let lower_left_corner =
             origin - horizontal / 2.0 - vertical / 2.0 - focus_distance * w;
 
-        Camera {
+        ProjectiveCamera {
+            projection: Projection::Perspective,
             origin,
             lower_left_corner,
             horizontal,
@@ -55,16 +91,140 @@ impl Camera {
             u,
             v,
             lens_radius: aperture / 2.0,
+            aperture: Arc::new(CircularAperture),
+            time0: 0.0,
+            time1: 0.0,
         }
     }
 
-    pub fn get_ray(&self, s: f64, t: f64) -> Ray {
-        let rd = self.lens_radius * random_in_unit_disk();
-        let offset = self.u * rd.x() + self.v * rd.y();
+    /// Orthographic camera looking from `look_from` towards `look_at`, with
+    /// an image plane `view_width` across centred on `look_from`. Rays start
+    /// on that plane, so geometry behind it is not seen.
+    pub fn orthographic(
+        look_from: Point,
+        look_at: Point,
+        v_up: Vec3,
+        view_width: f64,
+        aspect_ratio: f64,
+    ) -> Self {
+        let w = unit_vector(look_from - look_at);
+        let u = unit_vector(cross(v_up, w));
+        let v = cross(w, u);
+
+        let horizontal = view_width * u;
+        let vertical = view_width / aspect_ratio * v;
+        ProjectiveCamera {
+            projection: Projection::Orthographic,
+            origin: look_from,
+            lower_left_corner: look_from - horizontal / 2.0 - vertical / 2.0,
+            horizontal,
+            vertical,
+            w,
+            u,
+            v,
+            lens_radius: 0.0,
+            aperture: Arc::new(CircularAperture),
+            time0: 0.0,
+            time1: 0.0,
+        }
+    }
+
+    /// Gives the lens opening a shape other than a circle, such as the
+    /// polygon of a bladed iris, which shows in out of focus highlights.
+    pub fn with_aperture_shape(mut self, aperture: Arc<dyn Aperture + Sync + Send>) -> Self {
+        self.aperture = aperture;
+        self
+    }
+
+    /// Opens the shutter over `[open, close]`; each ray is fired at a random
+    /// instant in between, blurring moving objects.
+    pub fn with_shutter(mut self, open: f64, close: f64) -> Self {
+        self.time0 = open;
+        self.time1 = close;
+        self
+    }
+}
+
+impl Camera for ProjectiveCamera {
+    fn get_ray(&self, s: f64, t: f64) -> Option<Ray> {
+        let on_plane = self.lower_left_corner + s * self.horizontal + t * self.vertical;
+        let ray = match self.projection {
+            Projection::Perspective => {
+                let rd = self.lens_radius * self.aperture.sample();
+                let offset = self.u * rd.x() + self.v * rd.y();
+                Ray::new(self.origin + offset, on_plane - self.origin - offset)
+            }
+            Projection::Orthographic => Ray::new(on_plane, -self.w),
+        };
+        Some(ray.with_time(random_double(self.time0, self.time1)))
+    }
+}
+
+/// Position, orientation and shutter shared by the panoramic cameras, which
+/// map the image onto directions in the camera's local frame: `x` right, `y`
+/// up and `-z` towards `look_at`.
+#[derive(Clone)]
+struct Frame {
+    origin: Point,
+    u: Vec3,
+    v: Vec3,
+    w: Vec3,
+    time0: f64,
+    time1: f64,
+}
+
+impl Frame {
+    fn new(look_from: Point, look_at: Point, v_up: Vec3) -> Self {
+        let w = unit_vector(look_from - look_at);
+        let u = unit_vector(cross(v_up, w));
+        let v = cross(w, u);
+        Frame {
+            origin: look_from,
+            u,
+            v,
+            w,
+            time0: 0.0,
+            time1: 0.0,
+        }
+    }
+
+    fn to_world(&self, local: Vec3) -> Vec3 {
+        local.x() * self.u + local.y() * self.v + local.z() * self.w
+    }
+
+    fn ray(&self, local: Vec3) -> Ray {
+        self.ray_from(Vec3::default(), local)
+    }
+
+    /// Ray from `offset` away from the camera position, both in local
+    /// coordinates.
+    fn ray_from(&self, offset: Vec3, local: Vec3) -> Ray {
+        Ray::new(self.origin + self.to_world(offset), self.to_world(local))
+            .with_time(random_double(self.time0, self.time1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::vec3::{Point, Vec3};
+
+    use super::{Camera, ProjectiveCamera};
+
+    #[test]
+    fn orthographic_rays_are_parallel() {
+        let camera = ProjectiveCamera::orthographic(
+            Point::new(0.0, 0.0, 10.0),
+            Point::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            4.0,
+            2.0,
+        );
+        let centre = camera.get_ray(0.5, 0.5).unwrap();
+        assert_eq!(centre.origin(), Point::new(0.0, 0.0, 10.0));
+        assert_eq!(centre.direction(), Vec3::new(0.0, 0.0, -1.0));
 
-        Ray::new(
-            self.origin + offset,
-            self.lower_left_corner + s * self.horizontal + t * self.vertical - self.origin - offset,
-        )
+        let corner = camera.get_ray(1.0, 1.0).unwrap();
+        assert_eq!(corner.origin(), Point::new(2.0, 1.0, 10.0));
+        assert_eq!(corner.direction(), centre.direction());
     }
 }
@@ -0,0 +1,169 @@
+use std::f64::consts::PI;
+
+use crate::{
+    image::GrayImage,
+    utils::random_double,
+    vec3::{random_in_unit_disk, Vec3},
+};
+
+use super::degrees_to_radians;
+
+/// Shape of a lens opening, which out of focus highlights take on. Shapes
+/// lie within the square from -1 to 1 in `x` and `y`, scaled by the
+/// camera's aperture radius.
+pub trait Aperture {
+    /// Random point on the aperture with `z` zero, distributed by
+    /// `transmission`.
+    fn sample(&self) -> Vec3;
+
+    /// Fraction of light let through at `(x, y)`, zero outside the opening.
+    fn transmission(&self, x: f64, y: f64) -> f64;
+}
+
+/// The round opening of an ideal iris, filling the unit disk.
+pub struct CircularAperture;
+
+impl Aperture for CircularAperture {
+    fn sample(&self) -> Vec3 {
+        random_in_unit_disk()
+    }
+
+    fn transmission(&self, x: f64, y: f64) -> f64 {
+        if x * x + y * y <= 1.0 { 1.0 } else { 0.0 }
+    }
+}
+
+/// Regular polygon formed by an iris with straight `blades`, with its
+/// corners on the unit circle. A `rotation` of zero in degrees puts a corner
+/// on the `+x` axis.
+pub struct PolygonalAperture {
+    blades: usize,
+    rotation: f64,
+}
+
+impl PolygonalAperture {
+    pub fn new(blades: usize, rotation: f64) -> Self {
+        PolygonalAperture {
+            blades: blades.max(3),
+            rotation: degrees_to_radians(rotation),
+        }
+    }
+
+    fn corner(&self, i: usize) -> (f64, f64) {
+        let angle = self.rotation + 2.0 * PI * i as f64 / self.blades as f64;
+        (angle.cos(), angle.sin())
+    }
+}
+
+impl Aperture for PolygonalAperture {
+    fn sample(&self) -> Vec3 {
+        // The polygon is a fan of equal triangles around the centre; pick
+        // one, then a uniform point in it.
+        let i = (random_double(0.0, self.blades as f64) as usize).min(self.blades - 1);
+        let (a, b) = (self.corner(i), self.corner(i + 1));
+        let (mut s, mut t) = (random_double(0.0, 1.0), random_double(0.0, 1.0));
+        if s + t > 1.0 {
+            (s, t) = (1.0 - s, 1.0 - t);
+        }
+        Vec3::new(s * a.0 + t * b.0, s * a.1 + t * b.1, 0.0)
+    }
+
+    fn transmission(&self, x: f64, y: f64) -> f64 {
+        let sector = 2.0 * PI / self.blades as f64;
+        let angle = (f64::atan2(y, x) - self.rotation).rem_euclid(sector);
+        // Distance to the edge along this direction, from the apothem.
+        let edge = f64::cos(sector / 2.0) / f64::cos(angle - sector / 2.0);
+        if f64::hypot(x, y) <= edge { 1.0 } else { 0.0 }
+    }
+}
+
+/// Aperture drawn as a grayscale image, white where light passes, e.g. a
+/// heart or star cut into a lens cap. The longer side of the image spans
+/// -1 to 1 and the image is centred.
+pub struct MaskAperture {
+    mask: GrayImage,
+    scale: f64,
+    /// Running total of pixel values, row by row from the top, for sampling
+    /// pixels in proportion to their value.
+    cdf: Vec<f64>,
+}
+
+impl MaskAperture {
+    pub fn new(mask: GrayImage) -> Self {
+        let cdf = (0..mask.height())
+            .flat_map(|y| (0..mask.width()).map(move |x| (x, y)))
+            .scan(0.0, |total, (x, y)| {
+                *total += mask.get(x, y).max(0.0);
+                Some(*total)
+            })
+            .collect();
+        let scale = 2.0 / mask.width().max(mask.height()) as f64;
+        MaskAperture { mask, scale, cdf }
+    }
+
+    /// Position of the top left corner of pixel `(x, y)`.
+    fn pixel_corner(&self, x: f64, y: f64) -> (f64, f64) {
+        (
+            (x - self.mask.width() as f64 / 2.0) * self.scale,
+            (self.mask.height() as f64 / 2.0 - y) * self.scale,
+        )
+    }
+}
+
+impl Aperture for MaskAperture {
+    fn sample(&self) -> Vec3 {
+        let total = self.cdf.last().copied().unwrap_or(0.0);
+        if total <= 0.0 {
+            return Vec3::default();
+        }
+        let target = random_double(0.0, total);
+        let index = self.cdf.partition_point(|&c| c <= target).min(self.cdf.len() - 1);
+        let (x, y) = (index % self.mask.width(), index / self.mask.width());
+        let (px, py) = self.pixel_corner(
+            x as f64 + random_double(0.0, 1.0),
+            y as f64 + random_double(0.0, 1.0),
+        );
+        Vec3::new(px, py, 0.0)
+    }
+
+    fn transmission(&self, x: f64, y: f64) -> f64 {
+        let px = x / self.scale + self.mask.width() as f64 / 2.0;
+        let py = self.mask.height() as f64 / 2.0 - y / self.scale;
+        if px < 0.0 || py < 0.0 || px >= self.mask.width() as f64 || py >= self.mask.height() as f64 {
+            return 0.0;
+        }
+        self.mask.get(px as usize, py as usize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::image::GrayImage;
+
+    use super::*;
+
+    #[test]
+    fn samples_lie_inside_the_opening() {
+        let hexagon = PolygonalAperture::new(6, 0.0);
+        assert_eq!(hexagon.transmission(0.99, 0.0), 1.0);
+        assert_eq!(hexagon.transmission(0.0, 0.9), 0.0);
+        assert_eq!(hexagon.transmission(0.0, 0.85), 1.0);
+        let square = PolygonalAperture::new(4, 45.0);
+        assert_eq!(square.transmission(0.7, -0.7), 1.0);
+        assert_eq!(square.transmission(0.75, 0.0), 0.0);
+
+        // A mask with only its top right quarter open.
+        let mask = MaskAperture::new(GrayImage::new(2, 2, vec![0.0, 1.0, 0.0, 0.0]));
+        assert_eq!(mask.transmission(0.5, 0.5), 1.0);
+        assert_eq!(mask.transmission(-0.5, 0.5), 0.0);
+        assert_eq!(mask.transmission(1.5, 0.5), 0.0);
+
+        let shapes: [&dyn Aperture; 4] = [&CircularAperture, &hexagon, &square, &mask];
+        for shape in shapes {
+            for _ in 0..1000 {
+                let p = shape.sample();
+                assert!(shape.transmission(p.x(), p.y()) > 0.0, "{p:?}");
+            }
+        }
+    }
+}
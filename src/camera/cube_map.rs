@@ -0,0 +1,75 @@
+use crate::{
+    ray::Ray,
+    vec3::{Point, Vec3},
+};
+
+use super::{Camera, Frame};
+
+/// The six 90° views from a point, side by side in a 6:1 strip in the order
+/// +x, -x, +y, -y, +z, -z of the camera's local frame, for baking cube
+/// environment maps. Faces are seen unmirrored from the inside; the side
+/// faces are upright and the top and bottom faces join the -z face. With
+/// `look_at` down -z and `v_up` along +y the faces line up with the world
+/// axes.
+pub struct CubeMap {
+    frame: Frame,
+}
+
+/// Forward, right and up directions of each face.
+const FACES: [(Vec3, Vec3, Vec3); 6] = [
+    (Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 1.0), Vec3::new(0.0, 1.0, 0.0)),
+    (Vec3::new(-1.0, 0.0, 0.0), Vec3::new(0.0, 0.0, -1.0), Vec3::new(0.0, 1.0, 0.0)),
+    (Vec3::new(0.0, 1.0, 0.0), Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 1.0)),
+    (Vec3::new(0.0, -1.0, 0.0), Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 0.0, -1.0)),
+    (Vec3::new(0.0, 0.0, 1.0), Vec3::new(-1.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0)),
+    (Vec3::new(0.0, 0.0, -1.0), Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0)),
+];
+
+impl CubeMap {
+    pub fn new(look_from: Point, look_at: Point, v_up: Vec3) -> Self {
+        CubeMap {
+            frame: Frame::new(look_from, look_at, v_up),
+        }
+    }
+
+    pub fn with_shutter(mut self, open: f64, close: f64) -> Self {
+        self.frame.time0 = open;
+        self.frame.time1 = close;
+        self
+    }
+}
+
+impl Camera for CubeMap {
+    fn get_ray(&self, s: f64, t: f64) -> Option<Ray> {
+        let x = s * 6.0;
+        let face = (x as usize).min(5);
+        let a = 2.0 * (x - face as f64) - 1.0;
+        let b = 2.0 * t - 1.0;
+        let (forward, right, up) = FACES[face];
+        Some(self.frame.ray(forward + a * right + b * up))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::vec3::{cross, Point, Vec3};
+
+    use super::*;
+
+    #[test]
+    fn faces_look_along_the_axes() {
+        let camera = CubeMap::new(
+            Point::new(0.0, 0.0, 0.0),
+            Point::new(0.0, 0.0, -1.0),
+            Vec3::new(0.0, 1.0, 0.0),
+        );
+        for (i, (forward, right, up)) in FACES.into_iter().enumerate() {
+            let centre = camera.get_ray((i as f64 + 0.5) / 6.0, 0.5).unwrap();
+            assert_eq!(centre.direction(), forward);
+            // Unmirrored: right cross up points back at the viewer.
+            assert_eq!(cross(right, up), -forward);
+        }
+        let corner = camera.get_ray(1.0, 1.0).unwrap();
+        assert_eq!(corner.direction(), Vec3::new(1.0, 1.0, -1.0));
+    }
+}
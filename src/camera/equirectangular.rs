@@ -0,0 +1,66 @@
+use std::f64::consts::PI;
+
+use crate::{
+    ray::Ray,
+    vec3::{Point, Vec3},
+};
+
+use super::{Camera, Frame};
+
+/// Full 360° by 180° panorama in latitude-longitude layout, as used for
+/// environment maps and VR previews. The centre of the image looks towards
+/// `look_at`, the top row straight along `v_up`, and the left and right
+/// edges meet directly behind the camera. Render it at a 2:1 aspect ratio.
+pub struct Equirectangular {
+    frame: Frame,
+}
+
+impl Equirectangular {
+    pub fn new(look_from: Point, look_at: Point, v_up: Vec3) -> Self {
+        Equirectangular {
+            frame: Frame::new(look_from, look_at, v_up),
+        }
+    }
+
+    pub fn with_shutter(mut self, open: f64, close: f64) -> Self {
+        self.frame.time0 = open;
+        self.frame.time1 = close;
+        self
+    }
+}
+
+impl Camera for Equirectangular {
+    fn get_ray(&self, s: f64, t: f64) -> Option<Ray> {
+        let longitude = (s - 0.5) * 2.0 * PI;
+        let latitude = (t - 0.5) * PI;
+        let (sin_lon, cos_lon) = f64::sin_cos(longitude);
+        let (sin_lat, cos_lat) = f64::sin_cos(latitude);
+        Some(self.frame.ray(Vec3::new(cos_lat * sin_lon, sin_lat, -cos_lat * cos_lon)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::vec3::{Point, Vec3};
+
+    use super::*;
+
+    fn assert_direction(camera: &Equirectangular, (s, t): (f64, f64), expected: Vec3) {
+        let direction = camera.get_ray(s, t).unwrap().direction();
+        assert!((direction - expected).length() < 1e-9, "{direction:?} != {expected:?}");
+    }
+
+    #[test]
+    fn covers_the_sphere() {
+        let camera = Equirectangular::new(
+            Point::new(1.0, 2.0, 3.0),
+            Point::new(1.0, 2.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+        );
+        assert_direction(&camera, (0.5, 0.5), Vec3::new(0.0, 0.0, -1.0));
+        assert_direction(&camera, (0.75, 0.5), Vec3::new(1.0, 0.0, 0.0));
+        assert_direction(&camera, (0.0, 0.5), Vec3::new(0.0, 0.0, 1.0));
+        assert_direction(&camera, (0.3, 1.0), Vec3::new(0.0, 1.0, 0.0));
+        assert_eq!(camera.get_ray(0.1, 0.2).unwrap().origin(), Point::new(1.0, 2.0, 3.0));
+    }
+}
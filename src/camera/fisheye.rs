@@ -0,0 +1,107 @@
+use crate::{
+    ray::Ray,
+    vec3::{Point, Vec3},
+};
+
+use super::{degrees_to_radians, Camera, Frame};
+
+/// How a fisheye lens maps the angle from its axis to distance from the
+/// image centre.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FisheyeMapping {
+    /// Distance proportional to the angle, common in measurement lenses and
+    /// dome projection.
+    Equidistant,
+    /// Equal areas of the image cover equal solid angles, like most
+    /// photographic fisheyes.
+    Equisolid,
+}
+
+/// Circular fisheye whose image circle covers `fov` degrees and just fits
+/// the shorter side of the image. Up to 360° is possible; points outside the
+/// circle get no ray.
+pub struct Fisheye {
+    frame: Frame,
+    half_fov: f64,
+    aspect_ratio: f64,
+    mapping: FisheyeMapping,
+}
+
+impl Fisheye {
+    pub fn new(
+        look_from: Point,
+        look_at: Point,
+        v_up: Vec3,
+        fov: f64,
+        aspect_ratio: f64,
+        mapping: FisheyeMapping,
+    ) -> Self {
+        Fisheye {
+            frame: Frame::new(look_from, look_at, v_up),
+            half_fov: degrees_to_radians(fov) / 2.0,
+            aspect_ratio,
+            mapping,
+        }
+    }
+
+    pub fn with_shutter(mut self, open: f64, close: f64) -> Self {
+        self.frame.time0 = open;
+        self.frame.time1 = close;
+        self
+    }
+}
+
+impl Camera for Fisheye {
+    fn get_ray(&self, s: f64, t: f64) -> Option<Ray> {
+        let x = (s - 0.5) * self.aspect_ratio;
+        let y = t - 0.5;
+        let circle_radius = 0.5 * f64::min(self.aspect_ratio, 1.0);
+        let rho = f64::hypot(x, y);
+        let r = rho / circle_radius;
+        if r > 1.0 {
+            return None;
+        }
+
+        let theta = match self.mapping {
+            FisheyeMapping::Equidistant => r * self.half_fov,
+            FisheyeMapping::Equisolid => 2.0 * f64::asin(r * f64::sin(self.half_fov / 2.0)),
+        };
+        let (sin_theta, cos_theta) = f64::sin_cos(theta);
+        let (cos_phi, sin_phi) = if rho > 0.0 { (x / rho, y / rho) } else { (1.0, 0.0) };
+        Some(self.frame.ray(Vec3::new(sin_theta * cos_phi, sin_theta * sin_phi, -cos_theta)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::vec3::{dot, unit_vector, Point, Vec3};
+
+    use super::*;
+
+    #[test]
+    fn image_circle_spans_the_field_of_view() {
+        for mapping in [FisheyeMapping::Equidistant, FisheyeMapping::Equisolid] {
+            let camera = Fisheye::new(
+                Point::new(0.0, 0.0, 0.0),
+                Point::new(0.0, 0.0, -1.0),
+                Vec3::new(0.0, 1.0, 0.0),
+                180.0,
+                2.0,
+                mapping,
+            );
+            let axis = Vec3::new(0.0, 0.0, -1.0);
+            let centre = camera.get_ray(0.5, 0.5).unwrap();
+            assert!((unit_vector(centre.direction()) - axis).length() < 1e-9);
+
+            // The circle touches the top and bottom edges at 90° off axis.
+            let top = camera.get_ray(0.5, 1.0).unwrap();
+            assert!(dot(unit_vector(top.direction()), axis).abs() < 1e-9);
+            assert!(top.direction().y() > 0.0);
+            let right = camera.get_ray(0.75, 0.5).unwrap();
+            assert!(right.direction().x() > 0.0);
+
+            assert!(camera.get_ray(0.0, 0.5).is_none());
+            assert!(camera.get_ray(0.75, 0.9).is_none());
+        }
+    }
+}
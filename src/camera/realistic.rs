@@ -0,0 +1,368 @@
+use std::{
+    io::{self, BufRead},
+    sync::Arc,
+};
+
+use crate::{
+    material::refract,
+    ray::Ray,
+    utils::random_double,
+    vec3::{dot, random_in_unit_disk, unit_vector, Point, Vec3},
+};
+
+use super::{
+    aperture::{Aperture, CircularAperture},
+    Camera, Frame,
+};
+
+/// Lens prescriptions are in millimetres; scenes are taken to be in metres.
+const MILLIMETRES: f64 = 0.001;
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// One surface of a lens prescription, in millimetres.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LensElement {
+    /// Radius of curvature, positive when the centre lies towards the film.
+    /// Zero marks the aperture stop.
+    pub curvature_radius: f64,
+    /// Distance along the axis to the next surface towards the film.
+    pub thickness: f64,
+    /// Index of refraction of the medium behind the surface, towards the film.
+    pub eta: f64,
+    pub aperture_diameter: f64,
+}
+
+impl LensElement {
+    /// Reads a prescription in the format used by pbrt: one surface per line,
+    /// from the front of the lens to the back, as whitespace separated
+    /// `radius thickness ior aperture_diameter`. Lines starting with `#` are
+    /// skipped, and an index of 0 stands for air.
+    pub fn read_prescription<R: BufRead>(reader: R) -> io::Result<Vec<Self>> {
+        let mut elements = vec![];
+        for (number, line) in reader.lines().enumerate() {
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields: Vec<f64> = line
+                .split_whitespace()
+                .map(|f| f.parse::<f64>())
+                .collect::<Result<_, _>>()
+                .map_err(|_| invalid_data(format!("invalid lens surface on line {}", number + 1)))?;
+            let [curvature_radius, thickness, eta, aperture_diameter] = fields[..] else {
+                return Err(invalid_data(format!("expected 4 fields on line {}, found {}", number + 1, fields.len())));
+            };
+            elements.push(LensElement {
+                curvature_radius,
+                thickness,
+                eta: if eta == 0.0 { 1.0 } else { eta },
+                aperture_diameter,
+            });
+        }
+        if elements.is_empty() {
+            return Err(invalid_data("the prescription has no surfaces".to_string()));
+        }
+        Ok(elements)
+    }
+}
+
+/// Camera that traces each ray from the film through every surface of a
+/// real lens, as in pbrt's realistic camera, so vignetting, distortion and
+/// the shape of out of focus highlights come from the lens itself. The film
+/// sits at `look_from`. Rays blocked inside the lens give no sample, which
+/// also makes the image darker than a thin lens camera would.
+pub struct RealisticCamera {
+    frame: Frame,
+    elements: Vec<LensElement>,
+    film_size: (f64, f64),
+    /// Shape of the opening in the aperture stop.
+    aperture: Arc<dyn Aperture + Sync + Send>,
+    /// Radius of the disk on the rear element's vertex plane through which
+    /// every ray that makes it out of the lens passes.
+    pupil_radius: f64,
+}
+
+impl RealisticCamera {
+    /// `focus_distance` is measured from the film, in scene units, and may be
+    /// infinite. `film_diagonal` is in millimetres, e.g. 43.3 for full frame.
+    /// Panics if `elements` is empty.
+    pub fn new(
+        look_from: Point,
+        look_at: Point,
+        v_up: Vec3,
+        elements: Vec<LensElement>,
+        focus_distance: f64,
+        film_diagonal: f64,
+        aspect_ratio: f64,
+    ) -> Self {
+        assert!(!elements.is_empty(), "a lens needs at least one element");
+        let diagonal = f64::hypot(aspect_ratio, 1.0);
+        let mut camera = RealisticCamera {
+            frame: Frame::new(look_from, look_at, v_up),
+            elements,
+            film_size: (film_diagonal * aspect_ratio / diagonal, film_diagonal / diagonal),
+            aperture: Arc::new(CircularAperture),
+            pupil_radius: 0.0,
+        };
+        camera.focus(focus_distance / MILLIMETRES);
+        camera.pupil_radius = camera.find_pupil_radius();
+        camera
+    }
+
+    /// Stops the aperture down to `diameter` millimetres, at most the
+    /// diameter given in the prescription.
+    pub fn with_aperture_diameter(mut self, diameter: f64) -> Self {
+        if let Some(stop) = self.elements.iter_mut().find(|e| e.curvature_radius == 0.0) {
+            stop.aperture_diameter = f64::min(diameter, stop.aperture_diameter);
+        }
+        self.pupil_radius = self.find_pupil_radius();
+        self
+    }
+
+    /// Gives the aperture stop a shape other than a circle, scaled to its
+    /// diameter.
+    pub fn with_aperture_shape(mut self, aperture: Arc<dyn Aperture + Sync + Send>) -> Self {
+        self.aperture = aperture;
+        self.pupil_radius = self.find_pupil_radius();
+        self
+    }
+
+    pub fn with_shutter(mut self, open: f64, close: f64) -> Self {
+        self.frame.time0 = open;
+        self.frame.time1 = close;
+        self
+    }
+
+    /// Distance from the film to the rear surface.
+    fn rear_z(&self) -> f64 {
+        self.elements.last().map_or(0.0, |e| e.thickness)
+    }
+
+    /// Distance from the film to the front surface.
+    fn front_z(&self) -> f64 {
+        self.elements.iter().map(|e| e.thickness).sum()
+    }
+
+    /// Refracts `ray` through surface `i`, whose vertex is at `z`, from a
+    /// medium of index `eta_i` into one of `eta_t`. `None` if the ray misses
+    /// the surface's aperture or is totally internally reflected.
+    fn interface(&self, i: usize, z: f64, ray: &Ray, eta_i: f64, eta_t: f64) -> Option<Ray> {
+        let element = &self.elements[i];
+        let (o, d) = (ray.origin(), ray.direction());
+        let radius = element.curvature_radius;
+
+        let (t, normal) = if radius == 0.0 {
+            ((z - o.z()) / d.z(), Vec3::new(0.0, 0.0, 1.0))
+        } else {
+            let centre = Point::new(0.0, 0.0, z + radius);
+            let oc = o - centre;
+            let a = d.length_squared();
+            let half_b = dot(oc, d);
+            let c = oc.length_squared() - radius * radius;
+            let discriminant = half_b * half_b - a * c;
+            if discriminant < 0.0 {
+                return None;
+            }
+            let (t0, t1) = ((-half_b - discriminant.sqrt()) / a, (-half_b + discriminant.sqrt()) / a);
+            let t = if (d.z() > 0.0) ^ (radius < 0.0) { t0.min(t1) } else { t0.max(t1) };
+            (t, unit_vector(ray.at(t) - centre))
+        };
+        if t < 0.0 {
+            return None;
+        }
+
+        let p = ray.at(t);
+        let aperture_radius = element.aperture_diameter / 2.0;
+        if radius == 0.0 {
+            let transmission = self.aperture.transmission(p.x() / aperture_radius, p.y() / aperture_radius);
+            if transmission <= 0.0 || random_double(0.0, 1.0) >= transmission {
+                return None;
+            }
+            return Some(Ray::new(p, d));
+        }
+        if p.x() * p.x() + p.y() * p.y() > aperture_radius * aperture_radius {
+            return None;
+        }
+
+        let d = unit_vector(d);
+        let n = if dot(normal, d) > 0.0 { -normal } else { normal };
+        let eta = eta_i / eta_t;
+        let cos_i = -dot(d, n);
+        if eta * eta * (1.0 - cos_i * cos_i) > 1.0 {
+            return None;
+        }
+        Some(Ray::new(p, refract(d, n, eta)))
+    }
+
+    /// Follows a ray in lens space, where the film is at `z = 0` and the lens
+    /// lies towards `-z`, from the film out through the front surface.
+    fn trace_from_film(&self, ray: &Ray) -> Option<Ray> {
+        let mut z = 0.0;
+        let mut ray = *ray;
+        for i in (0..self.elements.len()).rev() {
+            z -= self.elements[i].thickness;
+            let eta_t = if i > 0 { self.elements[i - 1].eta } else { 1.0 };
+            ray = self.interface(i, z, &ray, self.elements[i].eta, eta_t)?;
+        }
+        Some(ray)
+    }
+
+    /// Follows a ray in lens space from the scene in through the rear
+    /// surface.
+    fn trace_from_scene(&self, ray: &Ray) -> Option<Ray> {
+        let mut z = -self.front_z();
+        let mut ray = *ray;
+        for i in 0..self.elements.len() {
+            let eta_i = if i > 0 { self.elements[i - 1].eta } else { 1.0 };
+            ray = self.interface(i, z, &ray, eta_i, self.elements[i].eta)?;
+            z += self.elements[i].thickness;
+        }
+        Some(ray)
+    }
+
+    /// Moves the lens along its axis, by changing the gap between the rear
+    /// surface and the film, until a point on the axis `distance` in front
+    /// of the film is imaged onto it. Uses a ray close to the axis, so
+    /// aberrations do not shift the focus.
+    fn focus(&mut self, distance: f64) {
+        let height = 1e-3 * self.elements[0].aperture_diameter;
+        for _ in 0..20 {
+            let front = -self.front_z();
+            let ray = if distance.is_finite() {
+                let object = Point::new(0.0, 0.0, -distance);
+                Ray::new(object, Point::new(height, 0.0, front) - object)
+            } else {
+                Ray::new(Point::new(height, 0.0, front - 1.0), Vec3::new(0.0, 0.0, 1.0))
+            };
+            let Some(out) = self.trace_from_scene(&ray) else {
+                return;
+            };
+            if out.direction().x() == 0.0 {
+                return;
+            }
+            let image_z = out.at(-out.origin().x() / out.direction().x()).z();
+            let rear = self.elements.len() - 1;
+            self.elements[rear].thickness += image_z;
+            if image_z.abs() < 1e-9 {
+                return;
+            }
+        }
+    }
+
+    /// Finds how far from the axis rays leaving the film, anywhere on it,
+    /// can cross the rear vertex plane and still make it through the lens.
+    fn find_pupil_radius(&self) -> f64 {
+        const FILM_STEPS: usize = 16;
+        const PUPIL_STEPS: usize = 64;
+
+        let rear_radius = self.elements.last().map_or(0.0, |e| e.aperture_diameter / 2.0);
+        let extent = 1.5 * rear_radius;
+        let step = 2.0 * extent / PUPIL_STEPS as f64;
+        let film_radius = f64::hypot(self.film_size.0, self.film_size.1) / 2.0;
+
+        let mut radius: f64 = 0.0;
+        for i in 0..=FILM_STEPS {
+            let film = Point::new(film_radius * i as f64 / FILM_STEPS as f64, 0.0, 0.0);
+            for x in 0..PUPIL_STEPS {
+                for y in 0..PUPIL_STEPS {
+                    let rear = Point::new(
+                        -extent + (x as f64 + 0.5) * step,
+                        -extent + (y as f64 + 0.5) * step,
+                        -self.rear_z(),
+                    );
+                    if self.trace_from_film(&Ray::new(film, rear - film)).is_some() {
+                        radius = radius.max(f64::hypot(rear.x(), rear.y()));
+                    }
+                }
+            }
+        }
+        if radius == 0.0 {
+            return rear_radius;
+        }
+        radius + step
+    }
+}
+
+impl Camera for RealisticCamera {
+    fn get_ray(&self, s: f64, t: f64) -> Option<Ray> {
+        // The lens turns the image upside down, so the top right of the
+        // picture comes from the bottom left of the film.
+        let film = Point::new(-(s - 0.5) * self.film_size.0, -(t - 0.5) * self.film_size.1, 0.0);
+        let pupil = self.pupil_radius * random_in_unit_disk();
+        let rear = Point::new(pupil.x(), pupil.y(), -self.rear_z());
+        let out = self.trace_from_film(&Ray::new(film, rear - film))?;
+
+        Some(self.frame.ray_from(MILLIMETRES * out.origin(), out.direction()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::vec3::{Point, Vec3};
+
+    use super::*;
+
+    /// A 50mm f/2 double Gauss lens, after US patent 2,673,491.
+    const DOUBLE_GAUSS: &str = "
+        # radius  thickness  ior  aperture
+        29.475   3.76    1.67   25.2
+        84.83    0.12    1      25.2
+        19.275   4.025   1.67   23
+        40.77    3.275   1.699  23
+        12.75    5.705   1      18
+        0        4.5     0      17.1
+        -14.495  1.18    1.603  17
+        40.77    6.065   1.658  20
+        -20.385  0.19    1      20
+        437.065  3.22    1.717  20
+        -39.73   0       1      20
+    ";
+
+    fn lens(focus_distance: f64) -> RealisticCamera {
+        let elements = LensElement::read_prescription(DOUBLE_GAUSS.as_bytes()).unwrap();
+        RealisticCamera::new(
+            Point::new(0.0, 0.0, 0.0),
+            Point::new(0.0, 0.0, -1.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            elements,
+            focus_distance,
+            43.3,
+            1.5,
+        )
+    }
+
+    #[test]
+    fn reads_prescriptions() {
+        let elements = LensElement::read_prescription(DOUBLE_GAUSS.as_bytes()).unwrap();
+        assert_eq!(elements.len(), 11);
+        assert_eq!(elements[5], LensElement { curvature_radius: 0.0, thickness: 4.5, eta: 1.0, aperture_diameter: 17.1 });
+        assert!(LensElement::read_prescription("1 2 3".as_bytes()).is_err());
+        assert!(LensElement::read_prescription("# nothing".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn focuses_rays_from_the_film() {
+        let camera = lens(2.0).with_aperture_diameter(4.0);
+        // Rays from the middle of the film cross the axis again at the focus
+        // distance, give or take a little spherical aberration.
+        let rays: Vec<_> = (0..1000).filter_map(|_| camera.get_ray(0.5, 0.5)).take(20).collect();
+        assert!(!rays.is_empty());
+        for ray in rays {
+            let focus = ray.at(-ray.origin().x() / ray.direction().x());
+            assert!(focus.y().abs() < 1e-9);
+            assert!((focus.z() + 2.0).abs() < 0.05, "{}", focus.z());
+        }
+
+        // A 50mm lens on full frame sees about 47° across the diagonal, so
+        // corner rays leave about 23.4° off the axis.
+        let camera = lens(f64::INFINITY);
+        let corner = (0..200).find_map(|_| camera.get_ray(1.0, 1.0)).unwrap();
+        let angle = f64::acos(-unit_vector(corner.direction()).z()).to_degrees();
+        assert!((angle - 23.4).abs() < 2.0, "{angle}");
+        assert!(corner.direction().x() > 0.0 && corner.direction().y() > 0.0);
+    }
+}
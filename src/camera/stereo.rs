@@ -0,0 +1,284 @@
+use std::f64::consts::PI;
+
+use crate::{
+    ray::Ray,
+    vec3::{cross, unit_vector, Color, Point, Vec3},
+};
+
+use super::{Camera, Frame, ProjectiveCamera};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Eye {
+    Left,
+    Right,
+}
+
+impl Eye {
+    /// Which way the eye sits from the centre of the rig along its right
+    /// vector.
+    fn side(self) -> f64 {
+        match self {
+            Eye::Left => -1.0,
+            Eye::Right => 1.0,
+        }
+    }
+}
+
+/// How the two eye images are packed into one frame.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StereoLayout {
+    /// Left eye on the left, right eye on the right, doubling the width.
+    SideBySide,
+    /// Left eye on top, right eye below, doubling the height.
+    OverUnder,
+}
+
+impl StereoLayout {
+    /// Packs two images of `width` pixels per row, stored top row first as
+    /// returned by `create_image`, into one.
+    pub fn combine(self, left: Vec<Color>, right: Vec<Color>, width: usize) -> Vec<Color> {
+        match self {
+            StereoLayout::SideBySide => left
+                .chunks(width)
+                .zip(right.chunks(width))
+                .flat_map(|(l, r)| l.iter().chain(r).copied())
+                .collect(),
+            StereoLayout::OverUnder => [left, right].concat(),
+        }
+    }
+}
+
+/// A pair of cameras, one per eye.
+pub trait StereoRig {
+    fn eye(&self, eye: Eye) -> Box<dyn Camera + Sync + Send>;
+}
+
+/// Two pinhole cameras `interocular` apart, centred on `look_from` and
+/// looking parallel. Rather than toeing in, which introduces vertical
+/// parallax, each eye's image is shifted so that both frame the same plane
+/// at the `convergence` distance: objects there appear at screen depth,
+/// nearer ones in front of it. Use `f64::INFINITY` to converge at infinity.
+pub struct StereoCamera {
+    look_from: Point,
+    look_at: Point,
+    v_up: Vec3,
+    vertical_fov: f64,
+    aspect_ratio: f64,
+    interocular: f64,
+    convergence: f64,
+    time0: f64,
+    time1: f64,
+}
+
+impl StereoCamera {
+    pub fn new(
+        look_from: Point,
+        look_at: Point,
+        v_up: Vec3,
+        vertical_fov: f64,
+        aspect_ratio: f64,
+        interocular: f64,
+        convergence: f64,
+    ) -> Self {
+        StereoCamera {
+            look_from,
+            look_at,
+            v_up,
+            vertical_fov,
+            aspect_ratio,
+            interocular,
+            convergence,
+            time0: 0.0,
+            time1: 0.0,
+        }
+    }
+
+    pub fn with_shutter(mut self, open: f64, close: f64) -> Self {
+        self.time0 = open;
+        self.time1 = close;
+        self
+    }
+
+    pub fn camera(&self, eye: Eye) -> ProjectiveCamera {
+        let u = unit_vector(cross(self.v_up, self.look_from - self.look_at));
+        let offset = eye.side() * self.interocular / 2.0 * u;
+        let (focus_distance, shift) = if self.convergence.is_finite() {
+            (self.convergence, offset)
+        } else {
+            (1.0, Vec3::default())
+        };
+        let mut camera = ProjectiveCamera::new(
+            self.look_from + offset,
+            self.look_at + offset,
+            self.v_up,
+            self.vertical_fov,
+            self.aspect_ratio,
+            0.0,
+            focus_distance,
+        )
+        .with_shutter(self.time0, self.time1);
+        camera.lower_left_corner = camera.lower_left_corner - shift;
+        camera
+    }
+}
+
+impl StereoRig for StereoCamera {
+    fn eye(&self, eye: Eye) -> Box<dyn Camera + Sync + Send> {
+        Box::new(self.camera(eye))
+    }
+}
+
+/// Omni-directional stereo: an equirectangular panorama per eye in which
+/// every column is seen from a pair of eyes turned to face that way. The
+/// eyes sit on a circle `interocular` across around `look_from`, and their
+/// rays converge at the `convergence` distance, `f64::INFINITY` for
+/// parallel. Depth is correct all around the horizon. The eyes close in
+/// with the cosine of latitude, so depth fades out towards the poles and
+/// the panoramas meet there without swirling. Render each eye at a 2:1
+/// aspect ratio, usually over-under.
+pub struct OmniStereo {
+    frame: Frame,
+    interocular: f64,
+    convergence: f64,
+}
+
+impl OmniStereo {
+    pub fn new(look_from: Point, look_at: Point, v_up: Vec3, interocular: f64, convergence: f64) -> Self {
+        OmniStereo {
+            frame: Frame::new(look_from, look_at, v_up),
+            interocular,
+            convergence,
+        }
+    }
+
+    pub fn with_shutter(mut self, open: f64, close: f64) -> Self {
+        self.frame.time0 = open;
+        self.frame.time1 = close;
+        self
+    }
+
+    pub fn camera(&self, eye: Eye) -> OmniStereoEye {
+        OmniStereoEye {
+            frame: self.frame.clone(),
+            offset: eye.side() * self.interocular / 2.0,
+            convergence: self.convergence,
+        }
+    }
+}
+
+impl StereoRig for OmniStereo {
+    fn eye(&self, eye: Eye) -> Box<dyn Camera + Sync + Send> {
+        Box::new(self.camera(eye))
+    }
+}
+
+/// One eye of an `OmniStereo` rig.
+pub struct OmniStereoEye {
+    frame: Frame,
+    offset: f64,
+    convergence: f64,
+}
+
+impl Camera for OmniStereoEye {
+    fn get_ray(&self, s: f64, t: f64) -> Option<Ray> {
+        let longitude = (s - 0.5) * 2.0 * PI;
+        let latitude = (t - 0.5) * PI;
+        let (sin_lon, cos_lon) = f64::sin_cos(longitude);
+        let (sin_lat, cos_lat) = f64::sin_cos(latitude);
+        let direction = Vec3::new(cos_lat * sin_lon, sin_lat, -cos_lat * cos_lon);
+
+        let eye = self.offset * cos_lat * Vec3::new(cos_lon, 0.0, sin_lon);
+        let direction = if self.convergence.is_finite() {
+            self.convergence * direction - eye
+        } else {
+            direction
+        };
+        Some(self.frame.ray_from(eye, direction))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::FRAC_1_SQRT_2;
+
+    use crate::vec3::{cross, dot, Color, Point, Vec3};
+
+    use super::*;
+
+    /// Where the lines of two rays pass closest, assuming they meet.
+    fn meeting_point(a: &Ray, b: &Ray) -> Point {
+        let n = cross(a.direction(), b.direction());
+        let t = dot(cross(b.origin() - a.origin(), b.direction()), n) / n.length_squared();
+        a.at(t)
+    }
+
+    #[test]
+    fn eyes_converge_on_the_screen_plane() {
+        let rig = StereoCamera::new(
+            Point::new(0.0, 0.0, 0.0),
+            Point::new(0.0, 0.0, -1.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            40.0,
+            1.5,
+            0.065,
+            3.0,
+        );
+        let left = rig.eye(Eye::Left);
+        let right = rig.eye(Eye::Right);
+        for (s, t) in [(0.5, 0.5), (0.1, 0.8)] {
+            let (l, r) = (left.get_ray(s, t).unwrap(), right.get_ray(s, t).unwrap());
+            assert!((l.origin() - Point::new(-0.0325, 0.0, 0.0)).length() < 1e-12);
+            assert!((r.origin() - Point::new(0.0325, 0.0, 0.0)).length() < 1e-12);
+            // The eyes look parallel, so the image plane is not toed in.
+            assert!((meeting_point(&l, &r).z() + 3.0).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn omni_stereo_eyes_circle_the_centre() {
+        let rig = OmniStereo::new(
+            Point::new(0.0, 1.0, 0.0),
+            Point::new(0.0, 1.0, -1.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            0.064,
+            f64::INFINITY,
+        );
+        let (left, right) = (rig.eye(Eye::Left), rig.eye(Eye::Right));
+        for s in [0.1, 0.5, 0.8] {
+            let (l, r) = (left.get_ray(s, 0.5).unwrap(), right.get_ray(s, 0.5).unwrap());
+            assert!(((l.origin() - Point::new(0.0, 1.0, 0.0)).length() - 0.032).abs() < 1e-12);
+            assert!((l.origin() + r.origin() - Point::new(0.0, 2.0, 0.0)).length() < 1e-12);
+            assert!((l.direction() - r.direction()).length() < 1e-12);
+            // The baseline is square to the view direction.
+            assert!(dot(r.origin() - l.origin(), l.direction()).abs() < 1e-12);
+            // The right eye is on the right looking along the ray.
+            assert!(cross(r.origin() - l.origin(), l.direction()).y() > 0.0);
+        }
+    }
+
+    #[test]
+    fn omni_stereo_eyes_meet_at_the_poles() {
+        let rig = OmniStereo::new(
+            Point::new(0.0, 0.0, 0.0),
+            Point::new(0.0, 0.0, -1.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            0.064,
+            f64::INFINITY,
+        );
+        let (left, right) = (rig.eye(Eye::Left), rig.eye(Eye::Right));
+        for (t, separation) in [(0.5, 0.064), (0.75, 0.064 * FRAC_1_SQRT_2), (1.0, 0.0)] {
+            let (l, r) = (left.get_ray(0.3, t).unwrap(), right.get_ray(0.3, t).unwrap());
+            assert!(((r.origin() - l.origin()).length() - separation).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn layouts() {
+        let left = vec![Color::new(1.0, 0.0, 0.0); 4];
+        let right = vec![Color::new(0.0, 1.0, 0.0); 4];
+        let packed = StereoLayout::SideBySide.combine(left.clone(), right.clone(), 2);
+        assert_eq!(packed, [left[0], left[0], right[0], right[0], left[0], left[0], right[0], right[0]]);
+        let packed = StereoLayout::OverUnder.combine(left.clone(), right.clone(), 2);
+        assert_eq!(packed, [&left[..], &right[..]].concat());
+    }
+}
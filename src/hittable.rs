@@ -1,21 +1,46 @@
 use std::{sync::Arc};
 
 pub mod sphere;
+pub mod constant_medium;
+pub mod grid_medium;
+pub mod transformed;
+pub mod bvh;
+pub mod quad;
+pub mod disk;
+pub mod plane;
+pub mod cuboid;
+pub mod cylinder;
+pub mod cone;
+pub mod paraboloid;
+pub mod torus;
+pub mod csg;
+pub mod sdf;
+pub mod heightfield;
+pub mod triangle;
+pub mod curve;
+pub mod particles;
 
 use crate::{
+    aabb::{surrounding_box, Aabb},
     material::Material,
     ray::{Ray},
-    vec3::{dot, Point, Vec3},
+    utils::random_double,
+    vec3::{dot, unit_vector, Point, Vec3},
 };
 
 #[derive(Default, Clone)]
 pub struct HitRecord {
     pub p: Point,
     pub t: f64,
+    pub u: f64,
+    pub v: f64,
     pub material: Option<Arc<dyn Material + Sync + Send>>,
 
     pub normal: Vec3,
     pub front_face: bool,
+    /// Direction of increasing `u` along the surface, for materials such as
+    /// hair that depend on it. Zero unless the primitive provides one.
+    pub tangent: Vec3,
 }
 
 impl HitRecord {
@@ -24,18 +49,43 @@ impl HitRecord {
         t: f64,
         material: Option<Arc<dyn Material + Sync + Send>>,
         outward_normal: Vec3,
-        ray: &Ray
+        ray: &Ray,
+        (u, v): (f64, f64),
     ) -> Self {
         let (front_face, normal) = Self::get_face_normal(ray, outward_normal);
         Self {
             p,
             t,
+            u,
+            v,
             material,
             normal,
             front_face,
+            tangent: Vec3::default(),
         }
     }
 
+    /// A scattering event inside a participating medium, where there is no
+    /// surface: the normal is arbitrary and the hit always counts as a front
+    /// face.
+    pub fn in_volume(p: Point, t: f64, material: Arc<dyn Material + Sync + Send>) -> Self {
+        Self {
+            p,
+            t,
+            u: 0.0,
+            v: 0.0,
+            material: Some(material),
+            normal: Vec3::new(1.0, 0.0, 0.0),
+            front_face: true,
+            tangent: Vec3::default(),
+        }
+    }
+
+    pub fn with_tangent(mut self, tangent: Vec3) -> Self {
+        self.tangent = tangent;
+        self
+    }
+
     fn get_face_normal(ray: &Ray, outward_normal: Vec3) -> (bool, Vec3) {
         let front_face = dot(ray.direction(), outward_normal) < 0.0;
         let normal = if front_face {
@@ -47,8 +97,81 @@ impl HitRecord {
     }
 }
 
+/// Texture coordinate running once around the y axis.
+pub(crate) fn azimuth_u(x: f64, z: f64) -> f64 {
+    (f64::atan2(-z, x) + std::f64::consts::PI) / (2.0 * std::f64::consts::PI)
+}
+
+/// The first of `crossings`, sorted by `t`, that lies within `[t_min, t_max]`.
+pub(crate) fn first_in_range(crossings: Vec<HitRecord>, t_min: f64, t_max: f64) -> Option<HitRecord> {
+    crossings
+        .into_iter()
+        .find(|rec| rec.t >= t_min && rec.t <= t_max)
+}
+
 pub trait Hittable {
     fn hit(&self, ray: &crate::ray::Ray, t_min: f64, t_max: f64) -> Option<HitRecord>;
+
+    /// Box enclosing the object, or `None` for unbounded objects.
+    fn bounding_box(&self) -> Option<Aabb>;
+
+    /// Solid angle density of `random` sampling `direction` from `origin`,
+    /// for objects that can be sampled as lights.
+    fn pdf_value(&self, _origin: Point, _direction: Vec3) -> f64 {
+        0.0
+    }
+
+    /// Direction from `origin` towards a random point on the surface.
+    fn random(&self, _origin: Point) -> Vec3 {
+        Vec3::new(1.0, 0.0, 0.0)
+    }
+}
+
+/// A stretch of a ray's line spent inside a solid, between the surface
+/// crossings where it enters and exits. `enter` is marked as a front face hit
+/// and `exit` as a back face hit; both normals face against the ray.
+#[derive(Clone)]
+pub struct Interval {
+    pub enter: HitRecord,
+    pub exit: HitRecord,
+}
+
+/// A closed hittable with a well defined inside, which can report every
+/// interval its ray spends inside rather than only the nearest hit. Used for
+/// constructive solid geometry. Shapes that are only closed when capped,
+/// such as a `Cylinder`, report no intervals without their caps.
+pub trait Solid: Hittable {
+    /// All intervals along the ray's whole line, including negative `t`,
+    /// sorted and disjoint.
+    fn intervals(&self, ray: &Ray) -> Vec<Interval>;
+}
+
+/// Pairs up the sorted surface crossings of a closed surface into intervals,
+/// merging crossings at the same `t` such as on edges between faces. Merging
+/// also folds the double root of a ray grazing the surface into a single
+/// crossing that neither enters nor exits, which would leave the rest paired
+/// the wrong way round, so an odd one out is dropped by taking the crossing
+/// most nearly tangent to the ray.
+pub(crate) fn pair_crossings(ray: &Ray, mut crossings: Vec<HitRecord>) -> Vec<Interval> {
+    crossings.dedup_by(|a, b| f64::abs(a.t - b.t) < 1e-9);
+    if crossings.len() % 2 == 1 {
+        let direction = unit_vector(ray.direction());
+        let grazing = |rec: &HitRecord| f64::abs(dot(direction, rec.normal));
+        let tangent = (0..crossings.len())
+            .min_by(|&i, &j| grazing(&crossings[i]).total_cmp(&grazing(&crossings[j])))
+            .unwrap();
+        crossings.remove(tangent);
+    }
+    crossings
+        .chunks_exact(2)
+        .map(|pair| {
+            let mut enter = pair[0].clone();
+            let mut exit = pair[1].clone();
+            enter.front_face = true;
+            exit.front_face = false;
+            Interval { enter, exit }
+        })
+        .collect()
 }
 
 #[derive(Default)]
@@ -85,4 +208,29 @@ impl Hittable for HittableList {
         }
         res
     }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let mut boxes = self.objects.iter().map(|object| object.bounding_box());
+        let first = boxes.next()??;
+        boxes.try_fold(first, |acc, b| Some(surrounding_box(acc, b?)))
+    }
+
+    fn pdf_value(&self, origin: Point, direction: Vec3) -> f64 {
+        if self.objects.is_empty() {
+            return 0.0;
+        }
+        let weight = 1.0 / self.objects.len() as f64;
+        self.objects
+            .iter()
+            .map(|object| weight * object.pdf_value(origin, direction))
+            .sum()
+    }
+
+    fn random(&self, origin: Point) -> Vec3 {
+        if self.objects.is_empty() {
+            return Vec3::new(1.0, 0.0, 0.0);
+        }
+        let index = (random_double(0.0, self.objects.len() as f64) as usize).min(self.objects.len() - 1);
+        self.objects[index].random(origin)
+    }
 }
@@ -0,0 +1,115 @@
+use std::sync::Arc;
+
+use crate::{
+    aabb::{surrounding_box, Aabb},
+    hittable::{HitRecord, Hittable, HittableList},
+    ray::Ray,
+};
+
+/// Bounding volume hierarchy over bounded hittables. Children are split at
+/// the median centroid along the longest axis of their bounds.
+pub struct BvhNode {
+    left: Arc<dyn Hittable + Sync + Send>,
+    right: Arc<dyn Hittable + Sync + Send>,
+    bbox: Aabb,
+}
+
+impl BvhNode {
+    /// Panics if `objects` is empty or any object is unbounded.
+    pub fn new(mut objects: Vec<Arc<dyn Hittable + Sync + Send>>) -> Self {
+        assert!(!objects.is_empty(), "a BVH needs at least one object");
+        let boxes = objects
+            .iter()
+            .map(|o| o.bounding_box().expect("BVH objects should be bounded"))
+            .collect::<Vec<_>>();
+        let bounds = boxes[1..].iter().fold(boxes[0], |acc, b| surrounding_box(acc, *b));
+        let size = bounds.size();
+        let axis = if size.x() > size.y() && size.x() > size.z() {
+            0
+        } else if size.y() > size.z() {
+            1
+        } else {
+            2
+        };
+
+        let (left, right): (Arc<dyn Hittable + Sync + Send>, Arc<dyn Hittable + Sync + Send>) =
+            match objects.len() {
+                1 => (Arc::clone(&objects[0]), Arc::clone(&objects[0])),
+                2 => (Arc::clone(&objects[0]), Arc::clone(&objects[1])),
+                n => {
+                    objects.sort_by(|a, b| {
+                        let a = a.bounding_box().unwrap().centroid()[axis];
+                        let b = b.bounding_box().unwrap().centroid()[axis];
+                        a.total_cmp(&b)
+                    });
+                    let rest = objects.split_off(n / 2);
+                    (Arc::new(BvhNode::new(objects)), Arc::new(BvhNode::new(rest)))
+                }
+            };
+
+        Self {
+            left,
+            right,
+            bbox: bounds,
+        }
+    }
+
+    pub fn from_list(list: HittableList) -> Self {
+        Self::new(list.objects)
+    }
+}
+
+impl Hittable for BvhNode {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        if !self.bbox.hit(ray, t_min, t_max) {
+            return None;
+        }
+        let left = self.left.hit(ray, t_min, t_max);
+        let t_max = left.as_ref().map_or(t_max, |rec| rec.t);
+        self.right.hit(ray, t_min, t_max).or(left)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(self.bbox)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        hittable::{sphere::Sphere, Hittable, HittableList},
+        material::lambertian::Lambertian,
+        ray::Ray,
+        utils::random_double,
+        vec3::{random_unit_vector, Color, Point, Vec3},
+    };
+
+    use super::BvhNode;
+
+    #[test]
+    fn matches_linear_list() {
+        let material = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+        let mut list = HittableList::new();
+        let mut objects: Vec<Arc<dyn Hittable + Sync + Send>> = vec![];
+        for _ in 0..200 {
+            let sphere = Arc::new(Sphere::new(
+                Vec3::random(-10.0, 10.0),
+                random_double(0.1, 1.0),
+                material.clone(),
+            ));
+            list.add(sphere.clone());
+            objects.push(sphere);
+        }
+        let bvh = BvhNode::new(objects);
+        assert_eq!(bvh.bounding_box(), list.bounding_box());
+
+        for _ in 0..500 {
+            let ray = Ray::new(Point::new(0.0, 0.0, 0.0) + 20.0 * random_unit_vector(), random_unit_vector());
+            let expected = list.hit(&ray, 0.001, f64::INFINITY).map(|rec| rec.t);
+            let actual = bvh.hit(&ray, 0.001, f64::INFINITY).map(|rec| rec.t);
+            assert_eq!(expected, actual);
+        }
+    }
+}
@@ -0,0 +1,145 @@
+use std::sync::Arc;
+
+use crate::{
+    aabb::Aabb,
+    hittable::{azimuth_u, first_in_range, pair_crossings, HitRecord, Hittable, Interval, Solid},
+    material::Material,
+    ray::Ray,
+    roots::solve_quadratic,
+    vec3::{unit_vector, Point, Vec3},
+};
+
+/// A cone around the y axis with its base disk of `radius` centred at `base`
+/// and its apex `height` above it.
+pub struct Cone {
+    base: Point,
+    radius: f64,
+    height: f64,
+    capped: bool,
+    material: Arc<dyn Material + Sync + Send>,
+}
+
+impl Cone {
+    pub fn new(
+        base: Point,
+        radius: f64,
+        height: f64,
+        capped: bool,
+        material: Arc<dyn Material + Sync + Send>,
+    ) -> Self {
+        Self {
+            base,
+            radius,
+            height,
+            capped,
+            material,
+        }
+    }
+
+    /// Every crossing of the surface along the ray's line, sorted by `t`.
+    pub(crate) fn crossings(&self, ray: &Ray) -> Vec<HitRecord> {
+        let o = ray.origin() - self.base;
+        let d = ray.direction();
+        let k2 = (self.radius / self.height).powi(2);
+        let w = self.height - o.y();
+        let mut hits = vec![];
+
+        // x^2 + z^2 = k^2 (height - y)^2
+        let a = d.x() * d.x() + d.z() * d.z() - k2 * d.y() * d.y();
+        let b = 2.0 * (o.x() * d.x() + o.z() * d.z()) + 2.0 * k2 * w * d.y();
+        let c = o.x() * o.x() + o.z() * o.z() - k2 * w * w;
+        for t in solve_quadratic(a, b, c) {
+            let local = o + t * d;
+            if local.y() < 0.0 || local.y() > self.height {
+                continue;
+            }
+            let outward_normal = unit_vector(Vec3::new(
+                local.x(),
+                k2 * (self.height - local.y()),
+                local.z(),
+            ));
+            let uv = (azimuth_u(local.x(), local.z()), local.y() / self.height);
+            let material = Some(Arc::clone(&self.material));
+            hits.push(HitRecord::new(ray.at(t), t, material, outward_normal, ray, uv));
+        }
+
+        if self.capped && d.y() != 0.0 {
+            let t = -o.y() / d.y();
+            let local = o + t * d;
+            let r2 = local.x() * local.x() + local.z() * local.z();
+            if r2 <= self.radius * self.radius {
+                let uv = (azimuth_u(local.x(), local.z()), f64::sqrt(r2) / self.radius);
+                let material = Some(Arc::clone(&self.material));
+                let outward_normal = Vec3::new(0.0, -1.0, 0.0);
+                hits.push(HitRecord::new(ray.at(t), t, material, outward_normal, ray, uv));
+            }
+        }
+
+        hits.sort_by(|a, b| a.t.total_cmp(&b.t));
+        hits
+    }
+}
+
+impl Hittable for Cone {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        first_in_range(self.crossings(ray), t_min, t_max)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let r = self.radius;
+        Some(Aabb::new(
+            self.base + Vec3::new(-r, 0.0, -r),
+            self.base + Vec3::new(r, self.height, r),
+        ))
+    }
+}
+
+impl Solid for Cone {
+    fn intervals(&self, ray: &Ray) -> Vec<Interval> {
+        if !self.capped {
+            return vec![];
+        }
+        pair_crossings(ray, self.crossings(ray))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        hittable::Hittable,
+        material::lambertian::Lambertian,
+        ray::Ray,
+        vec3::{Color, Point, Vec3},
+    };
+
+    use super::Cone;
+
+    #[test]
+    fn slanted_side() {
+        let cone = Cone::new(
+            Point::new(0.0, 0.0, 0.0),
+            1.0,
+            1.0,
+            true,
+            Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))),
+        );
+        // Halfway up the radius is 0.5.
+        let ray = Ray::new(Point::new(-5.0, 0.5, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        let rec = cone.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        assert!(f64::abs(rec.t - 4.5) < 1e-9);
+        let expected_normal = Vec3::new(-1.0, 1.0, 0.0) / f64::sqrt(2.0);
+        assert!((rec.normal - expected_normal).length() < 1e-9);
+
+        // Above the apex the double cone is clipped away.
+        let ray = Ray::new(Point::new(-5.0, 1.5, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        assert!(cone.hit(&ray, 0.001, f64::INFINITY).is_none());
+
+        let up = Ray::new(Point::new(0.2, -5.0, 0.0), Vec3::new(0.0, 1.0, 0.0));
+        let rec = cone.hit(&up, 0.001, f64::INFINITY).unwrap();
+        assert!(f64::abs(rec.t - 5.0) < 1e-9);
+        assert_eq!(rec.normal, Vec3::new(0.0, -1.0, 0.0));
+        assert_eq!(cone.crossings(&up).len(), 2);
+    }
+}
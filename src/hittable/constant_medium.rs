@@ -0,0 +1,114 @@
+use std::sync::Arc;
+
+use crate::{
+    aabb::Aabb,
+    hittable::{HitRecord, Hittable},
+    material::{isotropic::Isotropic, Material},
+    ray::Ray,
+    utils::random_double,
+    vec3::Color,
+};
+
+/// A volume of constant density bounded by a closed hittable, such as fog or
+/// smoke. Rays travelling through the boundary scatter at exponentially
+/// distributed distances.
+pub struct ConstantMedium {
+    boundary: Arc<dyn Hittable + Sync + Send>,
+    neg_inv_density: f64,
+    phase_function: Arc<dyn Material + Sync + Send>,
+}
+
+impl ConstantMedium {
+    pub fn new(boundary: Arc<dyn Hittable + Sync + Send>, density: f64, albedo: Color) -> Self {
+        Self::with_phase_function(boundary, density, Arc::new(Isotropic::new(albedo)))
+    }
+
+    pub fn with_phase_function(
+        boundary: Arc<dyn Hittable + Sync + Send>,
+        density: f64,
+        phase_function: Arc<dyn Material + Sync + Send>,
+    ) -> Self {
+        Self {
+            boundary,
+            neg_inv_density: -1.0 / density,
+            phase_function,
+        }
+    }
+}
+
+impl Hittable for ConstantMedium {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        // Find where the ray enters and exits the boundary, regardless of
+        // whether the origin is already inside it.
+        let entering = self.boundary.hit(ray, f64::NEG_INFINITY, f64::INFINITY)?;
+        let exiting = self
+            .boundary
+            .hit(ray, entering.t + 0.0001, f64::INFINITY)?;
+
+        let t_enter = f64::max(entering.t, t_min);
+        let t_exit = f64::min(exiting.t, t_max);
+        if t_enter >= t_exit {
+            return None;
+        }
+        let t_enter = f64::max(t_enter, 0.0);
+
+        let ray_length = ray.direction().length();
+        let distance_inside_boundary = (t_exit - t_enter) * ray_length;
+        let hit_distance = self.neg_inv_density * f64::ln(random_double(0.0, 1.0));
+        if hit_distance > distance_inside_boundary {
+            return None;
+        }
+
+        let t = t_enter + hit_distance / ray_length;
+        Some(HitRecord::in_volume(ray.at(t), t, Arc::clone(&self.phase_function)))
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.boundary.bounding_box()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        hittable::{sphere::Sphere, Hittable},
+        material::lambertian::Lambertian,
+        ray::Ray,
+        vec3::{Color, Point, Vec3},
+    };
+
+    use super::ConstantMedium;
+
+    fn unit_sphere_medium(density: f64) -> ConstantMedium {
+        let boundary = Arc::new(Sphere::new(
+            Point::new(0.0, 0.0, 0.0),
+            1.0,
+            Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))),
+        ));
+        ConstantMedium::new(boundary, density, Color::new(1.0, 1.0, 1.0))
+    }
+
+    #[test]
+    fn misses_outside_boundary() {
+        let medium = unit_sphere_medium(1000.0);
+        let ray = Ray::new(Point::new(0.0, 5.0, -5.0), Vec3::new(0.0, 0.0, 1.0));
+        assert!(medium.hit(&ray, 0.001, f64::INFINITY).is_none());
+    }
+
+    #[test]
+    fn scatters_inside_boundary() {
+        let medium = unit_sphere_medium(1000.0);
+        let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vec3::new(0.0, 0.0, 1.0));
+        for _ in 0..32 {
+            let rec = medium.hit(&ray, 0.001, f64::INFINITY).unwrap();
+            assert!(rec.t >= 4.0 && rec.t <= 6.0);
+        }
+
+        // Starting inside the volume scatters after the origin.
+        let ray = Ray::new(Point::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 1.0));
+        let rec = medium.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        assert!(rec.t >= 0.001 && rec.t <= 1.0);
+    }
+}
@@ -0,0 +1,247 @@
+use std::sync::Arc;
+
+use crate::{
+    aabb::{surrounding_box, Aabb},
+    hittable::{HitRecord, Hittable, Interval, Solid},
+    ray::Ray,
+    vec3::Point,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CsgOp {
+    Union,
+    Intersection,
+    Difference,
+}
+
+impl CsgOp {
+    fn inside(&self, in_left: bool, in_right: bool) -> bool {
+        match self {
+            CsgOp::Union => in_left || in_right,
+            CsgOp::Intersection => in_left && in_right,
+            CsgOp::Difference => in_left && !in_right,
+        }
+    }
+}
+
+/// Combines two solids with a boolean operation. Surfaces carved out by the
+/// right operand of a difference keep its material.
+pub struct Csg {
+    op: CsgOp,
+    left: Arc<dyn Solid + Sync + Send>,
+    right: Arc<dyn Solid + Sync + Send>,
+}
+
+impl Csg {
+    pub fn new(
+        op: CsgOp,
+        left: Arc<dyn Solid + Sync + Send>,
+        right: Arc<dyn Solid + Sync + Send>,
+    ) -> Self {
+        Self { op, left, right }
+    }
+
+    pub fn union(left: Arc<dyn Solid + Sync + Send>, right: Arc<dyn Solid + Sync + Send>) -> Self {
+        Self::new(CsgOp::Union, left, right)
+    }
+
+    pub fn intersection(
+        left: Arc<dyn Solid + Sync + Send>,
+        right: Arc<dyn Solid + Sync + Send>,
+    ) -> Self {
+        Self::new(CsgOp::Intersection, left, right)
+    }
+
+    pub fn difference(
+        left: Arc<dyn Solid + Sync + Send>,
+        right: Arc<dyn Solid + Sync + Send>,
+    ) -> Self {
+        Self::new(CsgOp::Difference, left, right)
+    }
+}
+
+impl Solid for Csg {
+    fn intervals(&self, ray: &Ray) -> Vec<Interval> {
+        // Sweep over the boundaries of both operands in order, tracking
+        // whether the line is inside each, and keep the boundaries where the
+        // combined inside state flips.
+        let mut events: Vec<(bool, bool, HitRecord)> = vec![];
+        for (is_left, intervals) in [(true, self.left.intervals(ray)), (false, self.right.intervals(ray))] {
+            for interval in intervals {
+                events.push((is_left, true, interval.enter));
+                events.push((is_left, false, interval.exit));
+            }
+        }
+        events.sort_by(|a, b| a.2.t.total_cmp(&b.2.t));
+
+        let (mut in_left, mut in_right) = (false, false);
+        let mut inside = false;
+        let mut enter: Option<HitRecord> = None;
+        let mut result = vec![];
+        for (is_left, entering, rec) in events {
+            if is_left {
+                in_left = entering;
+            } else {
+                in_right = entering;
+            }
+            let now_inside = self.op.inside(in_left, in_right);
+            if now_inside == inside {
+                continue;
+            }
+            inside = now_inside;
+
+            let mut rec = rec;
+            rec.front_face = now_inside;
+            if now_inside {
+                enter = Some(rec);
+            } else if let Some(enter) = enter.take() {
+                result.push(Interval { enter, exit: rec });
+            }
+        }
+        result
+    }
+}
+
+impl Hittable for Csg {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        self.intervals(ray)
+            .into_iter()
+            .flat_map(|interval| [interval.enter, interval.exit])
+            .find(|rec| rec.t >= t_min && rec.t <= t_max)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let left = self.left.bounding_box();
+        let right = self.right.bounding_box();
+        match self.op {
+            CsgOp::Union => Some(surrounding_box(left?, right?)),
+            CsgOp::Intersection => match (left, right) {
+                (Some(l), Some(r)) => {
+                    let (lo, hi) = (l.min(), l.max());
+                    let (r_lo, r_hi) = (r.min(), r.max());
+                    let min = Point::new(lo.x().max(r_lo.x()), lo.y().max(r_lo.y()), lo.z().max(r_lo.z()));
+                    let max = Point::new(hi.x().min(r_hi.x()), hi.y().min(r_hi.y()), hi.z().min(r_hi.z()));
+                    if (0..3).any(|axis| min[axis] > max[axis]) {
+                        // Disjoint operands intersect in nothing; any box will do.
+                        return Some(l);
+                    }
+                    Some(Aabb::new(min, max))
+                }
+                (l, r) => l.or(r),
+            },
+            CsgOp::Difference => left,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        hittable::{cuboid::Cuboid, cylinder::Cylinder, sphere::Sphere, transformed::Transformed, Hittable, Solid},
+        material::lambertian::Lambertian,
+        ray::Ray,
+        transform::Transform,
+        vec3::{Color, Point, Vec3},
+    };
+
+    use super::Csg;
+
+    fn sphere(x: f64) -> Arc<Sphere> {
+        Arc::new(Sphere::new(
+            Point::new(x, 0.0, 0.0),
+            1.0,
+            Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))),
+        ))
+    }
+
+    fn along_x() -> Ray {
+        Ray::new(Point::new(-5.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0))
+    }
+
+    fn spans(solid: &dyn Solid) -> Vec<(f64, f64)> {
+        solid
+            .intervals(&along_x())
+            .iter()
+            .map(|i| (i.enter.t, i.exit.t))
+            .collect()
+    }
+
+    #[test]
+    fn boolean_operations() {
+        // Spheres spanning x in [-1, 1] and [0, 2], i.e. t in [4, 6] and [5, 7].
+        assert_eq!(spans(&Csg::union(sphere(0.0), sphere(1.0))), vec![(4.0, 7.0)]);
+        assert_eq!(spans(&Csg::intersection(sphere(0.0), sphere(1.0))), vec![(5.0, 6.0)]);
+        assert_eq!(spans(&Csg::difference(sphere(0.0), sphere(1.0))), vec![(4.0, 5.0)]);
+        assert_eq!(spans(&Csg::difference(sphere(1.0), sphere(0.0))), vec![(6.0, 7.0)]);
+
+        // Disjoint union keeps both pieces; a difference can split a solid.
+        assert_eq!(spans(&Csg::union(sphere(0.0), sphere(3.0))), vec![(4.0, 6.0), (7.0, 9.0)]);
+        let block = Arc::new(Cuboid::new(
+            Point::new(-3.0, -0.5, -0.5),
+            Point::new(3.0, 0.5, 0.5),
+            Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))),
+        ));
+        assert_eq!(spans(&Csg::difference(block, sphere(0.0))), vec![(2.0, 4.0), (6.0, 8.0)]);
+    }
+
+    #[test]
+    fn carved_surface_faces_outwards() {
+        let carved = Csg::difference(sphere(1.0), sphere(0.0));
+        let rec = carved.hit(&along_x(), 0.001, f64::INFINITY).unwrap();
+        // The entry is the inside of the removed sphere's far side.
+        assert_eq!(rec.t, 6.0);
+        assert!(rec.front_face);
+        assert_eq!(rec.normal, Vec3::new(-1.0, 0.0, 0.0));
+
+        // From inside the result the first hit is the exit.
+        let inside = Ray::new(Point::new(1.5, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        let rec = carved.hit(&inside, 0.001, f64::INFINITY).unwrap();
+        assert_eq!(rec.t, 0.5);
+        assert!(!rec.front_face);
+    }
+
+    #[test]
+    fn nested_operations() {
+        let lens = Arc::new(Csg::intersection(sphere(0.0), sphere(1.0)));
+        let result = Csg::union(lens, sphere(3.0));
+        assert_eq!(spans(&result), vec![(5.0, 6.0), (7.0, 9.0)]);
+    }
+
+    #[test]
+    fn carves_with_transformed_solids() {
+        // A block drilled right through along x by a capped cylinder laid on
+        // its side.
+        let block = Arc::new(Cuboid::new(
+            Point::new(-1.0, -1.0, -1.0),
+            Point::new(1.0, 1.0, 1.0),
+            Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))),
+        ));
+        let drill = Arc::new(Transformed::new(
+            Arc::new(Cylinder::new(
+                Point::new(0.0, -2.0, 0.0),
+                0.25,
+                4.0,
+                true,
+                Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))),
+            )),
+            Transform::rotate_z(90.0),
+        ));
+        let drilled = Csg::difference(block, drill);
+
+        // Down the hole there is nothing left.
+        assert!(spans(&drilled).is_empty());
+
+        // Across it the block is split around the hole's walls.
+        let across = Ray::new(Point::new(0.0, -5.0, 0.0), Vec3::new(0.0, 1.0, 0.0));
+        let intervals = drilled.intervals(&across);
+        let expected = [(4.0, 4.75), (5.25, 6.0)];
+        assert_eq!(intervals.len(), 2);
+        for (interval, (enter, exit)) in intervals.iter().zip(expected) {
+            assert!(f64::abs(interval.enter.t - enter) < 1e-9 && f64::abs(interval.exit.t - exit) < 1e-9);
+        }
+        // The wall of the hole faces into it, against the ray.
+        assert!((intervals[0].exit.normal - Vec3::new(0.0, -1.0, 0.0)).length() < 1e-9);
+    }
+}
@@ -0,0 +1,108 @@
+use std::sync::Arc;
+
+use crate::{
+    aabb::Aabb,
+    hittable::{pair_crossings, quad::Quad, HitRecord, Hittable, HittableList, Interval, Solid},
+    material::Material,
+    ray::Ray,
+    vec3::{Point, Vec3},
+};
+
+/// An axis-aligned box made of six outward facing quads.
+pub struct Cuboid {
+    sides: HittableList,
+    bbox: Aabb,
+}
+
+impl Cuboid {
+    /// The box spanning the opposite corners `a` and `b`.
+    pub fn new(a: Point, b: Point, material: Arc<dyn Material + Sync + Send>) -> Self {
+        let bbox = Aabb::new(a, b);
+        let (min, max) = (bbox.min(), bbox.max());
+        let dx = Vec3::new(max.x() - min.x(), 0.0, 0.0);
+        let dy = Vec3::new(0.0, max.y() - min.y(), 0.0);
+        let dz = Vec3::new(0.0, 0.0, max.z() - min.z());
+
+        let mut sides = HittableList::new();
+        let mut add = |q: Point, u: Vec3, v: Vec3| {
+            sides.add(Arc::new(Quad::new(q, u, v, Arc::clone(&material))));
+        };
+        add(Point::new(min.x(), min.y(), max.z()), dx, dy); // front
+        add(Point::new(max.x(), min.y(), max.z()), -dz, dy); // right
+        add(Point::new(max.x(), min.y(), min.z()), -dx, dy); // back
+        add(Point::new(min.x(), min.y(), min.z()), dz, dy); // left
+        add(Point::new(min.x(), max.y(), max.z()), dx, -dz); // top
+        add(Point::new(min.x(), min.y(), min.z()), dx, dz); // bottom
+
+        Self { sides, bbox }
+    }
+}
+
+impl Hittable for Cuboid {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        self.sides.hit(ray, t_min, t_max)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(self.bbox)
+    }
+
+    fn pdf_value(&self, origin: Point, direction: Vec3) -> f64 {
+        self.sides.pdf_value(origin, direction)
+    }
+
+    fn random(&self, origin: Point) -> Vec3 {
+        self.sides.random(origin)
+    }
+}
+
+impl Solid for Cuboid {
+    fn intervals(&self, ray: &Ray) -> Vec<Interval> {
+        // Each side is planar, so it crosses the line at most once.
+        let mut crossings = self
+            .sides
+            .objects
+            .iter()
+            .filter_map(|side| side.hit(ray, f64::NEG_INFINITY, f64::INFINITY))
+            .collect::<Vec<_>>();
+        crossings.sort_by(|a, b| a.t.total_cmp(&b.t));
+        pair_crossings(ray, crossings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        hittable::Hittable,
+        material::lambertian::Lambertian,
+        ray::Ray,
+        vec3::{dot, Color, Point, Vec3},
+    };
+
+    use super::Cuboid;
+
+    #[test]
+    fn faces_point_outwards() {
+        let cuboid = Cuboid::new(
+            Point::new(1.0, 1.0, 1.0),
+            Point::new(-1.0, -1.0, -1.0),
+            Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))),
+        );
+        let axes = [
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            Vec3::new(0.0, 0.0, 1.0),
+        ];
+        for axis in axes {
+            for dir in [axis, -axis] {
+                let ray = Ray::new(Point::new(0.1, 0.2, 0.3) + 5.0 * dir, -dir);
+                let rec = cuboid.hit(&ray, 0.001, f64::INFINITY).unwrap();
+                assert!(rec.front_face, "face {dir:?} points inwards");
+                assert!(dot(rec.normal, dir) > 0.999);
+                assert!((0.0..=1.0).contains(&rec.u) && (0.0..=1.0).contains(&rec.v));
+            }
+        }
+    }
+}
@@ -0,0 +1,289 @@
+use std::sync::Arc;
+
+use crate::{
+    aabb::{surrounding_box, Aabb},
+    hittable::{HitRecord, Hittable},
+    material::Material,
+    onb::Onb,
+    ray::Ray,
+    vec3::{cross, dot, unit_vector, Point, Vec3},
+};
+
+/// How the width of a `Curve` is oriented.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CurveType {
+    /// A flat strip that always faces the ray, shaded as flat. Cheapest, for
+    /// fur and distant hair.
+    Flat,
+    /// A flat strip facing the ray but shaded as a tube, for hair seen up
+    /// close.
+    Cylinder,
+    /// A strip with a fixed orientation, given by its normal at each end,
+    /// for grass blades and the like.
+    Ribbon { normals: (Vec3, Vec3) },
+}
+
+/// Cubic Bezier curve swept with a width varying linearly from one end to
+/// the other. Texture `u` runs along the curve and `v` across it.
+///
+/// Rays are tested by recursively splitting the curve in a frame looking down
+/// the ray until each piece is nearly straight, after Nakamae et al.
+pub struct Curve {
+    control_points: [Point; 4],
+    widths: (f64, f64),
+    kind: CurveType,
+    material: Arc<dyn Material + Sync + Send>,
+    max_depth: usize,
+}
+
+fn lerp<T>(t: f64, a: T, b: T) -> T
+where
+    T: std::ops::Mul<f64, Output = T> + std::ops::Add<Output = T>,
+{
+    a * (1.0 - t) + b * t
+}
+
+/// Point on the curve at `t` and its derivative.
+fn evaluate(cp: &[Point; 4], t: f64) -> (Point, Vec3) {
+    let a = [lerp(t, cp[0], cp[1]), lerp(t, cp[1], cp[2]), lerp(t, cp[2], cp[3])];
+    let b = [lerp(t, a[0], a[1]), lerp(t, a[1], a[2])];
+    let derivative = 3.0 * (b[1] - b[0]);
+    // Coincident control points give no derivative at the ends.
+    let derivative = if derivative.near_zero() { cp[3] - cp[0] } else { derivative };
+    (lerp(t, b[0], b[1]), derivative)
+}
+
+fn split(cp: &[Point; 4]) -> ([Point; 4], [Point; 4]) {
+    let a = [0.5 * (cp[0] + cp[1]), 0.5 * (cp[1] + cp[2]), 0.5 * (cp[2] + cp[3])];
+    let b = [0.5 * (a[0] + a[1]), 0.5 * (a[1] + a[2])];
+    let mid = 0.5 * (b[0] + b[1]);
+    ([cp[0], a[0], b[0], mid], [mid, b[1], a[2], cp[3]])
+}
+
+impl Curve {
+    /// `widths` are the full widths at the start and end of the curve.
+    pub fn new(
+        control_points: [Point; 4],
+        widths: (f64, f64),
+        kind: CurveType,
+        material: Arc<dyn Material + Sync + Send>,
+    ) -> Self {
+        // Split until the pieces deviate from straight lines by no more than
+        // a twentieth of the width.
+        let curvature = (0..2)
+            .map(|i| (control_points[i] - 2.0 * control_points[i + 1] + control_points[i + 2]).length())
+            .fold(0.0, f64::max);
+        let tolerance = f64::max(widths.0, widths.1) / 20.0;
+        let depth = f64::log(f64::sqrt(2.0) * 6.0 * curvature / (8.0 * tolerance), 4.0);
+        let max_depth = if depth.is_finite() { depth.round().clamp(0.0, 10.0) as usize } else { 0 };
+
+        Self {
+            control_points,
+            widths,
+            kind,
+            material,
+            max_depth,
+        }
+    }
+
+    fn width(&self, u: f64) -> f64 {
+        lerp(u, self.widths.0, self.widths.1)
+    }
+
+    fn recursive_hit(
+        &self,
+        ray: &Ray,
+        frame: &Onb,
+        cp: &[Point; 4],
+        (u0, u1): (f64, f64),
+        depth: usize,
+        (t_min, t_max): (f64, f64),
+    ) -> Option<HitRecord> {
+        // The ray runs down the z axis of `cp` from the origin, so the piece
+        // can only be hit if its widened bounds straddle the axis.
+        let half_width = 0.5 * f64::max(self.width(u0), self.width(u1));
+        let lo = cp.iter().fold(Point::new(f64::INFINITY, f64::INFINITY, f64::INFINITY), |acc, p| {
+            Point::new(acc.x().min(p.x()), acc.y().min(p.y()), acc.z().min(p.z()))
+        });
+        let hi = cp.iter().fold(Point::new(f64::NEG_INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY), |acc, p| {
+            Point::new(acc.x().max(p.x()), acc.y().max(p.y()), acc.z().max(p.z()))
+        });
+        let ray_length = ray.direction().length();
+        if lo.x() - half_width > 0.0
+            || hi.x() + half_width < 0.0
+            || lo.y() - half_width > 0.0
+            || hi.y() + half_width < 0.0
+            || hi.z() + half_width < t_min * ray_length
+            || lo.z() - half_width > t_max * ray_length
+        {
+            return None;
+        }
+
+        if depth > 0 {
+            let (left, right) = split(cp);
+            let u_mid = 0.5 * (u0 + u1);
+            let near = self.recursive_hit(ray, frame, &left, (u0, u_mid), depth - 1, (t_min, t_max));
+            let t_max = near.as_ref().map_or(t_max, |rec| rec.t);
+            let far = self.recursive_hit(ray, frame, &right, (u_mid, u1), depth - 1, (t_min, t_max));
+            return far.or(near);
+        }
+
+        // The ray must pass between the planes through each end of the piece,
+        // perpendicular to the curve there, so that pieces meet without gaps
+        // or overlaps.
+        let dot_xy = |a: Vec3, b: Vec3| a.x() * b.x() + a.y() * b.y();
+        if dot_xy(-cp[0], cp[1] - cp[0]) < 0.0 || dot_xy(-cp[3], cp[2] - cp[3]) < 0.0 {
+            return None;
+        }
+        let segment = cp[3] - cp[0];
+        let length_squared = dot_xy(segment, segment);
+        if length_squared == 0.0 {
+            return None;
+        }
+        let w = f64::clamp(dot_xy(-cp[0], segment) / length_squared, 0.0, 1.0);
+        let (center, _) = evaluate(cp, w);
+        let u = lerp(w, u0, u1);
+
+        let (tangent, normal) = self.frame_at(u, ray);
+        let mut width = self.width(u);
+        if let CurveType::Ribbon { .. } = self.kind {
+            width *= f64::abs(dot(normal, unit_vector(ray.direction())));
+        }
+        let distance_squared = center.x() * center.x() + center.y() * center.y();
+        if distance_squared >= 0.25 * width * width {
+            return None;
+        }
+        let t = center.z() / ray_length;
+        if t < t_min || t > t_max {
+            return None;
+        }
+
+        // Offset of the ray from the centre line, as a fraction of the half
+        // width, signed along the side direction cross(normal, tangent).
+        let offset = -center.x() * frame.u() - center.y() * frame.v();
+        let side = unit_vector(cross(normal, tangent));
+        let h = f64::clamp(2.0 * dot(offset, side) / width, -1.0, 1.0);
+        let outward_normal = match self.kind {
+            CurveType::Cylinder => f64::sqrt(1.0 - h * h) * normal + h * side,
+            _ => normal,
+        };
+
+        let material = Some(Arc::clone(&self.material));
+        let rec = HitRecord::new(ray.at(t), t, material, outward_normal, ray, (u, 0.5 + 0.5 * h));
+        Some(rec.with_tangent(tangent))
+    }
+
+    /// Unit tangent at `u`, and the normal of the strip there. Strips facing
+    /// the ray take the direction back along the ray, made perpendicular to
+    /// the tangent.
+    fn frame_at(&self, u: f64, ray: &Ray) -> (Vec3, Vec3) {
+        let tangent = unit_vector(evaluate(&self.control_points, u).1);
+        let facing = match self.kind {
+            CurveType::Ribbon { normals: (n0, n1) } => lerp(u, n0, n1),
+            _ => -ray.direction(),
+        };
+        let normal = facing - dot(facing, tangent) * tangent;
+        let normal = if normal.near_zero() { Onb::build_from_w(tangent).u() } else { unit_vector(normal) };
+        (tangent, normal)
+    }
+}
+
+impl Hittable for Curve {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let frame = Onb::build_from_w(ray.direction());
+        let origin = ray.origin();
+        let cp = self.control_points.map(|p| {
+            let d = p - origin;
+            Point::new(dot(d, frame.u()), dot(d, frame.v()), dot(d, frame.w()))
+        });
+        self.recursive_hit(ray, &frame, &cp, (0.0, 1.0), self.max_depth, (t_min, t_max))
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        // The curve lies within the hull of its control points.
+        let r = 0.5 * f64::max(self.widths.0, self.widths.1);
+        let pad = Vec3::new(r, r, r);
+        let cp = &self.control_points;
+        let bbox = cp[1..].iter().fold(Aabb::new(cp[0] - pad, cp[0] + pad), |acc, &p| {
+            surrounding_box(acc, Aabb::new(p - pad, p + pad))
+        });
+        Some(bbox)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        hittable::Hittable,
+        material::lambertian::Lambertian,
+        ray::Ray,
+        vec3::{Color, Point, Vec3},
+    };
+
+    use super::{Curve, CurveType};
+
+    fn straight(widths: (f64, f64), kind: CurveType) -> Curve {
+        let cp = [-1.0, -1.0 / 3.0, 1.0 / 3.0, 1.0].map(|x| Point::new(x, 0.0, 0.0));
+        Curve::new(cp, widths, kind, Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))))
+    }
+
+    #[test]
+    fn cylinder_hit() {
+        let curve = straight((0.2, 0.2), CurveType::Cylinder);
+        let ray = Ray::new(Point::new(0.5, 0.05, 5.0), Vec3::new(0.0, 0.0, -1.0));
+        let rec = curve.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        assert!(f64::abs(rec.t - 5.0) < 1e-9);
+        assert!(f64::abs(rec.u - 0.75) < 1e-9);
+        assert!((rec.tangent - Vec3::new(1.0, 0.0, 0.0)).length() < 1e-9);
+        // Half way to the edge, the tube normal leans 30 degrees sideways.
+        assert!((rec.normal - Vec3::new(0.0, 0.5, f64::sqrt(0.75))).length() < 1e-9);
+        assert!(rec.front_face);
+
+        let beside = Ray::new(Point::new(0.5, 0.15, 5.0), Vec3::new(0.0, 0.0, -1.0));
+        assert!(curve.hit(&beside, 0.001, f64::INFINITY).is_none());
+        let beyond = Ray::new(Point::new(1.05, 0.0, 5.0), Vec3::new(0.0, 0.0, -1.0));
+        assert!(curve.hit(&beyond, 0.001, f64::INFINITY).is_none());
+    }
+
+    #[test]
+    fn tapered_width() {
+        let curve = straight((0.0, 0.4), CurveType::Flat);
+        let thin_end = Ray::new(Point::new(-0.9, 0.05, 5.0), Vec3::new(0.0, 0.0, -1.0));
+        assert!(curve.hit(&thin_end, 0.001, f64::INFINITY).is_none());
+        let thick_end = Ray::new(Point::new(0.9, 0.15, 5.0), Vec3::new(0.0, 0.0, -1.0));
+        let rec = curve.hit(&thick_end, 0.001, f64::INFINITY).unwrap();
+        assert_eq!(rec.normal, Vec3::new(0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn ribbon_is_invisible_edge_on() {
+        let up = Vec3::new(0.0, 1.0, 0.0);
+        let curve = straight((0.2, 0.2), CurveType::Ribbon { normals: (up, up) });
+        let above = Ray::new(Point::new(0.0, 5.0, 0.05), Vec3::new(0.0, -1.0, 0.0));
+        let rec = curve.hit(&above, 0.001, f64::INFINITY).unwrap();
+        assert!((rec.normal - up).length() < 1e-9);
+        let edge_on = Ray::new(Point::new(0.0, 0.0, 5.0), Vec3::new(0.0, 0.0, -1.0));
+        assert!(curve.hit(&edge_on, 0.001, f64::INFINITY).is_none());
+    }
+
+    #[test]
+    fn bent_curve_is_hit_along_its_length() {
+        let material = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+        let cp = [
+            Point::new(0.0, 0.0, 0.0),
+            Point::new(0.0, 2.0, 0.0),
+            Point::new(2.0, 2.0, 0.0),
+            Point::new(2.0, 0.0, 0.0),
+        ];
+        let curve = Curve::new(cp, (0.05, 0.05), CurveType::Flat, material);
+        for i in 0..=20 {
+            let u = i as f64 / 20.0;
+            let (p, _) = super::evaluate(&cp, u);
+            let ray = Ray::new(p + Vec3::new(0.0, 0.0, 3.0), Vec3::new(0.0, 0.0, -1.0));
+            let rec = curve.hit(&ray, 0.001, f64::INFINITY).unwrap();
+            assert!(f64::abs(rec.u - u) < 0.02, "{} != {u}", rec.u);
+        }
+    }
+}
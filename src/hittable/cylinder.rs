@@ -0,0 +1,160 @@
+use std::sync::Arc;
+
+use crate::{
+    aabb::Aabb,
+    hittable::{azimuth_u, first_in_range, pair_crossings, HitRecord, Hittable, Interval, Solid},
+    material::Material,
+    ray::Ray,
+    roots::solve_quadratic,
+    vec3::{Point, Vec3},
+};
+
+/// A cylinder around the y axis, standing on the disk of `radius` centred at
+/// `base`. Use a `Transformed` to orient it differently.
+pub struct Cylinder {
+    base: Point,
+    radius: f64,
+    height: f64,
+    capped: bool,
+    material: Arc<dyn Material + Sync + Send>,
+}
+
+impl Cylinder {
+    pub fn new(
+        base: Point,
+        radius: f64,
+        height: f64,
+        capped: bool,
+        material: Arc<dyn Material + Sync + Send>,
+    ) -> Self {
+        Self {
+            base,
+            radius,
+            height,
+            capped,
+            material,
+        }
+    }
+
+    /// Every crossing of the surface along the ray's line, sorted by `t`.
+    pub(crate) fn crossings(&self, ray: &Ray) -> Vec<HitRecord> {
+        let o = ray.origin() - self.base;
+        let d = ray.direction();
+        let mut hits = vec![];
+
+        let a = d.x() * d.x() + d.z() * d.z();
+        let b = 2.0 * (o.x() * d.x() + o.z() * d.z());
+        let c = o.x() * o.x() + o.z() * o.z() - self.radius * self.radius;
+        for t in solve_quadratic(a, b, c) {
+            let local = o + t * d;
+            if local.y() < 0.0 || local.y() > self.height {
+                continue;
+            }
+            let outward_normal = Vec3::new(local.x(), 0.0, local.z()) / self.radius;
+            let uv = (azimuth_u(local.x(), local.z()), local.y() / self.height);
+            let material = Some(Arc::clone(&self.material));
+            hits.push(HitRecord::new(ray.at(t), t, material, outward_normal, ray, uv));
+        }
+
+        if self.capped && d.y() != 0.0 {
+            for (y, normal_y) in [(0.0, -1.0), (self.height, 1.0)] {
+                let t = (y - o.y()) / d.y();
+                let local = o + t * d;
+                let r2 = local.x() * local.x() + local.z() * local.z();
+                if r2 > self.radius * self.radius {
+                    continue;
+                }
+                let outward_normal = Vec3::new(0.0, normal_y, 0.0);
+                let uv = (azimuth_u(local.x(), local.z()), f64::sqrt(r2) / self.radius);
+                let material = Some(Arc::clone(&self.material));
+                hits.push(HitRecord::new(ray.at(t), t, material, outward_normal, ray, uv));
+            }
+        }
+
+        hits.sort_by(|a, b| a.t.total_cmp(&b.t));
+        hits
+    }
+}
+
+impl Hittable for Cylinder {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        first_in_range(self.crossings(ray), t_min, t_max)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let r = self.radius;
+        Some(Aabb::new(
+            self.base + Vec3::new(-r, 0.0, -r),
+            self.base + Vec3::new(r, self.height, r),
+        ))
+    }
+}
+
+impl Solid for Cylinder {
+    fn intervals(&self, ray: &Ray) -> Vec<Interval> {
+        if !self.capped {
+            return vec![];
+        }
+        pair_crossings(ray, self.crossings(ray))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        hittable::{Hittable, Solid},
+        material::lambertian::Lambertian,
+        ray::Ray,
+        vec3::{Color, Point, Vec3},
+    };
+
+    use super::Cylinder;
+
+    fn cylinder(capped: bool) -> Cylinder {
+        Cylinder::new(
+            Point::new(0.0, 0.0, 0.0),
+            1.0,
+            2.0,
+            capped,
+            Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))),
+        )
+    }
+
+    #[test]
+    fn side_and_caps() {
+        let side = Ray::new(Point::new(-5.0, 1.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        let rec = cylinder(false).hit(&side, 0.001, f64::INFINITY).unwrap();
+        assert!(f64::abs(rec.t - 4.0) < 1e-9);
+        assert!((rec.normal - Vec3::new(-1.0, 0.0, 0.0)).length() < 1e-9);
+        assert!(f64::abs(rec.v - 0.5) < 1e-9);
+
+        let down = Ray::new(Point::new(0.5, 5.0, 0.0), Vec3::new(0.0, -1.0, 0.0));
+        assert!(cylinder(false).hit(&down, 0.001, f64::INFINITY).is_none());
+        let rec = cylinder(true).hit(&down, 0.001, f64::INFINITY).unwrap();
+        assert!(f64::abs(rec.t - 3.0) < 1e-9);
+        assert_eq!(rec.normal, Vec3::new(0.0, 1.0, 0.0));
+        assert_eq!(cylinder(true).crossings(&down).len(), 2);
+
+        // Uncapped cylinders are open tubes: looking in from the side at an
+        // angle hits the inside of the far wall.
+        let angled = Ray::new(Point::new(0.0, 3.0, 0.0), Vec3::new(1.0, -1.0, 0.0));
+        let rec = cylinder(false).hit(&angled, 0.001, f64::INFINITY).unwrap();
+        assert!(!rec.front_face);
+    }
+
+    #[test]
+    fn intervals_need_caps() {
+        let through = Ray::new(Point::new(-5.0, 1.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        let intervals = cylinder(true).intervals(&through);
+        assert_eq!(intervals.len(), 1);
+        assert!(f64::abs(intervals[0].enter.t - 4.0) < 1e-9);
+        assert!(f64::abs(intervals[0].exit.t - 6.0) < 1e-9);
+        assert!(cylinder(false).intervals(&through).is_empty());
+
+        // Touching the side only along a tangent never gets inside.
+        let grazing = Ray::new(Point::new(1.0, 0.5, -5.0), Vec3::new(0.0, 0.1, 1.0));
+        assert!(cylinder(true).intervals(&grazing).is_empty());
+    }
+}
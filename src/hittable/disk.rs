@@ -0,0 +1,127 @@
+use std::{f64::consts::PI, sync::Arc};
+
+use crate::{
+    aabb::Aabb,
+    hittable::{HitRecord, Hittable},
+    material::Material,
+    onb::Onb,
+    ray::Ray,
+    utils::random_double,
+    vec3::{dot, Point, Vec3},
+};
+
+/// A flat disk facing along `normal`. The texture coordinate `u` runs around
+/// the disk and `v` outwards from the centre.
+pub struct Disk {
+    center: Point,
+    radius: f64,
+    material: Arc<dyn Material + Sync + Send>,
+    uvw: Onb,
+}
+
+impl Disk {
+    pub fn new(
+        center: Point,
+        normal: Vec3,
+        radius: f64,
+        material: Arc<dyn Material + Sync + Send>,
+    ) -> Self {
+        Self {
+            center,
+            radius,
+            material,
+            uvw: Onb::build_from_w(normal),
+        }
+    }
+
+    pub fn area(&self) -> f64 {
+        PI * self.radius * self.radius
+    }
+}
+
+impl Hittable for Disk {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let normal = self.uvw.w();
+        let denom = dot(normal, ray.direction());
+        if f64::abs(denom) < 1e-8 {
+            return None;
+        }
+
+        let t = dot(normal, self.center - ray.origin()) / denom;
+        if t < t_min || t > t_max {
+            return None;
+        }
+
+        let p = ray.at(t);
+        let offset = p - self.center;
+        let r = offset.length();
+        if r > self.radius {
+            return None;
+        }
+
+        let phi = f64::atan2(dot(offset, self.uvw.v()), dot(offset, self.uvw.u())) + PI;
+        let uv = (phi / (2.0 * PI), r / self.radius);
+        let material = Some(Arc::clone(&self.material));
+        Some(HitRecord::new(p, t, material, normal, ray, uv))
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        // Extent of a disk along each axis is radius * sin(angle to normal).
+        let n = self.uvw.w();
+        let extent = |a: f64| self.radius * f64::sqrt(f64::max(0.0, 1.0 - a * a)) + 1e-4;
+        let e = Vec3::new(extent(n.x()), extent(n.y()), extent(n.z()));
+        Some(Aabb::new(self.center - e, self.center + e))
+    }
+
+    fn pdf_value(&self, origin: Point, direction: Vec3) -> f64 {
+        let Some(rec) = self.hit(&Ray::new(origin, direction), 0.001, f64::INFINITY) else {
+            return 0.0;
+        };
+        let distance_squared = rec.t * rec.t * direction.length_squared();
+        let cosine = f64::abs(dot(direction, rec.normal) / direction.length());
+        distance_squared / (cosine * self.area())
+    }
+
+    fn random(&self, origin: Point) -> Vec3 {
+        let r = self.radius * f64::sqrt(random_double(0.0, 1.0));
+        let phi = 2.0 * PI * random_double(0.0, 1.0);
+        let p = self.center + self.uvw.local(r * f64::cos(phi), r * f64::sin(phi), 0.0);
+        p - origin
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        hittable::Hittable,
+        material::lambertian::Lambertian,
+        ray::Ray,
+        vec3::{Color, Point, Vec3},
+    };
+
+    use super::Disk;
+
+    #[test]
+    fn hit_inside_radius() {
+        let disk = Disk::new(
+            Point::new(0.0, 1.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            2.0,
+            Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))),
+        );
+        let ray = Ray::new(Point::new(1.0, 5.0, 0.0), Vec3::new(0.0, -1.0, 0.0));
+        let rec = disk.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        assert_eq!(rec.t, 4.0);
+        assert_eq!(rec.v, 0.5);
+        assert!(rec.front_face);
+
+        let miss = Ray::new(Point::new(2.5, 5.0, 0.0), Vec3::new(0.0, -1.0, 0.0));
+        assert!(disk.hit(&miss, 0.001, f64::INFINITY).is_none());
+
+        let bbox = disk.bounding_box().unwrap();
+        assert!(f64::abs(bbox.max().x() - 2.0) < 1e-3);
+        assert!(f64::abs(bbox.max().y() - 1.0) < 1e-3);
+    }
+}
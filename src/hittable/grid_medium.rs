@@ -0,0 +1,292 @@
+use std::{
+    io::{self, BufRead, Read},
+    sync::Arc,
+};
+
+use crate::{
+    aabb::Aabb,
+    hittable::{HitRecord, Hittable},
+    material::Material,
+    ray::Ray,
+    utils::{clamp, random_double},
+    vec3::Point,
+};
+
+/// A regular grid of scalar samples (density, temperature, ...) stretched
+/// over an axis-aligned box in world space. Samples are stored with x varying
+/// fastest, then y, then z, and are trilinearly interpolated between cell
+/// centres.
+pub struct VoxelGrid {
+    nx: usize,
+    ny: usize,
+    nz: usize,
+    data: Vec<f64>,
+    bounds: Aabb,
+    max_value: f64,
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+impl VoxelGrid {
+    pub fn new(nx: usize, ny: usize, nz: usize, data: Vec<f64>, bounds: Aabb) -> Self {
+        assert!(nx > 0 && ny > 0 && nz > 0, "grid dimensions must be non-zero");
+        assert_eq!(data.len(), nx * ny * nz, "grid data does not match dimensions");
+        let max_value = data.iter().cloned().fold(0.0, f64::max);
+        Self {
+            nx,
+            ny,
+            nz,
+            data,
+            bounds,
+            max_value,
+        }
+    }
+
+    /// Reads the text grid format: the dimensions `nx ny nz` followed by
+    /// `nx * ny * nz` whitespace separated values. Anything after a `#` on a
+    /// line is a comment.
+    pub fn from_text<R: BufRead>(reader: R, bounds: Aabb) -> io::Result<Self> {
+        let mut tokens = vec![];
+        for line in reader.lines() {
+            let line = line?;
+            let line = line.split('#').next().unwrap_or("");
+            tokens.extend(line.split_whitespace().map(String::from));
+        }
+
+        let mut tokens = tokens.into_iter();
+        let mut dimension = || -> io::Result<usize> {
+            let token = tokens
+                .next()
+                .ok_or_else(|| invalid_data("missing grid dimensions".to_string()))?;
+            token
+                .parse()
+                .map_err(|_| invalid_data(format!("invalid grid dimension '{token}'")))
+        };
+        let (nx, ny, nz) = (dimension()?, dimension()?, dimension()?);
+
+        let data = tokens
+            .map(|token| {
+                token
+                    .parse::<f64>()
+                    .map_err(|_| invalid_data(format!("invalid grid value '{token}'")))
+            })
+            .collect::<io::Result<Vec<f64>>>()?;
+        Self::checked(nx, ny, nz, data, bounds)
+    }
+
+    /// Reads headerless little-endian `f32` samples of the given dimensions.
+    pub fn from_raw<R: Read>(
+        mut reader: R,
+        (nx, ny, nz): (usize, usize, usize),
+        bounds: Aabb,
+    ) -> io::Result<Self> {
+        let mut bytes = vec![];
+        reader.read_to_end(&mut bytes)?;
+        if bytes.len() % 4 != 0 {
+            return Err(invalid_data("raw grid is not a whole number of f32s".to_string()));
+        }
+        let data = bytes
+            .chunks_exact(4)
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f64)
+            .collect();
+        Self::checked(nx, ny, nz, data, bounds)
+    }
+
+    fn checked(nx: usize, ny: usize, nz: usize, data: Vec<f64>, bounds: Aabb) -> io::Result<Self> {
+        if nx == 0 || ny == 0 || nz == 0 {
+            return Err(invalid_data("grid dimensions must be non-zero".to_string()));
+        }
+        if data.len() != nx * ny * nz {
+            return Err(invalid_data(format!(
+                "expected {} grid values, found {}",
+                nx * ny * nz,
+                data.len()
+            )));
+        }
+        Ok(Self::new(nx, ny, nz, data, bounds))
+    }
+
+    pub fn bounds(&self) -> Aabb {
+        self.bounds
+    }
+
+    pub fn max_value(&self) -> f64 {
+        self.max_value
+    }
+
+    fn sample(&self, x: usize, y: usize, z: usize) -> f64 {
+        self.data[x + self.nx * (y + self.ny * z)]
+    }
+
+    /// Interpolated value at a world space point, zero outside the bounds.
+    pub fn value_at(&self, p: Point) -> f64 {
+        if !self.bounds.contains(p) {
+            return 0.0;
+        }
+        let size = self.bounds.size();
+        let local = p - self.bounds.min();
+        let dims = [self.nx, self.ny, self.nz];
+
+        let mut lo = [0; 3];
+        let mut hi = [0; 3];
+        let mut frac = [0.0; 3];
+        for axis in 0..3 {
+            let n = dims[axis];
+            let g = clamp(local[axis] / size[axis] * n as f64 - 0.5, 0.0, (n - 1) as f64);
+            lo[axis] = g.floor() as usize;
+            hi[axis] = usize::min(lo[axis] + 1, n - 1);
+            frac[axis] = g - lo[axis] as f64;
+        }
+
+        let lerp = |a: f64, b: f64, t: f64| a + t * (b - a);
+        let c = |x: usize, y: usize, z: usize| self.sample(x, y, z);
+        let c00 = lerp(c(lo[0], lo[1], lo[2]), c(hi[0], lo[1], lo[2]), frac[0]);
+        let c10 = lerp(c(lo[0], hi[1], lo[2]), c(hi[0], hi[1], lo[2]), frac[0]);
+        let c01 = lerp(c(lo[0], lo[1], hi[2]), c(hi[0], lo[1], hi[2]), frac[0]);
+        let c11 = lerp(c(lo[0], hi[1], hi[2]), c(hi[0], hi[1], hi[2]), frac[0]);
+        lerp(lerp(c00, c10, frac[1]), lerp(c01, c11, frac[1]), frac[2])
+    }
+}
+
+/// A heterogeneous participating medium whose density comes from a voxel
+/// grid, rendered with delta tracking against the grid's maximum density.
+pub struct GridMedium {
+    density: Arc<VoxelGrid>,
+    density_scale: f64,
+    phase_function: Arc<dyn Material + Sync + Send>,
+}
+
+impl GridMedium {
+    pub fn new(
+        density: Arc<VoxelGrid>,
+        density_scale: f64,
+        phase_function: Arc<dyn Material + Sync + Send>,
+    ) -> Self {
+        Self {
+            density,
+            density_scale,
+            phase_function,
+        }
+    }
+
+    fn max_density(&self) -> f64 {
+        self.density.max_value() * self.density_scale
+    }
+
+    fn density_at(&self, p: Point) -> f64 {
+        self.density.value_at(p) * self.density_scale
+    }
+
+    /// Estimates the transmittance along `ray` between `t_min` and `t_max`
+    /// with ratio tracking.
+    pub fn transmittance(&self, ray: &Ray, t_min: f64, t_max: f64) -> f64 {
+        let max_density = self.max_density();
+        let Some((t_enter, t_exit)) = self.density.bounds().intersect(ray, t_min, t_max) else {
+            return 1.0;
+        };
+        if max_density <= 0.0 {
+            return 1.0;
+        }
+
+        let ray_length = ray.direction().length();
+        let mut transmittance = 1.0;
+        let mut t = t_enter;
+        loop {
+            t -= f64::ln(1.0 - random_double(0.0, 1.0)) / (max_density * ray_length);
+            if t >= t_exit {
+                return transmittance;
+            }
+            transmittance *= 1.0 - self.density_at(ray.at(t)) / max_density;
+        }
+    }
+}
+
+impl Hittable for GridMedium {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let max_density = self.max_density();
+        if max_density <= 0.0 {
+            return None;
+        }
+        let (t_enter, t_exit) = self.density.bounds().intersect(ray, t_min, t_max)?;
+
+        // Delta tracking: sample tentative collisions against the majorant
+        // and accept each with probability density / max_density.
+        let ray_length = ray.direction().length();
+        let mut t = t_enter;
+        loop {
+            t -= f64::ln(1.0 - random_double(0.0, 1.0)) / (max_density * ray_length);
+            if t >= t_exit {
+                return None;
+            }
+            let p = ray.at(t);
+            if random_double(0.0, 1.0) * max_density < self.density_at(p) {
+                return Some(HitRecord::in_volume(p, t, Arc::clone(&self.phase_function)));
+            }
+        }
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(self.density.bounds())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        aabb::Aabb,
+        material::isotropic::Isotropic,
+        ray::Ray,
+        vec3::{Color, Point, Vec3},
+    };
+
+    use super::{GridMedium, VoxelGrid};
+
+    fn unit_bounds() -> Aabb {
+        Aabb::new(Point::new(0.0, 0.0, 0.0), Point::new(1.0, 1.0, 1.0))
+    }
+
+    #[test]
+    fn parse_text() {
+        let text = "# density\n2 1 1\n0.0 1.0\n";
+        let grid = VoxelGrid::from_text(text.as_bytes(), unit_bounds()).unwrap();
+        assert_eq!(grid.max_value(), 1.0);
+        assert_eq!(grid.value_at(Point::new(0.25, 0.5, 0.5)), 0.0);
+        assert_eq!(grid.value_at(Point::new(0.5, 0.5, 0.5)), 0.5);
+        assert_eq!(grid.value_at(Point::new(0.75, 0.5, 0.5)), 1.0);
+        assert_eq!(grid.value_at(Point::new(2.0, 0.5, 0.5)), 0.0);
+
+        assert!(VoxelGrid::from_text("2 1 1\n0.0".as_bytes(), unit_bounds()).is_err());
+        assert!(VoxelGrid::from_text("2 x 1\n0.0".as_bytes(), unit_bounds()).is_err());
+    }
+
+    #[test]
+    fn parse_raw() {
+        let bytes: Vec<u8> = [0.5f32, 2.0].iter().flat_map(|v| v.to_le_bytes()).collect();
+        let grid = VoxelGrid::from_raw(bytes.as_slice(), (1, 2, 1), unit_bounds()).unwrap();
+        assert_eq!(grid.max_value(), 2.0);
+        assert_eq!(grid.value_at(Point::new(0.5, 0.25, 0.5)), 0.5);
+        assert!(VoxelGrid::from_raw(bytes.as_slice(), (2, 2, 1), unit_bounds()).is_err());
+    }
+
+    #[test]
+    fn ratio_tracking_matches_beer_lambert() {
+        let density = 1.5;
+        let grid = Arc::new(VoxelGrid::new(2, 2, 2, vec![1.0; 8], unit_bounds()));
+        let medium = GridMedium::new(
+            grid,
+            density,
+            Arc::new(Isotropic::new(Color::new(1.0, 1.0, 1.0))),
+        );
+        let ray = Ray::new(Point::new(0.5, 0.5, -1.0), Vec3::new(0.0, 0.0, 1.0));
+        let n = 20_000;
+        let estimate = (0..n)
+            .map(|_| medium.transmittance(&ray, 0.0, f64::INFINITY))
+            .sum::<f64>()
+            / n as f64;
+        assert!(f64::abs(estimate - f64::exp(-density)) < 0.01);
+    }
+}
@@ -0,0 +1,288 @@
+use std::sync::Arc;
+
+use crate::{
+    aabb::Aabb,
+    hittable::{triangle::intersect_triangle, HitRecord, Hittable},
+    image::GrayImage,
+    material::Material,
+    ray::Ray,
+    vec3::{unit_vector, Point, Vec3},
+};
+
+/// Minimum and maximum height over blocks of `2^level` by `2^level` cells.
+struct MinMaxLevel {
+    width: usize,
+    depth: usize,
+    ranges: Vec<(f64, f64)>,
+}
+
+/// Terrain sampled on a regular grid of heights over the xz plane. Each cell
+/// is split into two triangles shaded with interpolated vertex normals. Rays
+/// descend a min/max quadtree over the cells, so only the cells whose height
+/// range the ray passes through are tested.
+pub struct Heightfield {
+    nx: usize,
+    nz: usize,
+    heights: Vec<f64>,
+    normals: Vec<Vec3>,
+    corner: Point,
+    size: Vec3,
+    levels: Vec<MinMaxLevel>,
+    material: Arc<dyn Material + Sync + Send>,
+}
+
+impl Heightfield {
+    /// Builds a heightfield from `nx` by `nz` samples in `[0, 1]`, stored row
+    /// by row along x. It spans `size` from `corner`, with a sample of 1
+    /// raised `size.y()` above the corner.
+    pub fn new(
+        nx: usize,
+        nz: usize,
+        samples: Vec<f64>,
+        corner: Point,
+        size: Vec3,
+        material: Arc<dyn Material + Sync + Send>,
+    ) -> Self {
+        assert!(nx >= 2 && nz >= 2, "a heightfield needs at least 2x2 samples");
+        assert_eq!(samples.len(), nx * nz, "sample count does not match the grid size");
+        let heights: Vec<f64> = samples.iter().map(|h| corner.y() + h * size.y()).collect();
+
+        let (dx, dz) = (size.x() / (nx - 1) as f64, size.z() / (nz - 1) as f64);
+        let normals = (0..nz)
+            .flat_map(|j| (0..nx).map(move |i| (i, j)))
+            .map(|(i, j)| {
+                let (i0, i1) = (i.saturating_sub(1), usize::min(i + 1, nx - 1));
+                let (j0, j1) = (j.saturating_sub(1), usize::min(j + 1, nz - 1));
+                let slope_x = (heights[j * nx + i1] - heights[j * nx + i0]) / ((i1 - i0) as f64 * dx);
+                let slope_z = (heights[j1 * nx + i] - heights[j0 * nx + i]) / ((j1 - j0) as f64 * dz);
+                unit_vector(Vec3::new(-slope_x, 1.0, -slope_z))
+            })
+            .collect();
+
+        let mut levels = vec![MinMaxLevel {
+            width: nx - 1,
+            depth: nz - 1,
+            ranges: (0..nz - 1)
+                .flat_map(|j| (0..nx - 1).map(move |i| (i, j)))
+                .map(|(i, j)| {
+                    let corners = [(i, j), (i + 1, j), (i, j + 1), (i + 1, j + 1)].map(|(i, j)| heights[j * nx + i]);
+                    corners.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &h| (lo.min(h), hi.max(h)))
+                })
+                .collect(),
+        }];
+        while levels.last().is_some_and(|l| l.width > 1 || l.depth > 1) {
+            let below = levels.last().unwrap();
+            let (width, depth) = (below.width.div_ceil(2), below.depth.div_ceil(2));
+            let mut ranges = vec![(f64::INFINITY, f64::NEG_INFINITY); width * depth];
+            for j in 0..below.depth {
+                for i in 0..below.width {
+                    let (lo, hi) = below.ranges[j * below.width + i];
+                    let range = &mut ranges[(j / 2) * width + i / 2];
+                    *range = (range.0.min(lo), range.1.max(hi));
+                }
+            }
+            levels.push(MinMaxLevel { width, depth, ranges });
+        }
+
+        Self {
+            nx,
+            nz,
+            heights,
+            normals,
+            corner,
+            size,
+            levels,
+            material,
+        }
+    }
+
+    /// Heightfield from a grayscale image, with rows running along +z.
+    pub fn from_image(
+        image: &GrayImage,
+        corner: Point,
+        size: Vec3,
+        material: Arc<dyn Material + Sync + Send>,
+    ) -> Self {
+        let samples = (0..image.height())
+            .flat_map(|y| (0..image.width()).map(move |x| (x, y)))
+            .map(|(x, y)| image.get(x, y))
+            .collect();
+        Self::new(image.width(), image.height(), samples, corner, size, material)
+    }
+
+    /// Heightfield sampling `height(u, v)` over `u, v` in `[0, 1]`, such as
+    /// fractal noise from `perlin::Perlin`.
+    pub fn from_fn(
+        nx: usize,
+        nz: usize,
+        height: impl Fn(f64, f64) -> f64,
+        corner: Point,
+        size: Vec3,
+        material: Arc<dyn Material + Sync + Send>,
+    ) -> Self {
+        let samples = (0..nz)
+            .flat_map(|j| (0..nx).map(move |i| (i, j)))
+            .map(|(i, j)| height(i as f64 / (nx - 1) as f64, j as f64 / (nz - 1) as f64))
+            .collect();
+        Self::new(nx, nz, samples, corner, size, material)
+    }
+
+    fn vertex(&self, i: usize, j: usize) -> Point {
+        Point::new(
+            self.corner.x() + self.size.x() * i as f64 / (self.nx - 1) as f64,
+            self.heights[j * self.nx + i],
+            self.corner.z() + self.size.z() * j as f64 / (self.nz - 1) as f64,
+        )
+    }
+
+    /// Bounds of block `(i, j)` of the given level, padded so that flat
+    /// blocks still have volume.
+    fn block_box(&self, level: usize, i: usize, j: usize) -> Aabb {
+        let (lo, hi) = self.levels[level].ranges[j * self.levels[level].width + i];
+        let (i0, i1) = (i << level, usize::min((i + 1) << level, self.nx - 1));
+        let (j0, j1) = (j << level, usize::min((j + 1) << level, self.nz - 1));
+        let pad = Vec3::new(1e-6, 1e-6, 1e-6);
+        let (a, b) = (self.vertex(i0, j0), self.vertex(i1, j1));
+        Aabb::new(
+            Point::new(a.x(), lo, a.z()) - pad,
+            Point::new(b.x(), hi, b.z()) + pad,
+        )
+    }
+
+    fn hit_block(&self, level: usize, i: usize, j: usize, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        if level == 0 {
+            return self.hit_cell(i, j, ray, t_min, t_max);
+        }
+
+        // Visit the children front to back, stopping once the nearest hit so
+        // far is closer than the next child.
+        let below = &self.levels[level - 1];
+        let mut children = [(0.0, 0, 0); 4];
+        let mut count = 0;
+        for cj in 2 * j..usize::min(2 * j + 2, below.depth) {
+            for ci in 2 * i..usize::min(2 * i + 2, below.width) {
+                if let Some((t_enter, _)) = self.block_box(level - 1, ci, cj).intersect(ray, t_min, t_max) {
+                    children[count] = (t_enter, ci, cj);
+                    count += 1;
+                }
+            }
+        }
+        children[..count].sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let mut closest = None;
+        let mut t_max = t_max;
+        for &(t_enter, ci, cj) in &children[..count] {
+            if t_enter > t_max {
+                break;
+            }
+            if let Some(rec) = self.hit_block(level - 1, ci, cj, ray, t_min, t_max) {
+                t_max = rec.t;
+                closest = Some(rec);
+            }
+        }
+        closest
+    }
+
+    fn hit_cell(&self, i: usize, j: usize, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let corners = [(i, j), (i + 1, j + 1), (i + 1, j), (i, j + 1)];
+        let triangles = [[corners[0], corners[1], corners[2]], [corners[0], corners[3], corners[1]]];
+
+        let mut closest: Option<(f64, Vec3)> = None;
+        for triangle in triangles {
+            let [a, b, c] = triangle.map(|(i, j)| self.vertex(i, j));
+            let t_limit = closest.map_or(t_max, |(t, _)| t);
+            if let Some((t, b1, b2)) = intersect_triangle(ray, (a, b, c), t_min, t_limit) {
+                let [na, nb, nc] = triangle.map(|(i, j)| self.normals[j * self.nx + i]);
+                closest = Some((t, (1.0 - b1 - b2) * na + b1 * nb + b2 * nc));
+            }
+        }
+
+        let (t, normal) = closest?;
+        let p = ray.at(t);
+        let uv = (
+            (p.x() - self.corner.x()) / self.size.x(),
+            (p.z() - self.corner.z()) / self.size.z(),
+        );
+        let material = Some(Arc::clone(&self.material));
+        Some(HitRecord::new(p, t, material, unit_vector(normal), ray, uv))
+    }
+}
+
+impl Hittable for Heightfield {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let top = self.levels.len() - 1;
+        self.block_box(top, 0, 0).intersect(ray, t_min, t_max)?;
+        self.hit_block(top, 0, 0, ray, t_min, t_max)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(self.block_box(self.levels.len() - 1, 0, 0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        hittable::Hittable,
+        material::lambertian::Lambertian,
+        perlin::Perlin,
+        ray::Ray,
+        utils::random_double,
+        vec3::{unit_vector, Color, Point, Vec3},
+    };
+
+    use super::Heightfield;
+
+    fn material() -> Arc<Lambertian> {
+        Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)))
+    }
+
+    #[test]
+    fn sloped_field_hit_and_normal() {
+        // Height rises with x at a slope of one half.
+        let field = Heightfield::from_fn(
+            9,
+            5,
+            |u, _| u,
+            Point::new(0.0, 0.0, 0.0),
+            Vec3::new(4.0, 2.0, 2.0),
+            material(),
+        );
+        let ray = Ray::new(Point::new(2.0, 10.0, 1.0), Vec3::new(0.0, -1.0, 0.0));
+        let rec = field.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        assert!(f64::abs(rec.t - 9.0) < 1e-9);
+        assert!((rec.normal - unit_vector(Vec3::new(-0.5, 1.0, 0.0))).length() < 1e-9);
+        assert!(f64::abs(rec.u - 0.5) < 1e-9 && f64::abs(rec.v - 0.5) < 1e-9);
+
+        let outside = Ray::new(Point::new(5.0, 10.0, 1.0), Vec3::new(0.0, -1.0, 0.0));
+        assert!(field.hit(&outside, 0.001, f64::INFINITY).is_none());
+    }
+
+    #[test]
+    fn quadtree_matches_brute_force() {
+        let perlin = Perlin::new(3);
+        let field = Heightfield::from_fn(
+            37,
+            23,
+            |u, v| 0.5 + 0.5 * perlin.fbm(Point::new(6.0 * u, 0.0, 6.0 * v), 4),
+            Point::new(-2.0, 0.0, -1.0),
+            Vec3::new(4.0, 1.0, 2.0),
+            material(),
+        );
+        for _ in 0..500 {
+            let origin = Point::new(random_double(-3.0, 3.0), 2.0, random_double(-2.0, 2.0));
+            let target = Point::new(random_double(-2.0, 2.0), random_double(0.0, 1.0), random_double(-1.0, 1.0));
+            let ray = Ray::new(origin, target - origin);
+
+            let brute = (0..22)
+                .flat_map(|j| (0..36).map(move |i| (i, j)))
+                .filter_map(|(i, j)| field.hit_cell(i, j, &ray, 0.001, f64::INFINITY))
+                .map(|rec| rec.t)
+                .fold(f64::INFINITY, f64::min);
+            let traced = field.hit(&ray, 0.001, f64::INFINITY).map_or(f64::INFINITY, |rec| rec.t);
+            assert!(brute == traced || f64::abs(brute - traced) < 1e-9, "{brute} != {traced}");
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use std::sync::Arc;
+
+use crate::{
+    aabb::Aabb,
+    hittable::{azimuth_u, first_in_range, pair_crossings, HitRecord, Hittable, Interval, Solid},
+    material::Material,
+    ray::Ray,
+    roots::solve_quadratic,
+    vec3::{unit_vector, Point, Vec3},
+};
+
+/// A paraboloid around the y axis opening upwards from its vertex, reaching
+/// `radius` at `height` above it.
+pub struct Paraboloid {
+    vertex: Point,
+    radius: f64,
+    height: f64,
+    capped: bool,
+    material: Arc<dyn Material + Sync + Send>,
+}
+
+impl Paraboloid {
+    pub fn new(
+        vertex: Point,
+        radius: f64,
+        height: f64,
+        capped: bool,
+        material: Arc<dyn Material + Sync + Send>,
+    ) -> Self {
+        Self {
+            vertex,
+            radius,
+            height,
+            capped,
+            material,
+        }
+    }
+
+    /// Every crossing of the surface along the ray's line, sorted by `t`.
+    pub(crate) fn crossings(&self, ray: &Ray) -> Vec<HitRecord> {
+        let o = ray.origin() - self.vertex;
+        let d = ray.direction();
+        let k = self.radius * self.radius / self.height;
+        let mut hits = vec![];
+
+        // x^2 + z^2 = k y
+        let a = d.x() * d.x() + d.z() * d.z();
+        let b = 2.0 * (o.x() * d.x() + o.z() * d.z()) - k * d.y();
+        let c = o.x() * o.x() + o.z() * o.z() - k * o.y();
+        for t in solve_quadratic(a, b, c) {
+            let local = o + t * d;
+            if local.y() < 0.0 || local.y() > self.height {
+                continue;
+            }
+            let outward_normal = unit_vector(Vec3::new(2.0 * local.x(), -k, 2.0 * local.z()));
+            let uv = (azimuth_u(local.x(), local.z()), local.y() / self.height);
+            let material = Some(Arc::clone(&self.material));
+            hits.push(HitRecord::new(ray.at(t), t, material, outward_normal, ray, uv));
+        }
+
+        if self.capped && d.y() != 0.0 {
+            let t = (self.height - o.y()) / d.y();
+            let local = o + t * d;
+            let r2 = local.x() * local.x() + local.z() * local.z();
+            if r2 <= self.radius * self.radius {
+                let uv = (azimuth_u(local.x(), local.z()), f64::sqrt(r2) / self.radius);
+                let material = Some(Arc::clone(&self.material));
+                let outward_normal = Vec3::new(0.0, 1.0, 0.0);
+                hits.push(HitRecord::new(ray.at(t), t, material, outward_normal, ray, uv));
+            }
+        }
+
+        hits.sort_by(|a, b| a.t.total_cmp(&b.t));
+        hits
+    }
+}
+
+impl Hittable for Paraboloid {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        first_in_range(self.crossings(ray), t_min, t_max)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let r = self.radius;
+        Some(Aabb::new(
+            self.vertex + Vec3::new(-r, 0.0, -r),
+            self.vertex + Vec3::new(r, self.height, r),
+        ))
+    }
+}
+
+impl Solid for Paraboloid {
+    fn intervals(&self, ray: &Ray) -> Vec<Interval> {
+        if !self.capped {
+            return vec![];
+        }
+        pair_crossings(ray, self.crossings(ray))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        hittable::Hittable,
+        material::lambertian::Lambertian,
+        ray::Ray,
+        vec3::{Color, Point, Vec3},
+    };
+
+    use super::Paraboloid;
+
+    #[test]
+    fn bowl() {
+        let bowl = Paraboloid::new(
+            Point::new(0.0, 0.0, 0.0),
+            2.0,
+            4.0,
+            false,
+            Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))),
+        );
+        // y = x^2: at y = 1 the radius is 1.
+        let ray = Ray::new(Point::new(-5.0, 1.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        let rec = bowl.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        assert!(f64::abs(rec.t - 4.0) < 1e-9);
+        assert!(rec.front_face);
+
+        // Looking straight down into the open bowl hits the inside at the vertex.
+        let down = Ray::new(Point::new(0.0, 5.0, 0.0), Vec3::new(0.0, -1.0, 0.0));
+        let rec = bowl.hit(&down, 0.001, f64::INFINITY).unwrap();
+        assert!(f64::abs(rec.t - 5.0) < 1e-9);
+        assert!(!rec.front_face);
+    }
+}
@@ -0,0 +1,327 @@
+use std::{
+    io::{self, BufRead, Read},
+    sync::Arc,
+};
+
+use crate::{
+    aabb::{surrounding_box, Aabb},
+    hittable::{sphere::Sphere, HitRecord, Hittable},
+    material::{lambertian::Lambertian, Material},
+    ray::Ray,
+    texture::Texture,
+    vec3::{dot, Color, Point, Vec3},
+};
+
+const MAX_LEAF_SIZE: usize = 4;
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Particle attributes as parallel arrays, as written by simulations.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParticleData {
+    pub positions: Vec<Point>,
+    pub radii: Vec<f64>,
+    pub colors: Vec<Color>,
+}
+
+impl ParticleData {
+    /// Reads one particle per line as comma separated `x,y,z,radius` with an
+    /// optional `r,g,b` colour, white if missing. A first line that is not
+    /// numeric is taken as a header, and lines starting with `#` are skipped.
+    pub fn from_csv<R: BufRead>(reader: R) -> io::Result<Self> {
+        let mut data = Self::default();
+        for (number, line) in reader.lines().enumerate() {
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields: Result<Vec<f64>, _> = line.split(',').map(|f| f.trim().parse::<f64>()).collect();
+            let fields = match fields {
+                Ok(fields) => fields,
+                Err(_) if number == 0 => continue,
+                Err(_) => return Err(invalid_data(format!("invalid particle on line {}", number + 1))),
+            };
+            let color = match fields.len() {
+                4 => Color::new(1.0, 1.0, 1.0),
+                7 => Color::new(fields[4], fields[5], fields[6]),
+                n => return Err(invalid_data(format!("expected 4 or 7 fields on line {}, found {n}", number + 1))),
+            };
+            data.positions.push(Point::new(fields[0], fields[1], fields[2]));
+            data.radii.push(fields[3]);
+            data.colors.push(color);
+        }
+        Ok(data)
+    }
+
+    /// Reads headerless records of seven little-endian `f32`s, `x y z radius
+    /// r g b`.
+    pub fn from_raw<R: Read>(mut reader: R) -> io::Result<Self> {
+        let mut bytes = vec![];
+        reader.read_to_end(&mut bytes)?;
+        if bytes.len() % 28 != 0 {
+            return Err(invalid_data("raw particles are not a whole number of records".to_string()));
+        }
+        let mut data = Self::default();
+        for record in bytes.chunks_exact(28) {
+            let f: Vec<f64> = record
+                .chunks_exact(4)
+                .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f64)
+                .collect();
+            data.positions.push(Point::new(f[0], f[1], f[2]));
+            data.radii.push(f[3]);
+            data.colors.push(Color::new(f[4], f[5], f[6]));
+        }
+        Ok(data)
+    }
+}
+
+/// Looks up the colour of the particle identified by the `u` texture
+/// coordinate of a `Particles` hit. Only meaningful on `Particles` with one
+/// colour per particle.
+pub struct ParticleColors {
+    colors: Vec<Color>,
+}
+
+impl ParticleColors {
+    /// Panics if `colors` is empty.
+    pub fn new(colors: Vec<Color>) -> Self {
+        assert!(!colors.is_empty(), "particle colours need at least one colour");
+        Self { colors }
+    }
+}
+
+impl Texture for ParticleColors {
+    fn value(&self, u: f64, _: f64, _: Point) -> Color {
+        let n = self.colors.len();
+        self.colors[usize::min((u * n as f64) as usize, n - 1)]
+    }
+}
+
+/// Node of the flattened hierarchy. Interior nodes are followed by their
+/// left child and point to their right one; leaves cover `count` entries of
+/// the particle order from `offset`.
+struct Node {
+    bounds: Aabb,
+    offset: u32,
+    count: u32,
+}
+
+/// Many spheres sharing a material, stored as flat arrays under their own
+/// bounding volume hierarchy rather than as individual hittables.
+///
+/// The `u` texture coordinate identifies the particle hit, as its index over
+/// the particle count, so that a `ParticleColors` texture can colour each
+/// one. `v` runs from pole to pole as on a `Sphere`. As `u` is not a position
+/// around the sphere, `ParticleColors` is the only texture varying in `u`
+/// that is valid on particles; others only see a single column at each
+/// particle's `u`.
+pub struct Particles {
+    positions: Vec<Point>,
+    radii: Vec<f64>,
+    order: Vec<u32>,
+    nodes: Vec<Node>,
+    material: Arc<dyn Material + Sync + Send>,
+}
+
+impl Particles {
+    /// Panics if there are no particles or the arrays differ in length.
+    pub fn new(positions: Vec<Point>, radii: Vec<f64>, material: Arc<dyn Material + Sync + Send>) -> Self {
+        assert!(!positions.is_empty(), "a particle system needs at least one particle");
+        assert_eq!(positions.len(), radii.len(), "every particle needs a radius");
+        let mut particles = Self {
+            order: (0..positions.len() as u32).collect(),
+            positions,
+            radii,
+            nodes: Vec::new(),
+            material,
+        };
+        let mut order = std::mem::take(&mut particles.order);
+        particles.build(&mut order, 0);
+        particles.order = order;
+        particles
+    }
+
+    /// Diffuse particles in their own colours. Panics if there are no
+    /// particles or the arrays differ in length.
+    pub fn diffuse(data: ParticleData) -> Self {
+        assert_eq!(data.positions.len(), data.colors.len(), "every particle needs a colour");
+        let material = Arc::new(Lambertian::with_texture(Arc::new(ParticleColors::new(data.colors))));
+        Self::new(data.positions, data.radii, material)
+    }
+
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    fn particle_box(&self, i: u32) -> Aabb {
+        let r = self.radii[i as usize];
+        let p = self.positions[i as usize];
+        Aabb::new(p - Vec3::new(r, r, r), p + Vec3::new(r, r, r))
+    }
+
+    /// Appends the subtree over `order`, which starts at `offset` in the full
+    /// order, splitting at the median centroid along the widest axis.
+    fn build(&mut self, order: &mut [u32], offset: usize) {
+        let bounds = order[1..].iter().fold(self.particle_box(order[0]), |acc, &i| surrounding_box(acc, self.particle_box(i)));
+        let node = self.nodes.len();
+        self.nodes.push(Node {
+            bounds,
+            offset: offset as u32,
+            count: order.len() as u32,
+        });
+        if order.len() <= MAX_LEAF_SIZE {
+            return;
+        }
+
+        let size = bounds.size();
+        let axis = if size.x() > size.y() && size.x() > size.z() {
+            0
+        } else if size.y() > size.z() {
+            1
+        } else {
+            2
+        };
+        let mid = order.len() / 2;
+        order.select_nth_unstable_by(mid, |&a, &b| {
+            self.positions[a as usize][axis].total_cmp(&self.positions[b as usize][axis])
+        });
+        let (left, right) = order.split_at_mut(mid);
+        self.build(left, offset);
+        let right_node = self.nodes.len();
+        self.build(right, offset + mid);
+        self.nodes[node].offset = right_node as u32;
+        self.nodes[node].count = 0;
+    }
+
+    fn hit_particle(&self, i: usize, ray: &Ray, t_min: f64, t_max: f64) -> Option<f64> {
+        let oc = ray.origin() - self.positions[i];
+        let a = ray.direction().length_squared();
+        let half_b = dot(oc, ray.direction());
+        let c = oc.length_squared() - self.radii[i].powi(2);
+        let discriminant = half_b * half_b - a * c;
+        if discriminant < 0.0 {
+            return None;
+        }
+        let sqrt_d = f64::sqrt(discriminant);
+        [(-half_b - sqrt_d) / a, (-half_b + sqrt_d) / a]
+            .into_iter()
+            .find(|&t| t >= t_min && t <= t_max)
+    }
+}
+
+impl Hittable for Particles {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let mut closest: Option<(f64, usize)> = None;
+        let mut t_max = t_max;
+        let mut stack = vec![0usize];
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            if !node.bounds.hit(ray, t_min, t_max) {
+                continue;
+            }
+            if node.count == 0 {
+                stack.push(node.offset as usize);
+                stack.push(index + 1);
+                continue;
+            }
+            let start = node.offset as usize;
+            for &i in &self.order[start..start + node.count as usize] {
+                if let Some(t) = self.hit_particle(i as usize, ray, t_min, t_max) {
+                    t_max = t;
+                    closest = Some((t, i as usize));
+                }
+            }
+        }
+
+        let (t, i) = closest?;
+        let p = ray.at(t);
+        let outward_normal = (p - self.positions[i]) / self.radii[i];
+        let u = (i as f64 + 0.5) / self.len() as f64;
+        let (_, v) = Sphere::get_sphere_uv(outward_normal);
+        let material = Some(Arc::clone(&self.material));
+        Some(HitRecord::new(p, t, material, outward_normal, ray, (u, v)))
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(self.nodes[0].bounds)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        hittable::{sphere::Sphere, Hittable, HittableList},
+        material::lambertian::Lambertian,
+        ray::Ray,
+        texture::Texture,
+        utils::random_double,
+        vec3::{Color, Point, Vec3},
+    };
+
+    use super::{ParticleColors, ParticleData, Particles};
+
+    #[test]
+    fn matches_individual_spheres() {
+        let material = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+        let positions: Vec<Point> = (0..300).map(|_| Vec3::random(-5.0, 5.0)).collect();
+        let radii: Vec<f64> = (0..300).map(|_| random_double(0.05, 0.3)).collect();
+
+        let mut list = HittableList::default();
+        for (&p, &r) in positions.iter().zip(&radii) {
+            list.add(Arc::new(Sphere::new(p, r, material.clone())));
+        }
+        let particles = Particles::new(positions, radii, material);
+
+        for _ in 0..500 {
+            let origin = Vec3::random(-8.0, 8.0);
+            let ray = Ray::new(origin, Vec3::random(-5.0, 5.0) - origin);
+            let expected = list.hit(&ray, 0.001, f64::INFINITY).map(|rec| rec.t);
+            let actual = particles.hit(&ray, 0.001, f64::INFINITY).map(|rec| rec.t);
+            assert_eq!(expected, actual);
+        }
+    }
+
+    #[test]
+    fn colors_follow_particles() {
+        let data = ParticleData {
+            positions: vec![Point::new(0.0, 0.0, 0.0), Point::new(3.0, 0.0, 0.0)],
+            radii: vec![1.0, 1.0],
+            colors: vec![Color::new(1.0, 0.0, 0.0), Color::new(0.0, 0.0, 1.0)],
+        };
+        let texture = ParticleColors::new(data.colors.clone());
+        let particles = Particles::diffuse(data);
+        let ray = Ray::new(Point::new(3.0, 0.0, 5.0), Vec3::new(0.0, 0.0, -1.0));
+        let rec = particles.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        assert_eq!(rec.t, 4.0);
+        assert_eq!(texture.value(rec.u, rec.v, rec.p), Color::new(0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn reads_csv_and_raw() {
+        let csv = "x,y,z,radius,r,g,b\n# comment\n1,2,3,0.5,0.1,0.2,0.3\n4, 5, 6, 0.25\n";
+        let data = ParticleData::from_csv(csv.as_bytes()).unwrap();
+        assert_eq!(data.positions, vec![Point::new(1.0, 2.0, 3.0), Point::new(4.0, 5.0, 6.0)]);
+        assert_eq!(data.radii, vec![0.5, 0.25]);
+        assert_eq!(data.colors[1], Color::new(1.0, 1.0, 1.0));
+        assert!(ParticleData::from_csv("1,2,3\n".as_bytes()).is_err());
+        assert!(ParticleData::from_csv("1,2,3,4\nx,y,z,r\n".as_bytes()).is_err());
+
+        let raw: Vec<u8> = [1.0f32, 2.0, 3.0, 0.5, 0.25, 0.5, 1.0]
+            .iter()
+            .flat_map(|f| f.to_le_bytes())
+            .collect();
+        let data = ParticleData::from_raw(raw.as_slice()).unwrap();
+        assert_eq!(data.positions, vec![Point::new(1.0, 2.0, 3.0)]);
+        assert_eq!(data.colors, vec![Color::new(0.25, 0.5, 1.0)]);
+        assert!(ParticleData::from_raw(&raw[..27]).is_err());
+    }
+}
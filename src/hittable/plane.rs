@@ -0,0 +1,54 @@
+use std::sync::Arc;
+
+use crate::{
+    aabb::Aabb,
+    hittable::{HitRecord, Hittable},
+    material::Material,
+    onb::Onb,
+    ray::Ray,
+    vec3::{dot, Point, Vec3},
+};
+
+/// An infinite plane through `point` facing along `normal`. The texture
+/// coordinates are distances along the plane in world units, so textures
+/// tile across it.
+pub struct Plane {
+    point: Point,
+    material: Arc<dyn Material + Sync + Send>,
+    uvw: Onb,
+}
+
+impl Plane {
+    pub fn new(point: Point, normal: Vec3, material: Arc<dyn Material + Sync + Send>) -> Self {
+        Self {
+            point,
+            material,
+            uvw: Onb::build_from_w(normal),
+        }
+    }
+}
+
+impl Hittable for Plane {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let normal = self.uvw.w();
+        let denom = dot(normal, ray.direction());
+        if f64::abs(denom) < 1e-8 {
+            return None;
+        }
+
+        let t = dot(normal, self.point - ray.origin()) / denom;
+        if t < t_min || t > t_max {
+            return None;
+        }
+
+        let p = ray.at(t);
+        let offset = p - self.point;
+        let uv = (dot(offset, self.uvw.u()), dot(offset, self.uvw.v()));
+        let material = Some(Arc::clone(&self.material));
+        Some(HitRecord::new(p, t, material, normal, ray, uv))
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        None
+    }
+}
@@ -0,0 +1,217 @@
+use std::sync::Arc;
+
+use crate::{
+    aabb::{surrounding_box, Aabb},
+    hittable::{HitRecord, Hittable},
+    material::Material,
+    ray::Ray,
+    utils::random_double,
+    vec3::{cross, dot, unit_vector, Point, Vec3},
+};
+
+/// A parallelogram with corner `q` and edges `u` and `v`. The texture
+/// coordinates run from 0 to 1 along each edge.
+pub struct Quad {
+    q: Point,
+    u: Vec3,
+    v: Vec3,
+    material: Arc<dyn Material + Sync + Send>,
+    normal: Vec3,
+    d: f64,
+    w: Vec3,
+    area: f64,
+    bbox: Aabb,
+}
+
+impl Quad {
+    pub fn new(q: Point, u: Vec3, v: Vec3, material: Arc<dyn Material + Sync + Send>) -> Self {
+        let n = cross(u, v);
+        let normal = unit_vector(n);
+        // Pad so that axis-aligned quads still have a box with volume.
+        let pad = Vec3::new(1e-4, 1e-4, 1e-4);
+        let corners = [q, q + u, q + v, q + u + v];
+        let bbox = corners[1..].iter().fold(Aabb::new(q - pad, q + pad), |acc, &c| {
+            surrounding_box(acc, Aabb::new(c - pad, c + pad))
+        });
+        Self {
+            q,
+            u,
+            v,
+            material,
+            normal,
+            d: dot(normal, q),
+            w: n / dot(n, n),
+            area: n.length(),
+            bbox,
+        }
+    }
+
+    /// Rectangle in the plane `z = k`, facing +z.
+    pub fn xy_rect(
+        (x0, x1): (f64, f64),
+        (y0, y1): (f64, f64),
+        k: f64,
+        material: Arc<dyn Material + Sync + Send>,
+    ) -> Self {
+        Self::new(
+            Point::new(x0, y0, k),
+            Vec3::new(x1 - x0, 0.0, 0.0),
+            Vec3::new(0.0, y1 - y0, 0.0),
+            material,
+        )
+    }
+
+    /// Rectangle in the plane `y = k`, facing +y.
+    pub fn xz_rect(
+        (x0, x1): (f64, f64),
+        (z0, z1): (f64, f64),
+        k: f64,
+        material: Arc<dyn Material + Sync + Send>,
+    ) -> Self {
+        Self::new(
+            Point::new(x0, k, z0),
+            Vec3::new(0.0, 0.0, z1 - z0),
+            Vec3::new(x1 - x0, 0.0, 0.0),
+            material,
+        )
+    }
+
+    /// Rectangle in the plane `x = k`, facing +x.
+    pub fn yz_rect(
+        (y0, y1): (f64, f64),
+        (z0, z1): (f64, f64),
+        k: f64,
+        material: Arc<dyn Material + Sync + Send>,
+    ) -> Self {
+        Self::new(
+            Point::new(k, y0, z0),
+            Vec3::new(0.0, y1 - y0, 0.0),
+            Vec3::new(0.0, 0.0, z1 - z0),
+            material,
+        )
+    }
+
+    pub fn area(&self) -> f64 {
+        self.area
+    }
+}
+
+impl Hittable for Quad {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let denom = dot(self.normal, ray.direction());
+        if f64::abs(denom) < 1e-8 {
+            return None;
+        }
+
+        let t = (self.d - dot(self.normal, ray.origin())) / denom;
+        if t < t_min || t > t_max {
+            return None;
+        }
+
+        let p = ray.at(t);
+        let planar = p - self.q;
+        let alpha = dot(self.w, cross(planar, self.v));
+        let beta = dot(self.w, cross(self.u, planar));
+        if !(0.0..=1.0).contains(&alpha) || !(0.0..=1.0).contains(&beta) {
+            return None;
+        }
+
+        let material = Some(Arc::clone(&self.material));
+        Some(HitRecord::new(p, t, material, self.normal, ray, (alpha, beta)))
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(self.bbox)
+    }
+
+    fn pdf_value(&self, origin: Point, direction: Vec3) -> f64 {
+        let Some(rec) = self.hit(&Ray::new(origin, direction), 0.001, f64::INFINITY) else {
+            return 0.0;
+        };
+        let distance_squared = rec.t * rec.t * direction.length_squared();
+        let cosine = f64::abs(dot(direction, rec.normal) / direction.length());
+        distance_squared / (cosine * self.area)
+    }
+
+    fn random(&self, origin: Point) -> Vec3 {
+        let p = self.q + random_double(0.0, 1.0) * self.u + random_double(0.0, 1.0) * self.v;
+        p - origin
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        hittable::Hittable,
+        material::lambertian::Lambertian,
+        ray::Ray,
+        vec3::{Color, Point, Vec3},
+    };
+
+    use super::Quad;
+
+    fn material() -> Arc<Lambertian> {
+        Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)))
+    }
+
+    #[test]
+    fn hit_and_uv() {
+        let quad = Quad::new(
+            Point::new(0.0, 0.0, 0.0),
+            Vec3::new(2.0, 0.0, 0.0),
+            Vec3::new(0.0, 4.0, 0.0),
+            material(),
+        );
+        let ray = Ray::new(Point::new(1.0, 1.0, 5.0), Vec3::new(0.0, 0.0, -1.0));
+        let rec = quad.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        assert_eq!(rec.t, 5.0);
+        assert_eq!((rec.u, rec.v), (0.5, 0.25));
+        assert_eq!(rec.normal, Vec3::new(0.0, 0.0, 1.0));
+        assert!(rec.front_face);
+
+        let miss = Ray::new(Point::new(3.0, 1.0, 5.0), Vec3::new(0.0, 0.0, -1.0));
+        assert!(quad.hit(&miss, 0.001, f64::INFINITY).is_none());
+        let parallel = Ray::new(Point::new(1.0, 1.0, 5.0), Vec3::new(1.0, 0.0, 0.0));
+        assert!(quad.hit(&parallel, 0.001, f64::INFINITY).is_none());
+    }
+
+    #[test]
+    fn axis_aligned_rects_face_positive_axes() {
+        let xz = Quad::xz_rect((0.0, 1.0), (0.0, 1.0), 2.0, material());
+        let ray = Ray::new(Point::new(0.5, 5.0, 0.5), Vec3::new(0.0, -1.0, 0.0));
+        let rec = xz.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        assert_eq!(rec.normal, Vec3::new(0.0, 1.0, 0.0));
+        assert!(rec.front_face);
+
+        let yz = Quad::yz_rect((0.0, 1.0), (0.0, 1.0), 2.0, material());
+        let ray = Ray::new(Point::new(5.0, 0.5, 0.5), Vec3::new(-1.0, 0.0, 0.0));
+        assert!(yz.hit(&ray, 0.001, f64::INFINITY).unwrap().front_face);
+    }
+
+    #[test]
+    fn light_sampling_pdf_matches_solid_angle() {
+        let quad = Quad::xz_rect((-1.0, 1.0), (-1.0, 1.0), 2.0, material());
+        let origin = Point::new(0.3, 0.0, -0.2);
+        for _ in 0..100 {
+            let direction = quad.random(origin);
+            assert!(quad.pdf_value(origin, direction) > 0.0);
+        }
+        assert_eq!(quad.pdf_value(origin, Vec3::new(0.0, -1.0, 0.0)), 0.0);
+
+        // The average of 1 / pdf over sampled directions is the solid angle
+        // of the quad. Split at the foot of the perpendicular from the origin,
+        // it is four rectangles with a corner there, each subtending
+        // atan(ab / (d sqrt(d^2 + a^2 + b^2))).
+        let n = 200_000;
+        let solid_angle = (0..n)
+            .map(|_| quad.random(origin))
+            .map(|d| 1.0 / quad.pdf_value(origin, d))
+            .sum::<f64>()
+            / n as f64;
+        let corner = |a: f64, b: f64| f64::atan(a * b / (2.0 * f64::sqrt(4.0 + a * a + b * b)));
+        let exact = corner(1.3, 0.8) + corner(1.3, 1.2) + corner(0.7, 0.8) + corner(0.7, 1.2);
+        assert!(f64::abs(solid_angle - exact) / exact < 0.01, "{solid_angle} != {exact}");
+    }
+}
@@ -0,0 +1,153 @@
+use std::sync::Arc;
+
+use crate::{
+    aabb::Aabb,
+    hittable::{HitRecord, Hittable},
+    material::Material,
+    ray::Ray,
+    sdf::Sdf,
+    vec3::{unit_vector, Point, Vec3},
+};
+
+/// Renders a signed distance function by sphere tracing within `bounds`,
+/// estimating normals from the gradient of the distance.
+pub struct SdfObject {
+    sdf: Arc<dyn Sdf + Sync + Send>,
+    bounds: Aabb,
+    material: Arc<dyn Material + Sync + Send>,
+    max_steps: usize,
+    epsilon: f64,
+    step_scale: f64,
+}
+
+impl SdfObject {
+    pub fn new(
+        sdf: Arc<dyn Sdf + Sync + Send>,
+        bounds: Aabb,
+        material: Arc<dyn Material + Sync + Send>,
+    ) -> Self {
+        Self {
+            sdf,
+            bounds,
+            material,
+            max_steps: 512,
+            epsilon: 1e-4,
+            step_scale: 1.0,
+        }
+    }
+
+    /// Scales every step by `step_scale` in `(0, 1]`, for functions such as
+    /// twisted shapes that overestimate the distance to the surface.
+    pub fn with_step_scale(mut self, step_scale: f64) -> Self {
+        self.step_scale = step_scale;
+        self
+    }
+
+    /// Distance below which the surface counts as hit, and the give up limit
+    /// on steps per ray.
+    pub fn with_precision(mut self, epsilon: f64, max_steps: usize) -> Self {
+        self.epsilon = epsilon;
+        self.max_steps = max_steps;
+        self
+    }
+
+    fn normal(&self, p: Point) -> Vec3 {
+        // Tetrahedral central differences need only four evaluations.
+        let h = self.epsilon;
+        let k = [
+            Vec3::new(1.0, -1.0, -1.0),
+            Vec3::new(-1.0, -1.0, 1.0),
+            Vec3::new(-1.0, 1.0, -1.0),
+            Vec3::new(1.0, 1.0, 1.0),
+        ];
+        let gradient = k
+            .iter()
+            .fold(Vec3::default(), |acc, &k| acc + k * self.sdf.distance(p + h * k));
+        unit_vector(gradient)
+    }
+}
+
+impl Hittable for SdfObject {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let (t_enter, t_exit) = self.bounds.intersect(ray, t_min, t_max)?;
+        let ray_length = ray.direction().length();
+
+        // Rays starting inside the shape march towards the exit instead.
+        let side = f64::signum(self.sdf.distance(ray.at(t_enter)));
+        let mut t = t_enter;
+        for _ in 0..self.max_steps {
+            let p = ray.at(t);
+            let distance = side * self.sdf.distance(p);
+            if distance < self.epsilon {
+                if t < t_min {
+                    return None;
+                }
+                let material = Some(Arc::clone(&self.material));
+                return Some(HitRecord::new(p, t, material, self.normal(p), ray, (0.0, 0.0)));
+            }
+            t += self.step_scale * distance / ray_length;
+            if t > t_exit {
+                return None;
+            }
+        }
+        None
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(self.bounds)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        aabb::Aabb,
+        hittable::Hittable,
+        material::lambertian::Lambertian,
+        ray::Ray,
+        sdf::{Mandelbulb, SdfSphere},
+        vec3::{Color, Point, Vec3},
+    };
+
+    use super::SdfObject;
+
+    fn bounds(r: f64) -> Aabb {
+        Aabb::new(Point::new(-r, -r, -r), Point::new(r, r, r))
+    }
+
+    #[test]
+    fn traced_sphere_matches_analytic() {
+        let object = SdfObject::new(
+            Arc::new(SdfSphere::new(1.0)),
+            bounds(1.5),
+            Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))),
+        );
+        let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vec3::new(0.0, 0.0, 2.0));
+        let rec = object.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        assert!(f64::abs(rec.t - 2.0) < 1e-3);
+        assert!((rec.normal - Vec3::new(0.0, 0.0, -1.0)).length() < 1e-3);
+        assert!(rec.front_face);
+
+        let inside = Ray::new(Point::new(0.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        let rec = object.hit(&inside, 0.001, f64::INFINITY).unwrap();
+        assert!(f64::abs(rec.t - 1.0) < 1e-3);
+        assert!(!rec.front_face);
+
+        let miss = Ray::new(Point::new(0.0, 1.2, -5.0), Vec3::new(0.0, 0.0, 1.0));
+        assert!(object.hit(&miss, 0.001, f64::INFINITY).is_none());
+    }
+
+    #[test]
+    fn mandelbulb_is_hit_from_outside() {
+        let object = SdfObject::new(
+            Arc::new(Mandelbulb::new(8.0, 10)),
+            bounds(1.2),
+            Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))),
+        );
+        let ray = Ray::new(Point::new(0.0, 0.0, -3.0), Vec3::new(0.0, 0.0, 1.0));
+        let rec = object.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        assert!(rec.t > 1.8 && rec.t < 3.0);
+    }
+}
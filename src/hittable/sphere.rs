@@ -1,6 +1,6 @@
-use std::{sync::Arc};
+use std::{f64::consts::PI, sync::Arc};
 
-use crate::{vec3::{Point, dot}, hittable::{Hittable, HitRecord}, material::Material};
+use crate::{vec3::{Point, Vec3, dot}, hittable::{Hittable, HitRecord, Interval, Solid}, material::Material, aabb::Aabb, ray::Ray};
 
 pub struct Sphere {
     center: Point,
@@ -16,6 +16,21 @@ impl Sphere {
             material: material
         }
     }
+
+    /// Maps a point on the unit sphere to (u, v) texture coordinates.
+    pub fn get_sphere_uv(p: Point) -> (f64, f64) {
+        let theta = f64::acos(-p.y());
+        let phi = f64::atan2(-p.z(), p.x()) + PI;
+        (phi / (2.0 * PI), theta / PI)
+    }
+
+    fn record(&self, ray: &Ray, t: f64) -> HitRecord {
+        let p = ray.at(t);
+        let material = Some(Arc::clone(&self.material));
+        let outward_normal = (p - self.center) / self.radius;
+        let uv = Self::get_sphere_uv(outward_normal);
+        HitRecord::new(p, t, material, outward_normal, ray, uv)
+    }
 }
 
 impl Hittable for Sphere {
@@ -38,11 +53,31 @@ impl Hittable for Sphere {
             }
         }
 
-        let t = root;
-        let p = ray.at(t);
-        let material = Some(Arc::clone(&self.material));
-        let outward_normal = (p - self.center) / self.radius;
-        Some(HitRecord::new(p, t, material, outward_normal, ray))
+        Some(self.record(ray, root))
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let r = Vec3::new(self.radius, self.radius, self.radius);
+        Some(Aabb::new(self.center - r, self.center + r))
+    }
+}
+
+impl Solid for Sphere {
+    fn intervals(&self, ray: &Ray) -> Vec<Interval> {
+        let oc = ray.origin() - self.center;
+        let a = ray.direction().length_squared();
+        let half_b = dot(oc, ray.direction());
+        let c = oc.length_squared() - self.radius.powi(2);
+
+        let discriminant = half_b.powi(2) - a * c;
+        if discriminant <= 0.0 {
+            return vec![];
+        }
 
+        let mut enter = self.record(ray, (-half_b - f64::sqrt(discriminant)) / a);
+        let mut exit = self.record(ray, (-half_b + f64::sqrt(discriminant)) / a);
+        enter.front_face = true;
+        exit.front_face = false;
+        vec![Interval { enter, exit }]
     }
 }
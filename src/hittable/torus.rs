@@ -0,0 +1,211 @@
+use std::{f64::consts::PI, sync::Arc};
+
+use crate::{
+    aabb::Aabb,
+    hittable::{azimuth_u, first_in_range, pair_crossings, HitRecord, Hittable, Interval, Solid},
+    material::Material,
+    ray::Ray,
+    roots::solve_quartic,
+    vec3::{dot, unit_vector, Point, Vec3},
+};
+
+/// A torus around the y axis: a tube of `minor_radius` swept around a circle
+/// of `major_radius` in the xz plane. The texture coordinate `u` runs around
+/// the y axis and `v` around the tube.
+pub struct Torus {
+    center: Point,
+    major_radius: f64,
+    minor_radius: f64,
+    material: Arc<dyn Material + Sync + Send>,
+}
+
+impl Torus {
+    pub fn new(
+        center: Point,
+        major_radius: f64,
+        minor_radius: f64,
+        material: Arc<dyn Material + Sync + Send>,
+    ) -> Self {
+        Self {
+            center,
+            major_radius,
+            minor_radius,
+            material,
+        }
+    }
+
+    /// Value of the implicit function, negative inside the tube.
+    pub fn implicit(&self, p: Point) -> f64 {
+        let local = p - self.center;
+        let (big, small) = (self.major_radius, self.minor_radius);
+        let s = local.length_squared() + big * big - small * small;
+        s * s - 4.0 * big * big * (local.x() * local.x() + local.z() * local.z())
+    }
+
+    /// Every crossing of the surface along the ray's line, sorted by `t`.
+    pub(crate) fn crossings(&self, ray: &Ray) -> Vec<HitRecord> {
+        let length = ray.direction().length();
+        let d = ray.direction() / length;
+
+        // Solve from the point on the line closest to the centre to keep the
+        // quartic's coefficients small.
+        let t_closest = dot(self.center - ray.origin(), d);
+        let o = ray.origin() + t_closest * d - self.center;
+        if o.length() > self.major_radius + self.minor_radius {
+            return vec![];
+        }
+
+        let (big, small) = (self.major_radius, self.minor_radius);
+        let e = o.length_squared() - big * big - small * small;
+        let f = dot(o, d);
+        let roots = solve_quartic(
+            1.0,
+            4.0 * f,
+            2.0 * e + 4.0 * f * f + 4.0 * big * big * d.y() * d.y(),
+            4.0 * f * e + 8.0 * big * big * o.y() * d.y(),
+            e * e - 4.0 * big * big * (small * small - o.y() * o.y()),
+        );
+
+        roots
+            .into_iter()
+            .map(|s| {
+                let t = (t_closest + s) / length;
+                let p = ray.at(t);
+                let local = p - self.center;
+                // Direction from the nearest point on the tube's core circle.
+                let radial = unit_vector(Vec3::new(local.x(), 0.0, local.z()));
+                let outward_normal = unit_vector(local - big * radial);
+                let tube_angle = f64::atan2(local.y(), dot(local, radial) - big);
+                let uv = (
+                    azimuth_u(local.x(), local.z()),
+                    (tube_angle + PI) / (2.0 * PI),
+                );
+                let material = Some(Arc::clone(&self.material));
+                HitRecord::new(p, t, material, outward_normal, ray, uv)
+            })
+            .collect()
+    }
+}
+
+impl Hittable for Torus {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        first_in_range(self.crossings(ray), t_min, t_max)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let r = self.major_radius + self.minor_radius;
+        let extent = Vec3::new(r, self.minor_radius, r);
+        Some(Aabb::new(self.center - extent, self.center + extent))
+    }
+}
+
+impl Solid for Torus {
+    fn intervals(&self, ray: &Ray) -> Vec<Interval> {
+        pair_crossings(ray, self.crossings(ray))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        hittable::{Hittable, Solid},
+        material::lambertian::Lambertian,
+        ray::Ray,
+        utils::random_double,
+        vec3::{dot, random_unit_vector, Color, Point, Vec3},
+    };
+
+    use super::Torus;
+
+    fn torus() -> Torus {
+        Torus::new(
+            Point::new(1.0, 2.0, 3.0),
+            2.0,
+            0.5,
+            Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))),
+        )
+    }
+
+    #[test]
+    fn axis_aligned_rays() {
+        let torus = torus();
+        let ray = Ray::new(Point::new(-9.0, 2.0, 3.0), Vec3::new(2.0, 0.0, 0.0));
+        let ts: Vec<f64> = torus.crossings(&ray).iter().map(|rec| rec.t).collect();
+        let expected = [3.75, 4.25, 5.75, 6.25];
+        assert_eq!(ts.len(), 4);
+        for (t, e) in ts.iter().zip(expected) {
+            assert!(f64::abs(t - e) < 1e-9, "{ts:?}");
+        }
+        let rec = torus.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        assert!((rec.normal - Vec3::new(-1.0, 0.0, 0.0)).length() < 1e-9);
+
+        // Straight down through the hole.
+        let ray = Ray::new(Point::new(1.0, 10.0, 3.0), Vec3::new(0.0, -1.0, 0.0));
+        assert!(torus.hit(&ray, 0.001, f64::INFINITY).is_none());
+    }
+
+    #[test]
+    fn grazing_the_hole_keeps_the_tube_interval() {
+        // Tangent to the inside of the ring at t = 10, between entering the
+        // tube at t = 8 and leaving it at t = 12.
+        let ray = Ray::new(Point::new(2.5, 2.0, -7.0), Vec3::new(0.0, 0.0, 1.0));
+        let intervals = torus().intervals(&ray);
+        assert_eq!(intervals.len(), 1);
+        assert!(f64::abs(intervals[0].enter.t - 8.0) < 1e-6);
+        assert!(f64::abs(intervals[0].exit.t - 12.0) < 1e-6);
+    }
+
+    /// Finds the first sign change of the implicit function by marching.
+    fn march(torus: &Torus, ray: &Ray, t_max: f64) -> Option<f64> {
+        let steps = 20_000;
+        let dt = t_max / steps as f64;
+        let mut previous = torus.implicit(ray.origin());
+        for i in 1..=steps {
+            let t = i as f64 * dt;
+            let value = torus.implicit(ray.at(t));
+            if value.signum() != previous.signum() {
+                return Some(t);
+            }
+            previous = value;
+        }
+        None
+    }
+
+    #[test]
+    fn sampled_rays_match_marching() {
+        let torus = torus();
+        let center = Point::new(1.0, 2.0, 3.0);
+        let mut hits = 0;
+        for _ in 0..300 {
+            // Aim from outside at random points near the torus.
+            let origin = center + 6.0 * random_unit_vector();
+            let target = center + Vec3::new(random_double(-2.5, 2.5), random_double(-0.6, 0.6), random_double(-2.5, 2.5));
+            let ray = Ray::new(origin, target - origin);
+
+            let found = torus.hit(&ray, 1e-6, f64::INFINITY);
+            let marched = march(&torus, &ray, 3.0);
+            match (found, marched) {
+                (Some(rec), Some(t)) => {
+                    hits += 1;
+                    assert!(f64::abs(rec.t - t) < 1e-3, "solver {} vs march {t}", rec.t);
+                    assert!(dot(rec.normal, ray.direction()) < 0.0);
+                    let p = rec.p - center;
+                    let ring = Vec3::new(p.x(), 0.0, p.z());
+                    let distance_to_core = (p - 2.0 * ring / ring.length()).length();
+                    assert!(f64::abs(distance_to_core - 0.5) < 1e-6);
+                }
+                (None, None) => {}
+                (found, marched) => {
+                    // Grazing rays can legitimately disagree within a step.
+                    let found = found.map(|rec| rec.t);
+                    let t = found.or(marched).unwrap();
+                    let p = ray.at(t);
+                    assert!(f64::abs(torus.implicit(p)) < 1e-2, "{found:?} vs {marched:?}");
+                }
+            }
+        }
+        assert!(hits > 100);
+    }
+}
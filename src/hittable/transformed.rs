@@ -0,0 +1,215 @@
+use std::sync::Arc;
+
+use crate::{
+    aabb::Aabb,
+    hittable::{HitRecord, Hittable, Interval, Solid},
+    ray::Ray,
+    transform::{AnimatedTransform, Transform},
+    vec3::unit_vector,
+};
+
+/// Places a shared object in the world through an affine transform, so one
+/// object can be instanced many times without copying it. Rays are moved into
+/// object space for intersection and hits are moved back out. A transformed
+/// `Solid` is a `Solid` too, so it can be combined in a `Csg`.
+pub struct Transformed<T: ?Sized = dyn Hittable + Sync + Send> {
+    object: Arc<T>,
+    transform: Transform,
+    bbox: Option<Aabb>,
+}
+
+impl<T: Hittable + ?Sized> Transformed<T> {
+    pub fn new(object: Arc<T>, transform: Transform) -> Self {
+        let bbox = object.bounding_box().map(|b| transform.aabb(&b));
+        Self {
+            object,
+            transform,
+            bbox,
+        }
+    }
+}
+
+/// Moves a hit on the object back out into world space. `t` carries over
+/// unchanged as the ray direction is not normalized, and the inverse
+/// transpose preserves the sign of dot(direction, normal), so the face
+/// orientation does too.
+fn to_world(transform: &Transform, mut rec: HitRecord) -> HitRecord {
+    rec.p = transform.point(rec.p);
+    rec.normal = unit_vector(transform.normal(rec.normal));
+    rec.tangent = transform.vector(rec.tangent);
+    rec
+}
+
+fn hit_transformed<T: Hittable + ?Sized>(
+    object: &T,
+    transform: &Transform,
+    ray: &Ray,
+    t_min: f64,
+    t_max: f64,
+) -> Option<HitRecord> {
+    let object_ray = transform.inverse().ray(ray);
+    let rec = object.hit(&object_ray, t_min, t_max)?;
+    Some(to_world(transform, rec))
+}
+
+fn intervals_transformed<T: Solid + ?Sized>(object: &T, transform: &Transform, ray: &Ray) -> Vec<Interval> {
+    let object_ray = transform.inverse().ray(ray);
+    object
+        .intervals(&object_ray)
+        .into_iter()
+        .map(|interval| Interval {
+            enter: to_world(transform, interval.enter),
+            exit: to_world(transform, interval.exit),
+        })
+        .collect()
+}
+
+impl<T: Hittable + ?Sized> Hittable for Transformed<T> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        hit_transformed(self.object.as_ref(), &self.transform, ray, t_min, t_max)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.bbox
+    }
+}
+
+impl<T: Solid + ?Sized> Solid for Transformed<T> {
+    fn intervals(&self, ray: &Ray) -> Vec<Interval> {
+        intervals_transformed(self.object.as_ref(), &self.transform, ray)
+    }
+}
+
+/// Like `Transformed`, but the transform is animated and evaluated at the
+/// time of each ray, giving motion blur.
+pub struct Moving<T: ?Sized = dyn Hittable + Sync + Send> {
+    object: Arc<T>,
+    motion: AnimatedTransform,
+    bbox: Option<Aabb>,
+}
+
+impl<T: Hittable + ?Sized> Moving<T> {
+    pub fn new(object: Arc<T>, motion: AnimatedTransform) -> Self {
+        let bbox = object.bounding_box().map(|b| motion.aabb(&b));
+        Self {
+            object,
+            motion,
+            bbox,
+        }
+    }
+}
+
+impl<T: Hittable + ?Sized> Hittable for Moving<T> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let transform = self.motion.at(ray.time());
+        hit_transformed(self.object.as_ref(), &transform, ray, t_min, t_max)
+    }
+
+    /// Covers the object over the whole animation.
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.bbox
+    }
+}
+
+impl<T: Solid + ?Sized> Solid for Moving<T> {
+    fn intervals(&self, ray: &Ray) -> Vec<Interval> {
+        intervals_transformed(self.object.as_ref(), &self.motion.at(ray.time()), ray)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        aabb::Aabb,
+        hittable::{sphere::Sphere, HitRecord, Hittable},
+        material::lambertian::Lambertian,
+        ray::Ray,
+        spectrum::SampledWavelengths,
+        transform::{AnimatedTransform, Keyframe, Transform},
+        vec3::{Color, Point, Vec3},
+    };
+
+    use super::{Moving, Transformed};
+
+    /// Hits at `t = 1` only for rays carrying the given wavelengths.
+    struct WavelengthProbe(SampledWavelengths);
+
+    impl Hittable for WavelengthProbe {
+        fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+            if ray.wavelengths() != Some(self.0) || t_min > 1.0 || t_max < 1.0 {
+                return None;
+            }
+            let material = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+            Some(HitRecord::in_volume(ray.at(1.0), 1.0, material))
+        }
+
+        fn bounding_box(&self) -> Option<Aabb> {
+            None
+        }
+    }
+
+    #[test]
+    fn scaled_and_translated_sphere() {
+        let sphere = Arc::new(Sphere::new(
+            Point::new(0.0, 0.0, 0.0),
+            1.0,
+            Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))),
+        ));
+        let transform = Transform::scale(Vec3::new(2.0, 1.0, 1.0))
+            .then(&Transform::translate(Vec3::new(0.0, 0.0, -5.0)));
+        let instance = Transformed::new(sphere, transform);
+
+        let ray = Ray::new(Point::new(-10.0, 0.0, -5.0), Vec3::new(1.0, 0.0, 0.0));
+        let rec = instance.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        assert!(f64::abs(rec.t - 8.0) < 1e-9);
+        assert!((rec.p - Point::new(-2.0, 0.0, -5.0)).length() < 1e-9);
+        assert!((rec.normal - Vec3::new(-1.0, 0.0, 0.0)).length() < 1e-9);
+        assert!(rec.front_face);
+
+        let bbox = instance.bounding_box().unwrap();
+        assert!((bbox.min() - Point::new(-2.0, -1.0, -6.0)).length() < 1e-9);
+        assert!((bbox.max() - Point::new(2.0, 1.0, -4.0)).length() < 1e-9);
+
+        let miss = Ray::new(Point::new(-10.0, 1.5, -5.0), Vec3::new(1.0, 0.0, 0.0));
+        assert!(instance.hit(&miss, 0.001, f64::INFINITY).is_none());
+    }
+
+    #[test]
+    fn moving_sphere_follows_ray_time() {
+        let sphere = Arc::new(Sphere::new(
+            Point::new(0.0, 0.0, 0.0),
+            1.0,
+            Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))),
+        ));
+        let moving = Moving::new(
+            sphere,
+            AnimatedTransform::linear(
+                Keyframe::translation(0.0, Vec3::new(0.0, 0.0, 0.0)),
+                Keyframe::translation(1.0, Vec3::new(0.0, 4.0, 0.0)),
+            ),
+        );
+        let ray = Ray::new(Point::new(0.0, 2.0, -5.0), Vec3::new(0.0, 0.0, 1.0));
+        assert!(moving.hit(&ray.with_time(0.0), 0.001, f64::INFINITY).is_none());
+        let rec = moving.hit(&ray.with_time(0.5), 0.001, f64::INFINITY).unwrap();
+        assert!(f64::abs(rec.t - 4.0) < 1e-9);
+        assert!(moving.hit(&ray.with_time(1.0), 0.001, f64::INFINITY).is_none());
+
+        let bbox = moving.bounding_box().unwrap();
+        assert!((bbox.max() - Point::new(1.0, 5.0, 1.0)).length() < 1e-9);
+    }
+
+    #[test]
+    fn object_space_rays_keep_wavelengths() {
+        let lambda = SampledWavelengths::sample_uniform(0.3);
+        let instance = Transformed::new(
+            Arc::new(WavelengthProbe(lambda)),
+            Transform::translate(Vec3::new(0.0, 0.0, -5.0)),
+        );
+        let ray = Ray::new(Point::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, -1.0));
+        assert!(instance.hit(&ray, 0.001, f64::INFINITY).is_none());
+        let rec = instance.hit(&ray.with_wavelengths(lambda), 0.001, f64::INFINITY).unwrap();
+        assert!((rec.p - Point::new(0.0, 0.0, -1.0)).length() < 1e-9);
+    }
+}
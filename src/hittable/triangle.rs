@@ -0,0 +1,219 @@
+use std::sync::Arc;
+
+use crate::{
+    aabb::Aabb,
+    hittable::{bvh::BvhNode, HitRecord, Hittable},
+    material::Material,
+    mesh::Mesh,
+    ray::Ray,
+    vec3::{cross, dot, unit_vector, Point, Vec3},
+};
+
+/// Moller-Trumbore intersection, returning `t` and the barycentric weights of
+/// `b` and `c`.
+pub(crate) fn intersect_triangle(
+    ray: &Ray,
+    (a, b, c): (Point, Point, Point),
+    t_min: f64,
+    t_max: f64,
+) -> Option<(f64, f64, f64)> {
+    let (e1, e2) = (b - a, c - a);
+    let pvec = cross(ray.direction(), e2);
+    let det = dot(e1, pvec);
+    if f64::abs(det) < 1e-12 {
+        return None;
+    }
+    let inv_det = 1.0 / det;
+    let tvec = ray.origin() - a;
+    let b1 = dot(tvec, pvec) * inv_det;
+    if !(0.0..=1.0).contains(&b1) {
+        return None;
+    }
+    let qvec = cross(tvec, e1);
+    let b2 = dot(ray.direction(), qvec) * inv_det;
+    if b2 < 0.0 || b1 + b2 > 1.0 {
+        return None;
+    }
+    let t = dot(e2, qvec) * inv_det;
+    (t >= t_min && t <= t_max).then_some((t, b1, b2))
+}
+
+/// One triangle of a shared mesh. Vertex normals and texture coordinates are
+/// interpolated when the mesh has them; otherwise the triangle is flat and
+/// uses its barycentric coordinates as texture coordinates.
+pub struct Triangle {
+    mesh: Arc<Mesh>,
+    index: usize,
+    material: Arc<dyn Material + Sync + Send>,
+}
+
+impl Triangle {
+    pub fn new(mesh: Arc<Mesh>, index: usize, material: Arc<dyn Material + Sync + Send>) -> Self {
+        Self { mesh, index, material }
+    }
+
+    fn vertices(&self) -> [usize; 3] {
+        self.mesh.indices[self.index]
+    }
+}
+
+impl Hittable for Triangle {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let [a, b, c] = self.vertices();
+        let positions = &self.mesh.positions;
+        let (pa, pb, pc) = (positions[a], positions[b], positions[c]);
+        let (t, b1, b2) = intersect_triangle(ray, (pa, pb, pc), t_min, t_max)?;
+        let b0 = 1.0 - b1 - b2;
+
+        let geometric = unit_vector(cross(pb - pa, pc - pa));
+        let shading = if self.mesh.normals.is_empty() {
+            None
+        } else {
+            let normals = &self.mesh.normals;
+            let shading = b0 * normals[a] + b1 * normals[b] + b2 * normals[c];
+            (!shading.near_zero()).then(|| unit_vector(shading))
+        };
+        let uv = if self.mesh.uvs.is_empty() {
+            (b1, b2)
+        } else {
+            let uvs = &self.mesh.uvs;
+            (
+                b0 * uvs[a].0 + b1 * uvs[b].0 + b2 * uvs[c].0,
+                b0 * uvs[a].1 + b1 * uvs[b].1 + b2 * uvs[c].1,
+            )
+        };
+
+        // Which side was hit is decided by the winding, as an interpolated
+        // normal can face away from a ray that hits the front near an edge.
+        // The shading normal is then flipped to the side that was hit.
+        let material = Some(Arc::clone(&self.material));
+        let mut rec = HitRecord::new(ray.at(t), t, material, geometric, ray, uv);
+        if let Some(shading) = shading {
+            rec.normal = if rec.front_face { shading } else { -shading };
+        }
+        Some(rec)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let [a, b, c] = self.vertices().map(|i| self.mesh.positions[i]);
+        // Pad so that axis-aligned triangles still have a box with volume.
+        let pad = Vec3::new(1e-4, 1e-4, 1e-4);
+        let min = Point::new(a.x().min(b.x()).min(c.x()), a.y().min(b.y()).min(c.y()), a.z().min(b.z()).min(c.z()));
+        let max = Point::new(a.x().max(b.x()).max(c.x()), a.y().max(b.y()).max(c.y()), a.z().max(b.z()).max(c.z()));
+        Some(Aabb::new(min - pad, max + pad))
+    }
+}
+
+/// All the triangles of a mesh in their own bounding volume hierarchy.
+pub struct TriangleMesh {
+    mesh: Arc<Mesh>,
+    bvh: BvhNode,
+}
+
+impl TriangleMesh {
+    /// Panics if the mesh has no triangles.
+    pub fn new(mesh: Mesh, material: Arc<dyn Material + Sync + Send>) -> Self {
+        let mesh = Arc::new(mesh);
+        let triangles = (0..mesh.indices.len())
+            .map(|i| Arc::new(Triangle::new(Arc::clone(&mesh), i, Arc::clone(&material))) as Arc<dyn Hittable + Sync + Send>)
+            .collect();
+        Self {
+            mesh,
+            bvh: BvhNode::new(triangles),
+        }
+    }
+
+    pub fn mesh(&self) -> &Mesh {
+        &self.mesh
+    }
+}
+
+impl Hittable for TriangleMesh {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        self.bvh.hit(ray, t_min, t_max)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.bvh.bounding_box()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        hittable::Hittable,
+        material::lambertian::Lambertian,
+        mesh::Mesh,
+        ray::Ray,
+        vec3::{dot, unit_vector, Color, Point, Vec3},
+    };
+
+    use super::TriangleMesh;
+
+    fn material() -> Arc<Lambertian> {
+        Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)))
+    }
+
+    fn square() -> Mesh {
+        Mesh::new(
+            vec![
+                Point::new(0.0, 0.0, 0.0),
+                Point::new(1.0, 0.0, 0.0),
+                Point::new(1.0, 1.0, 0.0),
+                Point::new(0.0, 1.0, 0.0),
+            ],
+            vec![[0, 1, 2], [0, 2, 3]],
+        )
+    }
+
+    #[test]
+    fn flat_triangles() {
+        let mesh = TriangleMesh::new(square(), material());
+        let ray = Ray::new(Point::new(0.25, 0.75, 2.0), Vec3::new(0.0, 0.0, -1.0));
+        let rec = mesh.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        assert_eq!(rec.t, 2.0);
+        assert_eq!(rec.normal, Vec3::new(0.0, 0.0, 1.0));
+        assert!(rec.front_face);
+
+        let miss = Ray::new(Point::new(1.25, 0.75, 2.0), Vec3::new(0.0, 0.0, -1.0));
+        assert!(mesh.hit(&miss, 0.001, f64::INFINITY).is_none());
+    }
+
+    #[test]
+    fn interpolated_normals_and_uvs() {
+        let mut square = square();
+        square.normals = vec![
+            unit_vector(Vec3::new(-1.0, -1.0, 1.0)),
+            unit_vector(Vec3::new(1.0, -1.0, 1.0)),
+            unit_vector(Vec3::new(1.0, 1.0, 1.0)),
+            unit_vector(Vec3::new(-1.0, 1.0, 1.0)),
+        ];
+        square.uvs = vec![(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)];
+        let mesh = TriangleMesh::new(square, material());
+
+        let ray = Ray::new(Point::new(0.5, 0.5, 2.0), Vec3::new(0.0, 0.0, -1.0));
+        let rec = mesh.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        assert!((rec.normal - Vec3::new(0.0, 0.0, 1.0)).length() < 1e-12);
+        assert!(f64::abs(rec.u - 0.5) < 1e-12 && f64::abs(rec.v - 0.5) < 1e-12);
+
+        let ray = Ray::new(Point::new(0.9, 0.5, 2.0), Vec3::new(0.0, 0.0, -1.0));
+        let rec = mesh.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        assert!(rec.normal.x() > 0.0);
+        assert!(f64::abs(rec.u - 0.9) < 1e-12);
+
+        // Grazing the front towards the edge, where the shading normal leans
+        // away from the ray: still a front face hit with the normal as given.
+        let ray = Ray::new(Point::new(0.4, 0.5, 0.05), Vec3::new(1.0, 0.0, -0.1));
+        let rec = mesh.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        assert!(dot(ray.direction(), rec.normal) > 0.0);
+        assert!(rec.front_face);
+
+        // From behind, both flip.
+        let ray = Ray::new(Point::new(0.9, 0.5, -2.0), Vec3::new(0.0, 0.0, 1.0));
+        let rec = mesh.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        assert!(!rec.front_face);
+        assert!(rec.normal.x() < 0.0 && rec.normal.z() < 0.0);
+    }
+}
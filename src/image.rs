@@ -1,4 +1,4 @@
-use std::io::{self, Write};
+use std::io::{self, BufRead, Write};
 
 use crate::{vec3::Color, utils::clamp};
 
@@ -37,4 +37,119 @@ pub fn print_ppm_binary(pixel_colors: &[Color], image_width: i32, image_height:
     }).collect();
 
     io::stdout().write_all(&image).unwrap();
-}
\ No newline at end of file
+}
+
+/// Single-channel image with values in `[0, 1]`, stored row by row from the
+/// top.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GrayImage {
+    width: usize,
+    height: usize,
+    data: Vec<f64>,
+}
+
+impl GrayImage {
+    pub fn new(width: usize, height: usize, data: Vec<f64>) -> Self {
+        assert_eq!(data.len(), width * height, "image data does not match its size");
+        Self { width, height, data }
+    }
+
+    /// Reads an ASCII (`P2`) or binary (`P5`) PGM file, with 8 or 16 bit
+    /// samples.
+    pub fn read_pgm(mut reader: impl BufRead) -> io::Result<Self> {
+        let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+
+        let mut header = Vec::new();
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+
+        // Header tokens are whitespace separated, with `#` comments, and end
+        // with a single whitespace byte before binary data.
+        let mut pos = 0;
+        while header.len() < 4 {
+            while pos < bytes.len() && (bytes[pos].is_ascii_whitespace() || bytes[pos] == b'#') {
+                if bytes[pos] == b'#' {
+                    while pos < bytes.len() && bytes[pos] != b'\n' {
+                        pos += 1;
+                    }
+                } else {
+                    pos += 1;
+                }
+            }
+            let start = pos;
+            while pos < bytes.len() && !bytes[pos].is_ascii_whitespace() {
+                pos += 1;
+            }
+            if start == pos {
+                return Err(invalid("truncated PGM header"));
+            }
+            header.push(String::from_utf8_lossy(&bytes[start..pos]).into_owned());
+        }
+        pos += 1;
+
+        let number = |token: &str| token.parse::<usize>().map_err(|_| invalid("invalid PGM header"));
+        let (width, height, max_value) = (number(&header[1])?, number(&header[2])?, number(&header[3])?);
+        if max_value == 0 || max_value > 65535 {
+            return Err(invalid("invalid PGM maximum value"));
+        }
+        let count = width * height;
+        let scale = 1.0 / max_value as f64;
+
+        let data: Vec<f64> = match header[0].as_str() {
+            "P2" => {
+                let text = String::from_utf8_lossy(bytes.get(pos..).unwrap_or_default());
+                text.split_whitespace()
+                    .take(count)
+                    .map(|token| number(token).map(|v| v as f64 * scale))
+                    .collect::<io::Result<_>>()?
+            }
+            "P5" => {
+                let size = if max_value < 256 { 1 } else { 2 };
+                let raw = bytes.get(pos..pos + count * size).ok_or_else(|| invalid("truncated PGM data"))?;
+                raw.chunks(size)
+                    .map(|c| c.iter().fold(0usize, |acc, &b| acc << 8 | b as usize) as f64 * scale)
+                    .collect()
+            }
+            _ => return Err(invalid("not a PGM file")),
+        };
+        if data.len() != count {
+            return Err(invalid("truncated PGM data"));
+        }
+        Ok(Self::new(width, height, data))
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> f64 {
+        self.data[y * self.width + x]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GrayImage;
+
+    #[test]
+    fn reads_ascii_and_binary_pgm() {
+        let ascii = "P2\n# comment\n3 2\n4\n0 1 2\n3 4 0\n";
+        let image = GrayImage::read_pgm(ascii.as_bytes()).unwrap();
+        assert_eq!((image.width(), image.height()), (3, 2));
+        assert_eq!(image.get(1, 0), 0.25);
+        assert_eq!(image.get(1, 1), 1.0);
+
+        let mut binary = b"P5 2 1 65535\n".to_vec();
+        binary.extend_from_slice(&[0xff, 0xff, 0x00, 0x00]);
+        let image = GrayImage::read_pgm(binary.as_slice()).unwrap();
+        assert_eq!(image.get(0, 0), 1.0);
+        assert_eq!(image.get(1, 0), 0.0);
+
+        assert!(GrayImage::read_pgm("P6 1 1 255\n".as_bytes()).is_err());
+        assert!(GrayImage::read_pgm("P5 2 2 255\n\0".as_bytes()).is_err());
+    }
+}
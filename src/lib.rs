@@ -1,18 +1,33 @@
 use std::{sync::Mutex, time::Duration};
 
-use camera::Camera;
+use camera::{
+    stereo::{Eye, StereoLayout, StereoRig},
+    Camera,
+};
 use hittable::{Hittable, HittableList};
 use indicatif::{ProgressBar, ProgressStyle};
 use ray::Ray;
+use spectrum::{SampledSpectrum, SampledWavelengths};
 use rayon::prelude::{IndexedParallelIterator, IntoParallelIterator, ParallelIterator};
 use utils::random_double;
 use vec3::{unit_vector, Color, Vec3};
 
+pub mod aabb;
 pub mod camera;
 pub mod hittable;
 pub mod image;
 pub mod material;
+pub mod matrix;
+pub mod mesh;
+pub mod onb;
+pub mod perlin;
 pub mod ray;
+pub mod roots;
+pub mod scene;
+pub mod sdf;
+pub mod spectrum;
+pub mod texture;
+pub mod transform;
 pub mod utils;
 pub mod vec3;
 
@@ -21,9 +36,62 @@ pub fn create_image(
     image_width: i32,
     samples_per_pixel: i32,
     max_depth: i32,
-    cam: &Camera,
+    cam: &(dyn Camera + Sync),
     world: &HittableList,
 ) -> Vec<Color> {
+    render(image_height, image_width, samples_per_pixel, cam, |ray| {
+        ray_color(ray, world, max_depth)
+    })
+}
+
+/// Renders both eyes of `rig` with `create_image`, one after the other, and
+/// packs them into one frame. The image size given is per eye.
+pub fn create_stereo_image(
+    image_height: i32,
+    image_width: i32,
+    samples_per_pixel: i32,
+    max_depth: i32,
+    rig: &dyn StereoRig,
+    layout: StereoLayout,
+    world: &HittableList,
+) -> Vec<Color> {
+    let [left, right] = [Eye::Left, Eye::Right].map(|eye| {
+        let cam = rig.eye(eye);
+        create_image(image_height, image_width, samples_per_pixel, max_depth, cam.as_ref(), world)
+    });
+    layout.combine(left, right, image_width as usize)
+}
+
+/// Renders like `create_image`, but traces each camera sample at a set of
+/// hero-sampled wavelengths and converts the result back to RGB through the
+/// CIE colour matching functions. Required for wavelength dependent effects
+/// such as dispersion.
+pub fn create_spectral_image(
+    image_height: i32,
+    image_width: i32,
+    samples_per_pixel: i32,
+    max_depth: i32,
+    cam: &(dyn Camera + Sync),
+    world: &HittableList,
+) -> Vec<Color> {
+    render(image_height, image_width, samples_per_pixel, cam, |ray| {
+        let mut lambda = SampledWavelengths::sample_uniform(random_double(0.0, 1.0));
+        let ray = ray.with_wavelengths(lambda);
+        let radiance = ray_color_spectral(&ray, world, max_depth, &mut lambda);
+        radiance.to_rgb(&lambda)
+    })
+}
+
+fn render<F>(
+    image_height: i32,
+    image_width: i32,
+    samples_per_pixel: i32,
+    cam: &(dyn Camera + Sync),
+    sample: F,
+) -> Vec<Color>
+where
+    F: Fn(&Ray) -> Color + Sync,
+{
     let progress = Mutex::new(
         ProgressBar::new(image_height as u64)
             .with_elapsed(Duration::new(0, 0))
@@ -45,8 +113,10 @@ pub fn create_image(
                         (0..samples_per_pixel).fold(Color::new(0.0, 0.0, 0.0), |acc, _| {
                             let u = (x as f64 + random_double(0.0, 1.0)) / (image_width) as f64;
                             let v = (y as f64 + random_double(0.0, 1.0)) / (image_height) as f64;
-                            let ray = cam.get_ray(u, v);
-                            acc + ray_color(&ray, world, max_depth)
+                            match cam.get_ray(u, v) {
+                                Some(ray) => acc + sample(&ray),
+                                None => acc,
+                            }
                         });
                     color
                 })
@@ -57,6 +127,12 @@ pub fn create_image(
         .collect()
 }
 
+fn background(ray: &Ray) -> Color {
+    let unit_direction = unit_vector(ray.direction());
+    let t = 0.5 * (unit_direction.y() + 1.0);
+    (1.0 - t) * Color::new(1.0, 1.0, 1.0) + t * Color::new(0.5, 0.7, 1.0)
+}
+
 fn ray_color(ray: &Ray, world: &dyn Hittable, depth: i32) -> Color {
     if depth <= 0 {
         return Color::new(0.0, 0.0, 0.0);
@@ -64,13 +140,47 @@ fn ray_color(ray: &Ray, world: &dyn Hittable, depth: i32) -> Color {
 
     if let Some(rec) = world.hit(ray, 0.001, f64::INFINITY) {
         if let Some(ref mat) = rec.material {
+            let emitted = mat.emitted(rec.u, rec.v, rec.p);
             if let Some((attenuation, scattered)) = mat.scatter(ray, &rec) {
-                return attenuation * ray_color(&scattered, world, depth - 1);
+                let scattered = scattered.with_time(ray.time());
+                return emitted + attenuation * ray_color(&scattered, world, depth - 1);
             }
+            return emitted;
         }
         return Color::new(0.0, 0.0, 0.0);
     }
-    let unit_direction = unit_vector(ray.direction());
-    let t = 0.5 * (unit_direction.y() + 1.0);
-    return (1.0 - t) * Color::new(1.0, 1.0, 1.0) + t * Color::new(0.5, 0.7, 1.0);
+    background(ray)
+}
+
+/// Spectral counterpart of `ray_color`. RGB emissions are upsampled at the
+/// path's wavelengths, and materials report spectral attenuations through
+/// `scatter_spectral`. Materials may hand back a scattered ray carrying
+/// updated wavelengths, e.g. with the secondary wavelengths terminated, which
+/// then apply to the rest of the path.
+fn ray_color_spectral(
+    ray: &Ray,
+    world: &dyn Hittable,
+    depth: i32,
+    lambda: &mut SampledWavelengths,
+) -> SampledSpectrum {
+    if depth <= 0 {
+        return SampledSpectrum::constant(0.0);
+    }
+
+    if let Some(rec) = world.hit(ray, 0.001, f64::INFINITY) {
+        if let Some(ref mat) = rec.material {
+            let emitted = SampledSpectrum::from_rgb(mat.emitted(rec.u, rec.v, rec.p), lambda);
+            if let Some((attenuation, scattered)) = mat.scatter_spectral(ray, &rec, lambda) {
+                if let Some(updated) = scattered.wavelengths() {
+                    *lambda = updated;
+                }
+                let scattered = scattered.with_wavelengths(*lambda).with_time(ray.time());
+                return emitted
+                    + attenuation * ray_color_spectral(&scattered, world, depth - 1, lambda);
+            }
+            return emitted;
+        }
+        return SampledSpectrum::constant(0.0);
+    }
+    SampledSpectrum::from_rgb(background(ray), lambda)
 }
@@ -4,7 +4,7 @@ use raytracer::material::{dielectric::Dielectric, lambertian::Lambertian, metal:
 use raytracer::{create_image};
 use std::sync::{Arc};
 
-use raytracer::camera::Camera;
+use raytracer::camera::ProjectiveCamera;
 use raytracer::hittable::sphere::Sphere;
 use raytracer::hittable::HittableList;
 use raytracer::utils::random_double;
@@ -85,7 +85,7 @@ fn main() {
     // world
     let world = random_scene();
 
-    let cam = Camera::new(
+    let cam = ProjectiveCamera::new(
         Point::new(13.0, 2.0, 3.0),
         Point::new(0.0, 0.0, 0.0),
         Vec3::new(0.0, 1.0, 0.0),
@@ -1,11 +1,37 @@
-use crate::{ray::Ray, hittable::HitRecord, vec3::{Color, Vec3, dot}};
+use crate::{ray::Ray, hittable::HitRecord, spectrum::{SampledSpectrum, SampledWavelengths}, vec3::{Color, Point, Vec3, dot}};
 
 pub mod lambertian;
 pub mod metal;
 pub mod dielectric;
+pub mod mix;
+pub mod coated;
+pub mod isotropic;
+pub mod henyey_greenstein;
+pub mod subsurface;
+pub mod thin_film;
+pub mod diffuse_light;
+pub mod hair;
 
 pub trait Material {
     fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<(Color, Ray)>;
+
+    fn emitted(&self, _u: f64, _v: f64, _p: Point) -> Color {
+        Color::new(0.0, 0.0, 0.0)
+    }
+
+    /// Spectral counterpart of `scatter`, for paths carrying the wavelengths
+    /// `lambda`. By default the RGB attenuation is upsampled at them;
+    /// materials whose attenuation varies faster with wavelength than an RGB
+    /// colour can express override this.
+    fn scatter_spectral(
+        &self,
+        r_in: &Ray,
+        rec: &HitRecord,
+        lambda: &SampledWavelengths,
+    ) -> Option<(SampledSpectrum, Ray)> {
+        let (attenuation, scattered) = self.scatter(r_in, rec)?;
+        Some((SampledSpectrum::from_rgb(attenuation, lambda), scattered))
+    }
 }
 
 pub fn reflect(v: Vec3, n: Vec3) -> Vec3 {
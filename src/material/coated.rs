@@ -0,0 +1,168 @@
+use std::sync::Arc;
+
+use crate::{
+    hittable::HitRecord,
+    material::Material,
+    ray::Ray,
+    spectrum::{SampledSpectrum, SampledWavelengths},
+    utils::random_double,
+    vec3::{dot, unit_vector, Color, Point},
+};
+
+use super::{dielectric::Dielectric, reflect};
+
+/// A dielectric clearcoat layered over an arbitrary base material, e.g.
+/// varnish over wood or the lacquer on car paint. Light is specularly
+/// reflected off the coat with Fresnel probability, otherwise it reaches the
+/// base. Light emitted by the base shines out through the coat.
+pub struct Coated {
+    base: Arc<dyn Material + Sync + Send>,
+    index_of_refraction: f64,
+    tint: Color,
+}
+
+impl Coated {
+    pub fn new(base: Arc<dyn Material + Sync + Send>, index_of_refraction: f64) -> Self {
+        Self::with_tint(base, index_of_refraction, Color::new(1.0, 1.0, 1.0))
+    }
+
+    /// `tint` attenuates light on each pass through the coat, in and out.
+    pub fn with_tint(
+        base: Arc<dyn Material + Sync + Send>,
+        index_of_refraction: f64,
+        tint: Color,
+    ) -> Self {
+        Self {
+            base,
+            index_of_refraction,
+            tint,
+        }
+    }
+}
+
+impl Coated {
+    /// The ray specularly reflected off the coat, if the light does not get
+    /// through to the base.
+    fn coat_reflection(&self, r_in: &Ray, rec: &HitRecord) -> Option<Ray> {
+        let unit_direction = unit_vector(r_in.direction());
+        let cos_theta = f64::min(dot(-unit_direction, rec.normal), 1.0);
+        let reflectance = Dielectric::reflectance(cos_theta, 1.0 / self.index_of_refraction);
+        (reflectance > random_double(0.0, 1.0)).then(|| Ray::new(rec.p, reflect(unit_direction, rec.normal)))
+    }
+}
+
+impl Material for Coated {
+    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<(Color, Ray)> {
+        if !rec.front_face {
+            return self.base.scatter(r_in, rec);
+        }
+        if let Some(reflected) = self.coat_reflection(r_in, rec) {
+            return Some((Color::new(1.0, 1.0, 1.0), reflected));
+        }
+        self.base
+            .scatter(r_in, rec)
+            .map(|(attenuation, scattered)| (self.tint * self.tint * attenuation, scattered))
+    }
+
+    fn scatter_spectral(
+        &self,
+        r_in: &Ray,
+        rec: &HitRecord,
+        lambda: &SampledWavelengths,
+    ) -> Option<(SampledSpectrum, Ray)> {
+        if !rec.front_face {
+            return self.base.scatter_spectral(r_in, rec, lambda);
+        }
+        if let Some(reflected) = self.coat_reflection(r_in, rec) {
+            return Some((SampledSpectrum::constant(1.0), reflected));
+        }
+        let tint = SampledSpectrum::from_rgb(self.tint * self.tint, lambda);
+        self.base
+            .scatter_spectral(r_in, rec, lambda)
+            .map(|(attenuation, scattered)| (tint * attenuation, scattered))
+    }
+
+    fn emitted(&self, u: f64, v: f64, p: Point) -> Color {
+        self.tint * self.base.emitted(u, v, p)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        hittable::HitRecord,
+        material::{diffuse_light::DiffuseLight, lambertian::Lambertian, thin_film::ThinFilm, Material},
+        ray::Ray,
+        spectrum::{SampledWavelengths, N_SPECTRUM_SAMPLES},
+        vec3::{Color, Point, Vec3},
+    };
+
+    use super::Coated;
+
+    fn hit(ray: &Ray) -> HitRecord {
+        HitRecord::new(Point::new(0.0, 0.0, 0.0), 1.0, None, Vec3::new(0.0, 0.0, 1.0), ray, (0.0, 0.0))
+    }
+
+    #[test]
+    fn reflects_off_the_coat_or_reaches_the_base() {
+        let base = Color::new(0.8, 0.4, 0.2);
+        let tint = Color::new(0.9, 0.9, 0.5);
+        let coated = Coated::with_tint(Arc::new(Lambertian::new(base)), 1.5, tint);
+        let ray = Ray::new(Point::new(0.0, 1.0, 1.0), Vec3::new(0.0, -1.0, -1.0));
+        let rec = hit(&ray);
+
+        let mut reflections = 0;
+        for _ in 0..2000 {
+            let (attenuation, scattered) = coated.scatter(&ray, &rec).unwrap();
+            if attenuation == Color::new(1.0, 1.0, 1.0) {
+                reflections += 1;
+                let mirror = Vec3::new(0.0, -1.0, 1.0) / f64::sqrt(2.0);
+                assert!((scattered.direction() - mirror).length() < 1e-12);
+            } else {
+                assert_eq!(attenuation, tint * tint * base);
+            }
+        }
+        // Fresnel reflectance of glass at 45° is about 4%.
+        assert!((50..=150).contains(&reflections), "{reflections}");
+
+        // From inside, the coat is skipped.
+        let inside = Ray::new(Point::new(0.0, 0.0, -1.0), Vec3::new(0.0, 0.0, 1.0));
+        for _ in 0..100 {
+            assert_eq!(coated.scatter(&inside, &hit(&inside)).unwrap().0, base);
+        }
+    }
+
+    #[test]
+    fn forwards_emission_through_the_tint() {
+        let coated = Coated::with_tint(
+            Arc::new(DiffuseLight::new(Color::new(2.0, 2.0, 2.0))),
+            1.5,
+            Color::new(1.0, 0.5, 0.25),
+        );
+        assert_eq!(coated.emitted(0.0, 0.0, Point::new(0.0, 0.0, 0.0)), Color::new(2.0, 1.0, 0.5));
+    }
+
+    #[test]
+    fn forwards_spectral_scattering_to_the_base() {
+        let coated = Coated::new(Arc::new(ThinFilm::conductor(300.0, 1.38, 0.958, 6.69)), 1.5);
+        let ray = Ray::new(Point::new(0.0, 0.0, 1.0), Vec3::new(0.0, 0.0, -1.0));
+        let rec = hit(&ray);
+        let lambda = SampledWavelengths::sample_uniform(0.5);
+
+        let mut through_coat = 0;
+        for _ in 0..100 {
+            let (attenuation, _) = coated.scatter_spectral(&ray, &rec, &lambda).unwrap();
+            if attenuation.value(0) == 1.0 {
+                continue;
+            }
+            through_coat += 1;
+            for i in 0..N_SPECTRUM_SAMPLES {
+                let film = ThinFilm::conductor_reflectance(1.0, lambda.lambda(i), 1.0, 1.38, 300.0, 0.958, 6.69);
+                assert!(f64::abs(attenuation.value(i) - film) < 1e-9);
+            }
+        }
+        assert!(through_coat > 0);
+    }
+}
@@ -1,51 +1,231 @@
 use crate::{
+    hittable::HitRecord,
     material::Material,
-    vec3::{unit_vector, Color, dot}, utils::random_double, ray::Ray,
+    vec3::{unit_vector, Color, Vec3, dot}, utils::random_double, ray::Ray,
 };
 
 use super::{reflect, refract};
 
+/// Wavelength of the sodium D line in nanometres, at which the index of
+/// dispersive dielectrics is evaluated when rendering in RGB.
+pub const SODIUM_D_WAVELENGTH: f64 = 587.6;
+
+/// Index of refraction of a dielectric as a function of wavelength.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IndexOfRefraction {
+    Constant(f64),
+    /// `n = a + b / lambda^2`, with `lambda` in micrometres.
+    Cauchy { a: f64, b: f64 },
+    /// `n^2 = 1 + sum(b_i lambda^2 / (lambda^2 - c_i))`, with `lambda` in
+    /// micrometres.
+    Sellmeier { b: [f64; 3], c: [f64; 3] },
+}
+
+impl IndexOfRefraction {
+    /// Index of refraction at `lambda` nanometres.
+    pub fn at(&self, lambda: f64) -> f64 {
+        let lambda_um = lambda / 1000.0;
+        let l2 = lambda_um * lambda_um;
+        match *self {
+            IndexOfRefraction::Constant(n) => n,
+            IndexOfRefraction::Cauchy { a, b } => a + b / l2,
+            IndexOfRefraction::Sellmeier { b, c } => {
+                let n2 = 1.0 + (0..3).map(|i| b[i] * l2 / (l2 - c[i])).sum::<f64>();
+                f64::sqrt(n2)
+            }
+        }
+    }
+
+    pub fn is_dispersive(&self) -> bool {
+        !matches!(self, IndexOfRefraction::Constant(_))
+    }
+}
+
 pub struct Dielectric {
-    index_of_refraction: f64,
+    index_of_refraction: IndexOfRefraction,
+    absorption: Color,
 }
 
 impl Dielectric {
     pub fn new(index_of_refraction: f64) -> Self {
+        Self::with_index(IndexOfRefraction::Constant(index_of_refraction))
+    }
+
+    pub fn with_index(index_of_refraction: IndexOfRefraction) -> Self {
         Self {
             index_of_refraction,
+            absorption: Color::new(0.0, 0.0, 0.0),
+        }
+    }
+
+    /// Absorption coefficient per unit distance travelled inside the medium,
+    /// for tinted glass and liquids. Transmittance follows the Beer-Lambert
+    /// law, `exp(-absorption * distance)`, with the distance taken from the
+    /// ray's origin to the back face where it leaves. Only meaningful on
+    /// closed objects, as open ones have no inside to absorb in.
+    pub fn with_absorption(mut self, absorption: Color) -> Self {
+        self.absorption = absorption;
+        self
+    }
+
+    fn transmittance(&self, r_in: &Ray, rec: &HitRecord) -> Color {
+        if rec.front_face {
+            return Color::new(1.0, 1.0, 1.0);
         }
+        let distance = rec.t * r_in.direction().length();
+        Color::new(
+            f64::exp(-self.absorption.x() * distance),
+            f64::exp(-self.absorption.y() * distance),
+            f64::exp(-self.absorption.z() * distance),
+        )
     }
+
+    pub fn cauchy(a: f64, b: f64) -> Self {
+        Self::with_index(IndexOfRefraction::Cauchy { a, b })
+    }
+
+    pub fn sellmeier(b: [f64; 3], c: [f64; 3]) -> Self {
+        Self::with_index(IndexOfRefraction::Sellmeier { b, c })
+    }
+
+    /// Schott N-BK7 borosilicate crown glass.
+    pub fn bk7() -> Self {
+        Self::sellmeier(
+            [1.03961212, 0.231792344, 1.01046945],
+            [0.00600069867, 0.0200179144, 103.560653],
+        )
+    }
+
+    /// Schott SF11 dense flint glass.
+    pub fn dense_flint() -> Self {
+        Self::sellmeier(
+            [1.73759695, 0.313747346, 1.89878101],
+            [0.013188707, 0.0623068142, 155.23629],
+        )
+    }
+
+    pub fn diamond() -> Self {
+        Self::sellmeier([0.3306, 4.3356, 0.0], [0.1750 * 0.1750, 0.1060 * 0.1060, 0.0])
+    }
+
     pub fn reflectance(cosine: f64, ref_idx: f64) -> f64 {
         let r0 = ((1.0 - ref_idx) / (1.0 + ref_idx)).powi(2);
         r0 + (1.0 - r0) * (1.0 - cosine).powi(5)
     }
-}
 
-impl Material for Dielectric {
-    fn scatter(
-        &self,
-        r_in: &crate::ray::Ray,
-        rec: &crate::hittable::HitRecord,
-    ) -> Option<(Color, Ray)> {
+    /// Picks the reflected or refracted direction of `unit_direction` at a
+    /// boundary with a medium of the given index of refraction, choosing
+    /// stochastically by Fresnel reflectance.
+    pub fn boundary_direction(unit_direction: Vec3, rec: &HitRecord, index_of_refraction: f64) -> Vec3 {
         let refraction_ratio = if rec.front_face {
-            1.0 / self.index_of_refraction
+            1.0 / index_of_refraction
         } else {
-            self.index_of_refraction
+            index_of_refraction
         };
 
-        let unit_direction = unit_vector(r_in.direction());
         let cos_theta = f64::min(dot(-unit_direction, rec.normal), 1.0);
         let sin_theta = f64::sqrt(1.0 - cos_theta.powi(2));
 
         let cannot_refract = refraction_ratio * sin_theta > 1.0;
-        let direction = if cannot_refract || Dielectric::reflectance(cos_theta, refraction_ratio) > random_double(0.0, 1.0) {
+        if cannot_refract || Dielectric::reflectance(cos_theta, refraction_ratio) > random_double(0.0, 1.0) {
             reflect(unit_direction, rec.normal)
         } else {
             refract(unit_direction, rec.normal, refraction_ratio)
-        };
+        }
+    }
+}
+
+impl Material for Dielectric {
+    fn scatter(
+        &self,
+        r_in: &crate::ray::Ray,
+        rec: &HitRecord,
+    ) -> Option<(Color, Ray)> {
+        let unit_direction = unit_vector(r_in.direction());
+        let attenuation = self.transmittance(r_in, rec);
+
+        match r_in.wavelengths() {
+            Some(mut lambda) if self.index_of_refraction.is_dispersive() => {
+                // Each wavelength bends differently, so only the hero
+                // wavelength can follow the refracted path.
+                let index = self.index_of_refraction.at(lambda.hero());
+                let direction = Dielectric::boundary_direction(unit_direction, rec, index);
+                lambda.terminate_secondary();
+                let scattered = Ray::new(rec.p, direction).with_wavelengths(lambda);
+                Some((attenuation, scattered))
+            }
+            _ => {
+                let index = self.index_of_refraction.at(SODIUM_D_WAVELENGTH);
+                let direction = Dielectric::boundary_direction(unit_direction, rec, index);
+                Some((attenuation, Ray::new(rec.p, direction)))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        hittable::HitRecord,
+        material::Material,
+        ray::Ray,
+        spectrum::SampledWavelengths,
+        vec3::{Color, Point, Vec3},
+    };
+
+    use super::{Dielectric, SODIUM_D_WAVELENGTH};
+
+    fn assert_index(material: Dielectric, expected: f64) {
+        let n = material.index_of_refraction.at(SODIUM_D_WAVELENGTH);
+        assert!(f64::abs(n - expected) < 1e-3, "{n} != {expected}");
+    }
+
+    #[test]
+    fn preset_indices() {
+        assert_index(Dielectric::new(1.5), 1.5);
+        assert_index(Dielectric::bk7(), 1.5168);
+        assert_index(Dielectric::dense_flint(), 1.7847);
+        assert_index(Dielectric::diamond(), 2.4175);
+    }
+
+    #[test]
+    fn normal_dispersion() {
+        for material in [Dielectric::cauchy(1.5046, 0.0042), Dielectric::bk7(), Dielectric::diamond()] {
+            let blue = material.index_of_refraction.at(450.0);
+            let red = material.index_of_refraction.at(650.0);
+            assert!(blue > red);
+        }
+    }
+
+    #[test]
+    fn dispersion_terminates_secondary_wavelengths() {
+        let lambda = SampledWavelengths::sample_uniform(0.5);
+        let ray = Ray::new(Point::new(0.0, 1.0, 1.0), Vec3::new(0.0, -1.0, -1.0)).with_wavelengths(lambda);
+        let rec = HitRecord::new(Point::new(0.0, 0.0, 0.0), 1.0, None, Vec3::new(0.0, 1.0, 0.0), &ray, (0.0, 0.0));
+
+        let (_, scattered) = Dielectric::bk7().scatter(&ray, &rec).unwrap();
+        assert!(scattered.wavelengths().unwrap().secondary_terminated());
+
+        let (_, scattered) = Dielectric::new(1.5).scatter(&ray, &rec).unwrap();
+        assert!(scattered.wavelengths().is_none());
+    }
+
+    #[test]
+    fn beer_lambert_absorption() {
+        let absorption = Color::new(0.1, 0.5, 1.0);
+        let material = Dielectric::new(1.5).with_absorption(absorption);
+
+        // Exiting through a face at distance 2 after travelling inside.
+        let ray = Ray::new(Point::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 2.0));
+        let rec = HitRecord::new(ray.at(1.0), 1.0, None, Vec3::new(0.0, 0.0, 1.0), &ray, (0.0, 0.0));
+        let (attenuation, _) = material.scatter(&ray, &rec).unwrap();
+        let expected = Color::new(f64::exp(-0.2), f64::exp(-1.0), f64::exp(-2.0));
+        assert!((attenuation - expected).length() < 1e-12);
 
-        let attenuation = Color::new(1.0, 1.0, 1.0);
-        let scattered = Ray::new(rec.p, direction);
-        Some((attenuation, scattered))
+        // Entering from outside is not attenuated.
+        let ray = Ray::new(Point::new(0.0, 0.0, 4.0), Vec3::new(0.0, 0.0, -2.0));
+        let rec = HitRecord::new(ray.at(1.0), 1.0, None, Vec3::new(0.0, 0.0, 1.0), &ray, (0.0, 0.0));
+        let (attenuation, _) = material.scatter(&ray, &rec).unwrap();
+        assert_eq!(attenuation, Color::new(1.0, 1.0, 1.0));
     }
 }
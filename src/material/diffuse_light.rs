@@ -0,0 +1,35 @@
+use std::sync::Arc;
+
+use crate::{
+    hittable::HitRecord,
+    material::Material,
+    ray::Ray,
+    texture::{SolidColor, Texture},
+    vec3::{Color, Point},
+};
+
+/// An emissive material for area lights. It emits from both faces and does
+/// not scatter.
+pub struct DiffuseLight {
+    emit: Arc<dyn Texture + Sync + Send>,
+}
+
+impl DiffuseLight {
+    pub fn new(emit: Color) -> Self {
+        Self::with_texture(Arc::new(SolidColor::new(emit)))
+    }
+
+    pub fn with_texture(emit: Arc<dyn Texture + Sync + Send>) -> Self {
+        Self { emit }
+    }
+}
+
+impl Material for DiffuseLight {
+    fn scatter(&self, _: &Ray, _: &HitRecord) -> Option<(Color, Ray)> {
+        None
+    }
+
+    fn emitted(&self, u: f64, v: f64, p: Point) -> Color {
+        self.emit.value(u, v, p)
+    }
+}
@@ -0,0 +1,279 @@
+use std::f64::consts::PI;
+
+use crate::{
+    hittable::HitRecord,
+    material::Material,
+    onb::Onb,
+    ray::Ray,
+    utils::random_double,
+    vec3::{cross, dot, unit_vector, Color},
+};
+
+use super::dielectric::Dielectric;
+
+const LOBES: usize = 4;
+
+/// Hair fibre scattering after Marschner et al., in the energy conserving
+/// form of Chiang et al. Light reflects off the cuticle (R), passes straight
+/// through (TT), or reflects once inside (TRT), with all further paths
+/// lumped into a fourth, uniformly spread lobe.
+///
+/// Needs the fibre direction in `HitRecord::tangent` and the offset across
+/// the fibre in `v`, as given by `hittable::curve::Curve`. On other surfaces
+/// the fibre runs in an arbitrary direction across the normal.
+pub struct Hair {
+    absorption: Color,
+    index_of_refraction: f64,
+    /// Longitudinal variance of each lobe.
+    variance: [f64; LOBES],
+    /// Azimuthal logistic scale.
+    scale: f64,
+    /// `sin` and `cos` of the cuticle scale tilt times 1, 2 and 4.
+    tilt: [(f64, f64); 3],
+}
+
+fn logistic_cdf(x: f64, s: f64) -> f64 {
+    1.0 / (1.0 + f64::exp(-x / s))
+}
+
+/// Samples the logistic distribution of scale `s` restricted to `[a, b]`.
+fn sample_trimmed_logistic(u: f64, s: f64, a: f64, b: f64) -> f64 {
+    let k = logistic_cdf(b, s) - logistic_cdf(a, s);
+    let x = -s * f64::ln(1.0 / (u * k + logistic_cdf(a, s)) - 1.0);
+    x.clamp(a, b)
+}
+
+fn luminance(c: Color) -> f64 {
+    0.2126 * c.x() + 0.7152 * c.y() + 0.0722 * c.z()
+}
+
+fn exp(c: Color) -> Color {
+    Color::new(f64::exp(c.x()), f64::exp(c.y()), f64::exp(c.z()))
+}
+
+impl Hair {
+    /// `absorption` is per unit of fibre diameter. `beta_m` and `beta_n` in
+    /// `[0, 1]` set the longitudinal and azimuthal roughness.
+    pub fn new(absorption: Color, beta_m: f64, beta_n: f64) -> Self {
+        let v0 = (0.726 * beta_m + 0.812 * beta_m.powi(2) + 3.7 * beta_m.powi(20)).powi(2);
+        let scale = f64::sqrt(PI / 8.0) * (0.265 * beta_n + 1.194 * beta_n.powi(2) + 5.372 * beta_n.powi(22));
+        Self {
+            absorption,
+            index_of_refraction: 1.55,
+            variance: [v0, 0.25 * v0, 4.0 * v0, 4.0 * v0],
+            scale,
+            tilt: [(0.0, 1.0); 3],
+        }
+        .with_scale_angle(2.0)
+    }
+
+    /// Absorption from concentrations of the dark eumelanin and red
+    /// pheomelanin pigments. Eumelanin around 0.3 gives blonde hair, 1.3
+    /// brown and 8 black.
+    pub fn from_melanin(eumelanin: f64, pheomelanin: f64, beta_m: f64, beta_n: f64) -> Self {
+        let absorption = eumelanin * Color::new(0.419, 0.697, 1.37) + pheomelanin * Color::new(0.187, 0.4, 1.05);
+        Self::new(absorption, beta_m, beta_n)
+    }
+
+    /// Tilt of the cuticle scales, in degrees.
+    pub fn with_scale_angle(mut self, degrees: f64) -> Self {
+        let (sin, cos) = f64::sin_cos(degrees.to_radians());
+        self.tilt[0] = (sin, cos);
+        for k in 1..self.tilt.len() {
+            let (sin, cos) = self.tilt[k - 1];
+            self.tilt[k] = (2.0 * cos * sin, cos * cos - sin * sin);
+        }
+        self
+    }
+
+    /// `sin` and `cos` of the angle off the normal plane that `lobe` is
+    /// centred on for light leaving at `sin_theta_o` and `cos_theta_o`. The
+    /// scale tilt shifts the R lobe by twice its angle one way, and TT and
+    /// TRT by once and four times its angle the other.
+    fn tilted(&self, lobe: usize, sin_theta_o: f64, cos_theta_o: f64) -> (f64, f64) {
+        let (sin, cos) = match lobe {
+            0 => (self.tilt[1].0, self.tilt[1].1),
+            1 => (-self.tilt[0].0, self.tilt[0].1),
+            2 => (-self.tilt[2].0, self.tilt[2].1),
+            _ => (0.0, 1.0),
+        };
+        (sin_theta_o * cos - cos_theta_o * sin, cos_theta_o * cos + sin_theta_o * sin)
+    }
+
+    /// Attenuation of each lobe for light leaving at `sin_theta_o` off the
+    /// normal plane of the fibre, at offset `h` across it.
+    fn attenuation(&self, sin_theta_o: f64, h: f64) -> [Color; LOBES] {
+        let eta = self.index_of_refraction;
+        let cos_theta_o = f64::sqrt(1.0 - sin_theta_o * sin_theta_o);
+
+        // Transmittance of one pass through the fibre interior.
+        let sin_theta_t = sin_theta_o / eta;
+        let cos_theta_t = f64::sqrt(1.0 - sin_theta_t * sin_theta_t);
+        let eta_p = f64::sqrt(eta * eta - sin_theta_o * sin_theta_o) / cos_theta_o;
+        let sin_gamma_t = h / eta_p;
+        let cos_gamma_t = f64::sqrt(1.0 - sin_gamma_t * sin_gamma_t);
+        let transmittance = exp(-(2.0 * cos_gamma_t / cos_theta_t) * self.absorption);
+
+        let cos_gamma_o = f64::sqrt(1.0 - h * h);
+        let f = Dielectric::reflectance(cos_theta_o * cos_gamma_o, eta);
+        let r = f * Color::new(1.0, 1.0, 1.0);
+        let tt = (1.0 - f).powi(2) * transmittance;
+        let trt = f * tt * transmittance;
+        let ft = f * transmittance;
+        let rest = f * trt * transmittance
+            * Color::new(1.0 / (1.0 - ft.x()), 1.0 / (1.0 - ft.y()), 1.0 / (1.0 - ft.z()));
+        [r, tt, trt, rest]
+    }
+}
+
+impl Material for Hair {
+    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<(Color, Ray)> {
+        // Local frame with x along the fibre, z along the normal and y
+        // across the fibre, towards increasing `v`.
+        // Surfaces that give no fibre direction get an arbitrary one.
+        let z = rec.normal;
+        let along = rec.tangent - dot(rec.tangent, z) * z;
+        let x = if along.near_zero() {
+            Onb::build_from_w(z).u()
+        } else {
+            unit_vector(along)
+        };
+        let y = cross(z, x);
+
+        let wo = -unit_vector(r_in.direction());
+        let sin_theta_o = dot(wo, x).clamp(-1.0, 1.0);
+        let cos_theta_o = f64::sqrt(1.0 - sin_theta_o * sin_theta_o);
+        let phi_o = f64::atan2(dot(wo, z), dot(wo, y));
+        // Grazing the very edge of the fibre reflects everything, leaving
+        // the attenuation of the remaining lobes undefined.
+        let h = (2.0 * rec.v - 1.0).clamp(-0.9999, 0.9999);
+
+        // Pick a lobe in proportion to its attenuation. Each lobe's
+        // longitudinal and azimuthal terms are sampled exactly, so the
+        // weight is just the attenuation over the probability of the pick.
+        let attenuation = self.attenuation(sin_theta_o, h);
+        let weights = attenuation.map(luminance);
+        let total: f64 = weights.iter().sum();
+        if total <= 0.0 {
+            return None;
+        }
+        let mut pick = random_double(0.0, total);
+        let mut p = 0;
+        while p < LOBES - 1 && pick >= weights[p] {
+            pick -= weights[p];
+            p += 1;
+        }
+
+        let (sin_theta_op, cos_theta_op) = self.tilted(p, sin_theta_o, cos_theta_o);
+
+        // Longitudinal scattering.
+        let v = self.variance[p];
+        let u = f64::max(random_double(0.0, 1.0), 1e-5);
+        let cos_theta = 1.0 + v * f64::ln(u + (1.0 - u) * f64::exp(-2.0 / v));
+        let sin_theta = f64::sqrt(f64::max(0.0, 1.0 - cos_theta * cos_theta));
+        let cos_phi = f64::cos(2.0 * PI * random_double(0.0, 1.0));
+        let sin_theta_i = (-cos_theta * sin_theta_op + sin_theta * cos_phi * cos_theta_op).clamp(-1.0, 1.0);
+        let cos_theta_i = f64::sqrt(1.0 - sin_theta_i * sin_theta_i);
+
+        // Azimuthal scattering around the deflection of the lobe's path.
+        let dphi = if p < LOBES - 1 {
+            let eta = self.index_of_refraction;
+            let eta_p = f64::sqrt(eta * eta - sin_theta_o * sin_theta_o) / cos_theta_o;
+            let gamma_o = f64::asin(h);
+            let gamma_t = f64::asin((h / eta_p).clamp(-1.0, 1.0));
+            let p = p as f64;
+            let deflection = 2.0 * p * gamma_t - 2.0 * gamma_o + p * PI;
+            deflection + sample_trimmed_logistic(random_double(0.0, 1.0), self.scale, -PI, PI)
+        } else {
+            random_double(0.0, 2.0 * PI)
+        };
+        let phi_i = phi_o + dphi;
+
+        let direction = sin_theta_i * x + cos_theta_i * f64::cos(phi_i) * y + cos_theta_i * f64::sin(phi_i) * z;
+        Some((attenuation[p] * (total / weights[p]), Ray::new(rec.p, direction)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        hittable::HitRecord,
+        material::Material,
+        ray::Ray,
+        vec3::{Color, Point, Vec3},
+    };
+
+    use super::Hair;
+
+    fn hit(ray: &Ray, v: f64) -> HitRecord {
+        let mut rec = HitRecord::new(Point::new(0.0, 0.0, 0.0), 1.0, None, Vec3::new(0.0, 0.0, 1.0), ray, (0.5, v));
+        rec.tangent = Vec3::new(1.0, 0.0, 0.0);
+        rec
+    }
+
+    #[test]
+    fn clear_fibre_conserves_energy() {
+        let hair = Hair::new(Color::new(0.0, 0.0, 0.0), 0.3, 0.3);
+        let ray = Ray::new(Point::new(0.3, 0.0, 1.0), Vec3::new(-0.3, 0.0, -1.0));
+        for i in 0..200 {
+            let (attenuation, scattered) = hair.scatter(&ray, &hit(&ray, i as f64 / 199.0)).unwrap();
+            assert!((attenuation - Color::new(1.0, 1.0, 1.0)).length() < 1e-9);
+            assert!(f64::abs(scattered.direction().length() - 1.0) < 1e-9);
+        }
+    }
+
+    #[test]
+    fn melanin_darkens() {
+        let lobes = |hair: Hair| hair.attenuation(0.2, 0.3).iter().fold(Color::default(), |acc, &c| acc + c);
+        let blonde = lobes(Hair::from_melanin(0.3, 0.0, 0.3, 0.3));
+        let black = lobes(Hair::from_melanin(8.0, 0.0, 0.3, 0.3));
+        assert!(black.x() < blonde.x() && black.z() < blonde.z());
+        // Eumelanin absorbs blue more than red, so blonde hair is warm.
+        assert!(blonde.x() > blonde.z());
+    }
+
+    #[test]
+    fn rough_reflection_stays_near_specular_cone() {
+        // Light only reflects off the cuticle when the fibre is opaque.
+        let hair = Hair::from_melanin(100.0, 0.0, 0.1, 0.3).with_scale_angle(0.0);
+        let ray = Ray::new(Point::new(-0.5, 0.0, 1.0), Vec3::new(0.5, 0.0, -1.0));
+        let mut mean = 0.0;
+        for _ in 0..2000 {
+            let (_, scattered) = hair.scatter(&ray, &hit(&ray, 0.5)).unwrap();
+            mean += scattered.direction().x() / 2000.0;
+        }
+        // R reflects to the same angle on the far side of the normal plane.
+        let expected = 0.5 / f64::sqrt(1.25);
+        assert!(f64::abs(mean - expected) < 0.05, "{mean} != {expected}");
+    }
+
+    #[test]
+    fn scale_tilt_shifts_lobes() {
+        let alpha = 3.0;
+        let hair = Hair::new(Color::new(0.5, 0.5, 0.5), 0.3, 0.3).with_scale_angle(alpha);
+        let theta_o = 20.0_f64.to_radians();
+        let shift = |lobe| {
+            let (sin, cos) = hair.tilted(lobe, theta_o.sin(), theta_o.cos());
+            (theta_o - f64::atan2(sin, cos)).to_degrees()
+        };
+        assert!(f64::abs(shift(0) - 2.0 * alpha) < 1e-9);
+        assert!(f64::abs(shift(1) + alpha) < 1e-9);
+        assert!(f64::abs(shift(2) + 4.0 * alpha) < 1e-9);
+        assert!(f64::abs(shift(3)) < 1e-9);
+    }
+
+    #[test]
+    fn missing_tangent_falls_back_to_any_fibre_direction() {
+        let hair = Hair::new(Color::new(0.5, 0.5, 0.5), 0.3, 0.3);
+        let ray = Ray::new(Point::new(0.3, 0.0, 1.0), Vec3::new(-0.3, 0.0, -1.0));
+        for tangent in [Vec3::default(), Vec3::new(0.0, 0.0, 2.0)] {
+            let mut rec = hit(&ray, 0.5);
+            rec.tangent = tangent;
+            for _ in 0..100 {
+                let (attenuation, scattered) = hair.scatter(&ray, &rec).unwrap();
+                assert!(attenuation.x().is_finite() && attenuation.z().is_finite());
+                assert!(f64::abs(scattered.direction().length() - 1.0) < 1e-9);
+            }
+        }
+    }
+}
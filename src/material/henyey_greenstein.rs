@@ -0,0 +1,115 @@
+use std::{f64::consts::PI, sync::Arc};
+
+use crate::{
+    hittable::{grid_medium::VoxelGrid, HitRecord},
+    material::Material,
+    onb::Onb,
+    ray::Ray,
+    utils::{clamp, random_double},
+    vec3::{Color, Point},
+};
+
+/// Anisotropic phase function for participating media. `g` in `(-1, 1)`
+/// controls the mean scattering cosine: positive values scatter forwards,
+/// negative values backwards and zero is isotropic.
+pub struct HenyeyGreenstein {
+    albedo: Color,
+    g: f64,
+    emission: Option<(Color, Arc<VoxelGrid>)>,
+}
+
+impl HenyeyGreenstein {
+    pub fn new(albedo: Color, g: f64) -> Self {
+        Self {
+            albedo,
+            g: clamp(g, -0.999, 0.999),
+            emission: None,
+        }
+    }
+
+    /// Makes the medium glow, e.g. for fire. The emitted radiance at a point
+    /// is `radiance` scaled by the value of `grid` there, and by the fraction
+    /// of light the medium absorbs rather than scatters.
+    pub fn with_emission(mut self, radiance: Color, grid: Arc<VoxelGrid>) -> Self {
+        self.emission = Some((radiance, grid));
+        self
+    }
+
+    /// Probability density of scattering by an angle with cosine `cos_theta`
+    /// relative to the direction of travel.
+    pub fn phase(&self, cos_theta: f64) -> f64 {
+        let denom = 1.0 + self.g * self.g - 2.0 * self.g * cos_theta;
+        (1.0 - self.g * self.g) / (4.0 * PI * denom * f64::sqrt(denom))
+    }
+
+    /// Samples the cosine of the scattering angle relative to the direction
+    /// of travel.
+    pub fn sample_cos_theta(&self) -> f64 {
+        let xi = random_double(0.0, 1.0);
+        if f64::abs(self.g) < 1e-3 {
+            return 1.0 - 2.0 * xi;
+        }
+        let g = self.g;
+        let sqr_term = (1.0 - g * g) / (1.0 - g + 2.0 * g * xi);
+        clamp((1.0 + g * g - sqr_term * sqr_term) / (2.0 * g), -1.0, 1.0)
+    }
+}
+
+impl Material for HenyeyGreenstein {
+    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<(Color, Ray)> {
+        let cos_theta = self.sample_cos_theta();
+        let sin_theta = f64::sqrt(f64::max(0.0, 1.0 - cos_theta * cos_theta));
+        let phi = 2.0 * PI * random_double(0.0, 1.0);
+
+        let uvw = Onb::build_from_w(r_in.direction());
+        let direction = uvw.local(
+            sin_theta * f64::cos(phi),
+            sin_theta * f64::sin(phi),
+            cos_theta,
+        );
+        Some((self.albedo, Ray::new(rec.p, direction)))
+    }
+
+    fn emitted(&self, _: f64, _: f64, p: Point) -> Color {
+        match &self.emission {
+            Some((radiance, grid)) => {
+                let absorbed = Color::new(1.0, 1.0, 1.0) - self.albedo;
+                grid.value_at(p) * absorbed * *radiance
+            }
+            None => Color::new(0.0, 0.0, 0.0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::vec3::Color;
+
+    use super::HenyeyGreenstein;
+
+    #[test]
+    fn mean_cosine_matches_g() {
+        for g in [-0.6, 0.0, 0.3, 0.8] {
+            let hg = HenyeyGreenstein::new(Color::new(1.0, 1.0, 1.0), g);
+            let n = 200_000;
+            let mean = (0..n).map(|_| hg.sample_cos_theta()).sum::<f64>() / n as f64;
+            assert!(f64::abs(mean - g) < 0.01, "g = {g}, mean = {mean}");
+        }
+    }
+
+    #[test]
+    fn phase_is_normalized() {
+        let hg = HenyeyGreenstein::new(Color::new(1.0, 1.0, 1.0), 0.5);
+        // Integrate over the sphere: 2 pi * integral over cos_theta in [-1, 1].
+        let n = 10_000;
+        let integral: f64 = (0..n)
+            .map(|i| {
+                let cos_theta = -1.0 + 2.0 * (i as f64 + 0.5) / n as f64;
+                hg.phase(cos_theta) * 2.0 / n as f64
+            })
+            .sum::<f64>()
+            * 2.0
+            * std::f64::consts::PI;
+        assert!(f64::abs(integral - 1.0) < 1e-3);
+    }
+}
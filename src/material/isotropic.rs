@@ -0,0 +1,25 @@
+use crate::{
+    hittable::HitRecord,
+    material::Material,
+    ray::Ray,
+    vec3::{random_unit_vector, Color},
+};
+
+/// Phase function for participating media that scatters uniformly in all
+/// directions.
+pub struct Isotropic {
+    albedo: Color,
+}
+
+impl Isotropic {
+    pub fn new(albedo: Color) -> Self {
+        Self { albedo }
+    }
+}
+
+impl Material for Isotropic {
+    fn scatter(&self, _: &Ray, rec: &HitRecord) -> Option<(Color, Ray)> {
+        let scattered = Ray::new(rec.p, random_unit_vector());
+        Some((self.albedo, scattered))
+    }
+}
@@ -1,11 +1,14 @@
+use std::sync::Arc;
+
 use crate::{
     material::Material,
     ray::Ray,
+    texture::{SolidColor, Texture},
     vec3::{random_unit_vector, Color, random_in_unit_sphere, random_in_hemisphere},
 };
 
 pub struct Lambertian {
-    albedo: Color,
+    albedo: Arc<dyn Texture + Sync + Send>,
     distribution: Distribution,
 }
 
@@ -18,6 +21,10 @@ pub enum Distribution {
 
 impl Lambertian {
     pub fn new(albedo: Color) -> Self {
+        Self::with_texture(Arc::new(SolidColor::new(albedo)))
+    }
+
+    pub fn with_texture(albedo: Arc<dyn Texture + Sync + Send>) -> Self {
         Self {
             albedo,
             distribution: Distribution::Lambertian,
@@ -42,7 +49,7 @@ impl Material for Lambertian {
             scatter_direction = rec.normal;
         }
         let scattered = Ray::new(rec.p, scatter_direction);
-        let attenuation = self.albedo;
+        let attenuation = self.albedo.value(rec.u, rec.v, rec.p);
         Some((attenuation, scattered))
     }
 }
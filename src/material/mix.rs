@@ -0,0 +1,153 @@
+use std::sync::Arc;
+
+use crate::{
+    hittable::HitRecord,
+    material::Material,
+    ray::Ray,
+    spectrum::{SampledSpectrum, SampledWavelengths},
+    texture::{SolidColor, Texture},
+    utils::{clamp, random_double},
+    vec3::{Color, Point},
+};
+
+/// Blends two materials by stochastically picking one of them per scatter
+/// event. A weight of 0 is entirely `first`, a weight of 1 entirely `second`.
+/// Emission is blended by the same weight.
+pub struct MixMaterial {
+    first: Arc<dyn Material + Sync + Send>,
+    second: Arc<dyn Material + Sync + Send>,
+    weight: Arc<dyn Texture + Sync + Send>,
+}
+
+impl MixMaterial {
+    pub fn new(
+        first: Arc<dyn Material + Sync + Send>,
+        second: Arc<dyn Material + Sync + Send>,
+        weight: f64,
+    ) -> Self {
+        let weight = clamp(weight, 0.0, 1.0);
+        Self::with_texture(
+            first,
+            second,
+            Arc::new(SolidColor::new(Color::new(weight, weight, weight))),
+        )
+    }
+
+    /// The weight is the average of the texture's channels at the hit point.
+    pub fn with_texture(
+        first: Arc<dyn Material + Sync + Send>,
+        second: Arc<dyn Material + Sync + Send>,
+        weight: Arc<dyn Texture + Sync + Send>,
+    ) -> Self {
+        Self {
+            first,
+            second,
+            weight,
+        }
+    }
+
+    fn weight(&self, u: f64, v: f64, p: Point) -> f64 {
+        let w = self.weight.value(u, v, p);
+        clamp((w.x() + w.y() + w.z()) / 3.0, 0.0, 1.0)
+    }
+
+    fn pick(&self, rec: &HitRecord) -> &Arc<dyn Material + Sync + Send> {
+        if random_double(0.0, 1.0) < self.weight(rec.u, rec.v, rec.p) {
+            &self.second
+        } else {
+            &self.first
+        }
+    }
+}
+
+impl Material for MixMaterial {
+    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<(Color, Ray)> {
+        self.pick(rec).scatter(r_in, rec)
+    }
+
+    fn scatter_spectral(
+        &self,
+        r_in: &Ray,
+        rec: &HitRecord,
+        lambda: &SampledWavelengths,
+    ) -> Option<(SampledSpectrum, Ray)> {
+        self.pick(rec).scatter_spectral(r_in, rec, lambda)
+    }
+
+    fn emitted(&self, u: f64, v: f64, p: Point) -> Color {
+        let w = self.weight(u, v, p);
+        (1.0 - w) * self.first.emitted(u, v, p) + w * self.second.emitted(u, v, p)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        hittable::HitRecord,
+        material::{diffuse_light::DiffuseLight, lambertian::Lambertian, thin_film::ThinFilm, Material},
+        ray::Ray,
+        spectrum::{SampledWavelengths, N_SPECTRUM_SAMPLES},
+        vec3::{Color, Point, Vec3},
+    };
+
+    use super::MixMaterial;
+
+    #[test]
+    fn constant_weight_extremes() {
+        let red = Color::new(1.0, 0.0, 0.0);
+        let blue = Color::new(0.0, 0.0, 1.0);
+        let ray = Ray::new(Point::new(0.0, 0.0, 1.0), Vec3::new(0.0, 0.0, -1.0));
+        let rec = HitRecord::new(
+            Point::new(0.0, 0.0, 0.0),
+            1.0,
+            None,
+            Vec3::new(0.0, 0.0, 1.0),
+            &ray,
+            (0.0, 0.0),
+        );
+
+        let all_first = MixMaterial::new(
+            Arc::new(Lambertian::new(red)),
+            Arc::new(Lambertian::new(blue)),
+            0.0,
+        );
+        let all_second = MixMaterial::new(
+            Arc::new(Lambertian::new(red)),
+            Arc::new(Lambertian::new(blue)),
+            1.0,
+        );
+        for _ in 0..32 {
+            assert_eq!(all_first.scatter(&ray, &rec).unwrap().0, red);
+            assert_eq!(all_second.scatter(&ray, &rec).unwrap().0, blue);
+        }
+    }
+
+    #[test]
+    fn blends_emission() {
+        let lamp = MixMaterial::new(
+            Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))),
+            Arc::new(DiffuseLight::new(Color::new(4.0, 2.0, 0.0))),
+            0.25,
+        );
+        assert_eq!(lamp.emitted(0.0, 0.0, Point::new(0.0, 0.0, 0.0)), Color::new(1.0, 0.5, 0.0));
+    }
+
+    #[test]
+    fn forwards_spectral_scattering() {
+        let film = MixMaterial::new(
+            Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))),
+            Arc::new(ThinFilm::conductor(300.0, 1.38, 0.958, 6.69)),
+            1.0,
+        );
+        let ray = Ray::new(Point::new(0.0, 0.0, 1.0), Vec3::new(0.0, 0.0, -1.0));
+        let rec = HitRecord::new(Point::new(0.0, 0.0, 0.0), 1.0, None, Vec3::new(0.0, 0.0, 1.0), &ray, (0.0, 0.0));
+        let lambda = SampledWavelengths::sample_uniform(0.5);
+        let (attenuation, _) = film.scatter_spectral(&ray, &rec, &lambda).unwrap();
+        for i in 0..N_SPECTRUM_SAMPLES {
+            let expected = ThinFilm::conductor_reflectance(1.0, lambda.lambda(i), 1.0, 1.38, 300.0, 0.958, 6.69);
+            assert!(f64::abs(attenuation.value(i) - expected) < 1e-9);
+        }
+    }
+}
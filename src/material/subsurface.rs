@@ -0,0 +1,97 @@
+use crate::{
+    hittable::HitRecord,
+    material::Material,
+    ray::Ray,
+    utils::random_double,
+    vec3::{random_unit_vector, unit_vector, Color},
+};
+
+use super::dielectric::Dielectric;
+
+/// Translucent material for skin, wax, marble and the like. Light refracts
+/// into the object like a `Dielectric` and then random-walks through the
+/// interior, scattering isotropically after exponentially distributed
+/// distances until it refracts back out.
+///
+/// Scattering events are placed along the stretch of a ray that ends on a
+/// back face, taking the whole stretch back to the ray's origin to be inside,
+/// so the object must be a closed surface.
+pub struct Subsurface {
+    index_of_refraction: f64,
+    albedo: Color,
+    mean_free_path: f64,
+}
+
+impl Subsurface {
+    /// `albedo` is the fraction of light surviving each scattering event and
+    /// `mean_free_path` the average distance between events.
+    pub fn new(albedo: Color, mean_free_path: f64, index_of_refraction: f64) -> Self {
+        Self {
+            index_of_refraction,
+            albedo,
+            mean_free_path,
+        }
+    }
+}
+
+impl Material for Subsurface {
+    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<(Color, Ray)> {
+        let unit_direction = unit_vector(r_in.direction());
+
+        if !rec.front_face {
+            let ray_length = r_in.direction().length();
+            let distance_inside = rec.t * ray_length;
+            let scatter_distance = -self.mean_free_path * f64::ln(random_double(0.0, 1.0));
+            if scatter_distance < distance_inside {
+                let p = r_in.at(scatter_distance / ray_length);
+                return Some((self.albedo, Ray::new(p, random_unit_vector())));
+            }
+        }
+
+        let direction = Dielectric::boundary_direction(unit_direction, rec, self.index_of_refraction);
+        Some((Color::new(1.0, 1.0, 1.0), Ray::new(rec.p, direction)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        hittable::HitRecord,
+        material::Material,
+        ray::Ray,
+        vec3::{Color, Point, Vec3},
+    };
+
+    use super::Subsurface;
+
+    fn exit_hit(ray: &Ray) -> HitRecord {
+        // Leaving a surface at z = 1 whose outward normal faces +z.
+        HitRecord::new(ray.at(1.0), 1.0, None, Vec3::new(0.0, 0.0, 1.0), ray, (0.0, 0.0))
+    }
+
+    #[test]
+    fn dense_medium_scatters_inside() {
+        let albedo = Color::new(0.8, 0.5, 0.3);
+        let material = Subsurface::new(albedo, 1e-6, 1.5);
+        let ray = Ray::new(Point::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 1.0));
+        let rec = exit_hit(&ray);
+        assert!(!rec.front_face);
+        for _ in 0..32 {
+            let (attenuation, scattered) = material.scatter(&ray, &rec).unwrap();
+            assert_eq!(attenuation, albedo);
+            assert!(scattered.origin().z() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn thin_medium_leaves_through_boundary() {
+        let material = Subsurface::new(Color::new(0.8, 0.5, 0.3), 1e9, 1.5);
+        let ray = Ray::new(Point::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 1.0));
+        let rec = exit_hit(&ray);
+        for _ in 0..32 {
+            let (attenuation, scattered) = material.scatter(&ray, &rec).unwrap();
+            assert_eq!(attenuation, Color::new(1.0, 1.0, 1.0));
+            assert_eq!(scattered.origin(), rec.p);
+        }
+    }
+}
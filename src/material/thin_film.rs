@@ -0,0 +1,434 @@
+use std::{
+    f64::consts::PI,
+    ops::{Add, Div, Mul, Sub},
+};
+
+use crate::{
+    hittable::HitRecord,
+    material::Material,
+    ray::Ray,
+    spectrum::{SampledSpectrum, SampledWavelengths, N_SPECTRUM_SAMPLES},
+    utils::{clamp, random_double},
+    vec3::{dot, unit_vector, Color, Vec3},
+};
+
+use super::{reflect, refract};
+
+/// Wavelengths in nanometres that stand in for the red, green and blue
+/// channels when rendering in RGB.
+pub const RGB_WAVELENGTHS: [f64; 3] = [630.0, 532.0, 465.0];
+
+/// What lies beneath a `ThinFilm`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Substrate {
+    /// A transparent medium of this index, which the light the film lets
+    /// through refracts into.
+    Dielectric(f64),
+    /// A metal of complex index `eta + i k`, which absorbs the light the film
+    /// lets through.
+    Conductor { eta: f64, k: f64 },
+}
+
+/// A thin transparent film on a substrate, as on soap bubbles, oil slicks,
+/// coated lenses or anodized metal. Light reflected off the top and bottom of
+/// the film interferes, so the reflectance varies with wavelength, film
+/// thickness and viewing angle. The reflectance accounts for the
+/// film/substrate interface too, so light that is not reflected is refracted
+/// into a dielectric substrate or absorbed by a metal one.
+pub struct ThinFilm {
+    substrate: Substrate,
+    thickness: f64,
+    film_ior: f64,
+}
+
+impl ThinFilm {
+    /// Film on a transparent substrate of index `substrate_ior`, e.g. 1.0 for
+    /// a soap bubble, 1.33 for oil on water or 1.5 for coated glass.
+    /// `thickness` is in nanometres. Rays leaving the substrate see the film
+    /// from below.
+    pub fn new(thickness: f64, film_ior: f64, substrate_ior: f64) -> Self {
+        Self {
+            substrate: Substrate::Dielectric(substrate_ior),
+            thickness,
+            film_ior,
+        }
+    }
+
+    /// Film on a metal of complex index of refraction `eta + i k`, taken as
+    /// constant over the visible range, e.g. 0.96 and 6.69 for aluminium.
+    pub fn conductor(thickness: f64, film_ior: f64, eta: f64, k: f64) -> Self {
+        Self {
+            substrate: Substrate::Conductor { eta, k },
+            thickness,
+            film_ior,
+        }
+    }
+
+    /// Unpolarized reflectance of a film of index `film_ior` and `thickness`
+    /// nanometres between a medium of index `ior` above and `substrate_ior`
+    /// below, for light of wavelength `lambda` arriving at `cos_theta` to the
+    /// normal.
+    pub fn reflectance(
+        cos_theta: f64,
+        lambda: f64,
+        ior: f64,
+        film_ior: f64,
+        thickness: f64,
+        substrate_ior: f64,
+    ) -> f64 {
+        film_reflectance(cos_theta, lambda, ior, film_ior, thickness, Complex::real(substrate_ior))
+    }
+
+    /// Like `reflectance`, over a metal of complex index `eta + i k`.
+    pub fn conductor_reflectance(
+        cos_theta: f64,
+        lambda: f64,
+        ior: f64,
+        film_ior: f64,
+        thickness: f64,
+        eta: f64,
+        k: f64,
+    ) -> f64 {
+        film_reflectance(cos_theta, lambda, ior, film_ior, thickness, Complex::new(eta, k))
+    }
+
+    /// Reflectance at `lambda` for a ray arriving at `cos_theta` on the side
+    /// of the film given by `rec`.
+    fn reflectance_at(&self, rec: &HitRecord, cos_theta: f64, lambda: f64) -> f64 {
+        match self.substrate {
+            Substrate::Dielectric(n) if rec.front_face => {
+                Self::reflectance(cos_theta, lambda, 1.0, self.film_ior, self.thickness, n)
+            }
+            Substrate::Dielectric(n) => Self::reflectance(cos_theta, lambda, n, self.film_ior, self.thickness, 1.0),
+            Substrate::Conductor { eta, k } => {
+                Self::conductor_reflectance(cos_theta, lambda, 1.0, self.film_ior, self.thickness, eta, k)
+            }
+        }
+    }
+
+    /// Scatters off the film with the given `reflectance` at each wavelength,
+    /// of which the first `carried` decide between reflection and
+    /// transmission. The direction does not depend on wavelength, so every
+    /// wavelength follows the chosen path.
+    fn scatter_with<const N: usize>(
+        &self,
+        r_in: &Ray,
+        rec: &HitRecord,
+        reflectance: [f64; N],
+        carried: usize,
+    ) -> ([f64; N], Ray) {
+        let unit_direction = unit_vector(r_in.direction());
+        let reflected = Ray::new(rec.p, reflect(unit_direction, rec.normal));
+        let Substrate::Dielectric(substrate_ior) = self.substrate else {
+            return (reflectance, reflected);
+        };
+
+        let p_reflect = clamp(reflectance[..carried].iter().sum::<f64>() / carried as f64, 1e-4, 1.0 - 1e-4);
+        if random_double(0.0, 1.0) < p_reflect {
+            return (reflectance.map(|r| r / p_reflect), reflected);
+        }
+        let transmitted = reflectance.map(|r| (1.0 - r) / (1.0 - p_reflect));
+        (transmitted, Ray::new(rec.p, refracted(unit_direction, rec, substrate_ior)))
+    }
+}
+
+fn cos_incidence(r_in: &Ray, rec: &HitRecord) -> f64 {
+    f64::min(dot(-unit_vector(r_in.direction()), rec.normal), 1.0)
+}
+
+/// Direction of light the film lets through into a substrate of index
+/// `substrate_ior`. Past the critical angle the film reflects everything, so
+/// the reflected direction only stands in for the rare sample that gets here.
+fn refracted(unit_direction: Vec3, rec: &HitRecord, substrate_ior: f64) -> Vec3 {
+    let ratio = if rec.front_face { 1.0 / substrate_ior } else { substrate_ior };
+    let cos_theta = f64::min(dot(-unit_direction, rec.normal), 1.0);
+    if ratio * f64::sqrt(1.0 - cos_theta * cos_theta) > 1.0 {
+        reflect(unit_direction, rec.normal)
+    } else {
+        refract(unit_direction, rec.normal, ratio)
+    }
+}
+
+impl Material for ThinFilm {
+    /// Evaluates the film at wavelengths standing in for red, green and blue.
+    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<(Color, Ray)> {
+        let cos_theta = cos_incidence(r_in, rec);
+        let reflectance = RGB_WAVELENGTHS.map(|l| self.reflectance_at(rec, cos_theta, l));
+        let ([red, green, blue], scattered) = self.scatter_with(r_in, rec, reflectance, 3);
+        Some((Color::new(red, green, blue), scattered))
+    }
+
+    /// Evaluates the film at every wavelength the path carries. Once the
+    /// secondary wavelengths are terminated only the hero decides the path.
+    fn scatter_spectral(
+        &self,
+        r_in: &Ray,
+        rec: &HitRecord,
+        lambda: &SampledWavelengths,
+    ) -> Option<(SampledSpectrum, Ray)> {
+        let cos_theta = cos_incidence(r_in, rec);
+        let reflectance = std::array::from_fn(|i| self.reflectance_at(rec, cos_theta, lambda.lambda(i)));
+        let carried = if lambda.secondary_terminated() { 1 } else { N_SPECTRUM_SAMPLES };
+        let (attenuation, scattered) = self.scatter_with(r_in, rec, reflectance, carried);
+        Some((SampledSpectrum::new(attenuation), scattered))
+    }
+}
+
+/// Reflectance of a film between a medium of index `ior` above and a
+/// substrate of possibly complex index below, by summing the reflections
+/// inside the film as in Airy's formula.
+fn film_reflectance(cos_theta: f64, lambda: f64, ior: f64, film_ior: f64, thickness: f64, substrate: Complex) -> f64 {
+    let cos1 = Complex::real(clamp(cos_theta, 0.0, 1.0));
+    let sin2_1 = 1.0 - cos_theta * cos_theta;
+    let (n1, n2, n3) = (Complex::real(ior), Complex::real(film_ior), substrate);
+    let cos2 = refracted_cos(ior, n2, sin2_1);
+    let cos3 = refracted_cos(ior, n3, sin2_1);
+
+    // Phase difference between successive reflections inside the film.
+    let delta = Complex::real(4.0 * PI * thickness / lambda) * n2 * cos2;
+    let phase = delta.mul_i().exp();
+
+    let airy = |r12: Complex, r23: Complex| {
+        let r = (r12 + r23 * phase) / (Complex::real(1.0) + r12 * r23 * phase);
+        r.norm_sqr()
+    };
+
+    let rs = airy(fresnel_s(n1, cos1, n2, cos2), fresnel_s(n2, cos2, n3, cos3));
+    let rp = airy(fresnel_p(n1, cos1, n2, cos2), fresnel_p(n2, cos2, n3, cos3));
+    clamp(0.5 * (rs + rp), 0.0, 1.0)
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Complex {
+    re: f64,
+    im: f64,
+}
+
+impl Complex {
+    fn new(re: f64, im: f64) -> Self {
+        Self { re, im }
+    }
+
+    fn real(re: f64) -> Self {
+        Self::new(re, 0.0)
+    }
+
+    fn mul_i(self) -> Self {
+        Self::new(-self.im, self.re)
+    }
+
+    fn exp(self) -> Self {
+        let scale = f64::exp(self.re);
+        Self::new(scale * f64::cos(self.im), scale * f64::sin(self.im))
+    }
+
+    /// Principal square root, with a non-negative real part.
+    fn sqrt(self) -> Self {
+        let norm = f64::sqrt(self.norm_sqr());
+        let re = f64::sqrt(f64::max(0.0, 0.5 * (norm + self.re)));
+        let im = f64::sqrt(f64::max(0.0, 0.5 * (norm - self.re)));
+        Self::new(re, if self.im < 0.0 { -im } else { im })
+    }
+
+    fn norm_sqr(self) -> f64 {
+        self.re * self.re + self.im * self.im
+    }
+}
+
+impl Add for Complex {
+    type Output = Complex;
+    fn add(self, rhs: Self) -> Self::Output {
+        Complex::new(self.re + rhs.re, self.im + rhs.im)
+    }
+}
+
+impl Sub for Complex {
+    type Output = Complex;
+    fn sub(self, rhs: Self) -> Self::Output {
+        Complex::new(self.re - rhs.re, self.im - rhs.im)
+    }
+}
+
+impl Mul for Complex {
+    type Output = Complex;
+    fn mul(self, rhs: Self) -> Self::Output {
+        Complex::new(
+            self.re * rhs.re - self.im * rhs.im,
+            self.re * rhs.im + self.im * rhs.re,
+        )
+    }
+}
+
+impl Div for Complex {
+    type Output = Complex;
+    fn div(self, rhs: Self) -> Self::Output {
+        let denom = rhs.norm_sqr();
+        Complex::new(
+            (self.re * rhs.re + self.im * rhs.im) / denom,
+            (self.im * rhs.re - self.re * rhs.im) / denom,
+        )
+    }
+}
+
+/// Cosine of the refracted angle in a medium of index `n_t`, which is
+/// complex in a metal or past total internal reflection.
+fn refracted_cos(n_i: f64, n_t: Complex, sin2_i: f64) -> Complex {
+    let ratio = Complex::real(n_i) / n_t;
+    (Complex::real(1.0) - ratio * ratio * Complex::real(sin2_i)).sqrt()
+}
+
+fn fresnel_s(n_i: Complex, cos_i: Complex, n_t: Complex, cos_t: Complex) -> Complex {
+    (n_i * cos_i - n_t * cos_t) / (n_i * cos_i + n_t * cos_t)
+}
+
+fn fresnel_p(n_i: Complex, cos_i: Complex, n_t: Complex, cos_t: Complex) -> Complex {
+    (n_t * cos_i - n_i * cos_t) / (n_t * cos_i + n_i * cos_t)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        hittable::HitRecord,
+        material::Material,
+        ray::Ray,
+        spectrum::{SampledWavelengths, N_SPECTRUM_SAMPLES},
+        vec3::{Point, Vec3},
+    };
+
+    use super::{ThinFilm, RGB_WAVELENGTHS};
+
+    fn assert_close(a: f64, b: f64) {
+        assert!(f64::abs(a - b) < 1e-6, "{a} != {b}");
+    }
+
+    #[test]
+    fn vanishing_film_is_bare_interface() {
+        // Air to glass at normal incidence: ((1 - 1.5) / (1 + 1.5))^2.
+        assert_close(ThinFilm::reflectance(1.0, 550.0, 1.0, 1.38, 0.0, 1.5), 0.04);
+    }
+
+    #[test]
+    fn quarter_wave_antireflection() {
+        let n_film = f64::sqrt(1.5);
+        let lambda = 550.0;
+        let thickness = lambda / (4.0 * n_film);
+        assert_close(ThinFilm::reflectance(1.0, lambda, 1.0, n_film, thickness, 1.5), 0.0);
+        // Off the design wavelength the coating no longer cancels fully.
+        assert!(ThinFilm::reflectance(1.0, 450.0, 1.0, n_film, thickness, 1.5) > 1e-3);
+    }
+
+    #[test]
+    fn half_wave_layer_is_absent() {
+        let lambda = 600.0;
+        let thickness = lambda / (2.0 * 2.0);
+        assert_close(ThinFilm::reflectance(1.0, lambda, 1.0, 2.0, thickness, 1.5), 0.04);
+    }
+
+    #[test]
+    fn soap_film_peak_reflectance() {
+        // A quarter-wave soap film in air reflects
+        // ((n1 n3 - n2^2) / (n1 n3 + n2^2))^2 at normal incidence.
+        let n: f64 = 1.33;
+        let lambda = 500.0;
+        let expected = ((1.0 - n * n) / (1.0 + n * n)).powi(2);
+        let thickness = lambda / (4.0 * n);
+        assert_close(ThinFilm::reflectance(1.0, lambda, 1.0, n, thickness, 1.0), expected);
+    }
+
+    #[test]
+    fn grazing_incidence_reflects_fully() {
+        for lambda in [450.0, 550.0, 650.0] {
+            let r = ThinFilm::reflectance(0.0, lambda, 1.0, 1.33, 300.0, 1.0);
+            assert_close(r, 1.0);
+        }
+    }
+
+    #[test]
+    fn bare_conductor_matches_fresnel() {
+        // Aluminium at 550nm, n = 0.958 and k = 6.69, reflects
+        // ((n - 1)^2 + k^2) / ((n + 1)^2 + k^2) = 92.1% at normal incidence.
+        let (eta, k): (f64, f64) = (0.958, 6.69);
+        let expected = ((eta - 1.0).powi(2) + k * k) / ((eta + 1.0).powi(2) + k * k);
+        assert!(f64::abs(expected - 0.921) < 1e-3);
+        assert_close(ThinFilm::conductor_reflectance(1.0, 550.0, 1.0, 1.38, 0.0, eta, k), expected);
+
+        // A half-wave layer leaves the metal as it was.
+        let thickness = 550.0 / (2.0 * 1.38);
+        assert_close(ThinFilm::conductor_reflectance(1.0, 550.0, 1.0, 1.38, thickness, eta, k), expected);
+        // Other thicknesses tint it.
+        let quarter = ThinFilm::conductor_reflectance(1.0, 550.0, 1.0, 1.38, thickness / 2.0, eta, k);
+        assert!(f64::abs(quarter - expected) > 1e-3);
+    }
+
+    fn normal_hit(ray: &Ray) -> HitRecord {
+        HitRecord::new(Point::new(0.0, 0.0, 0.0), 1.0, None, Vec3::new(0.0, 0.0, 1.0), ray, (0.0, 0.0))
+    }
+
+    #[test]
+    fn conductor_absorbs_transmitted_light() {
+        let (thickness, eta, k) = (300.0, 0.958, 6.69);
+        let material = ThinFilm::conductor(thickness, 1.38, eta, k);
+        let ray = Ray::new(Point::new(0.0, 0.0, 1.0), Vec3::new(0.0, 0.0, -1.0));
+        let rec = normal_hit(&ray);
+        let reflectance = |lambda| ThinFilm::conductor_reflectance(1.0, lambda, 1.0, 1.38, thickness, eta, k);
+
+        // Only the film's own reflection leaves the surface.
+        let (attenuation, scattered) = material.scatter(&ray, &rec).unwrap();
+        assert_eq!(scattered.direction(), Vec3::new(0.0, 0.0, 1.0));
+        assert_close(attenuation.x(), reflectance(RGB_WAVELENGTHS[0]));
+        assert_close(attenuation.z(), reflectance(RGB_WAVELENGTHS[2]));
+
+        let lambda = SampledWavelengths::sample_uniform(0.5);
+        let (attenuation, _) = material.scatter_spectral(&ray, &rec, &lambda).unwrap();
+        for i in 0..N_SPECTRUM_SAMPLES {
+            assert_close(attenuation.value(i), reflectance(lambda.lambda(i)));
+        }
+    }
+
+    #[test]
+    fn transparent_substrate_is_not_fresnel_weighted_twice() {
+        // A vanishing film on glass is the bare interface: about 4% of normal
+        // light reflects and the rest passes straight in.
+        let material = ThinFilm::new(0.0, 1.38, 1.5);
+        let ray = Ray::new(Point::new(0.0, 0.0, 1.0), Vec3::new(0.0, 0.0, -1.0));
+        let rec = normal_hit(&ray);
+
+        let n = 20_000;
+        let mut reflected = 0;
+        for _ in 0..n {
+            let (attenuation, scattered) = material.scatter(&ray, &rec).unwrap();
+            if scattered.direction().z() > 0.0 {
+                reflected += 1;
+            } else {
+                assert!((scattered.direction() - Vec3::new(0.0, 0.0, -1.0)).length() < 1e-12);
+                assert!(f64::abs(attenuation.x() - 1.0) < 1e-6);
+            }
+        }
+        let fraction = reflected as f64 / n as f64;
+        assert!(f64::abs(fraction - 0.04) < 0.01, "{fraction}");
+    }
+
+    #[test]
+    fn reflection_weights_every_wavelength() {
+        let thickness = 400.0;
+        let material = ThinFilm::new(thickness, 1.33, 1.0);
+        let ray = Ray::new(Point::new(0.0, 0.0, 1.0), Vec3::new(0.0, 0.0, -1.0));
+        let rec = normal_hit(&ray);
+        let lambda = SampledWavelengths::sample_uniform(0.5);
+        let reflectance: Vec<f64> = (0..N_SPECTRUM_SAMPLES)
+            .map(|i| ThinFilm::reflectance(1.0, lambda.lambda(i), 1.0, 1.33, thickness, 1.0))
+            .collect();
+        let p_reflect = reflectance.iter().sum::<f64>() / N_SPECTRUM_SAMPLES as f64;
+
+        // Each wavelength keeps its own reflectance over the shared
+        // probability of the reflected path, rather than being zeroed.
+        let (attenuation, _) = (0..10_000)
+            .map(|_| material.scatter_spectral(&ray, &rec, &lambda).unwrap())
+            .find(|(_, scattered)| scattered.direction().z() > 0.0)
+            .unwrap();
+        for (i, r) in reflectance.iter().enumerate() {
+            assert_close(attenuation.value(i) * p_reflect, *r);
+        }
+        assert!(reflectance.iter().all(|&r| r > 0.0));
+    }
+}
@@ -0,0 +1,182 @@
+use std::ops::{Index, Mul};
+
+use crate::vec3::{Point, Vec3};
+
+/// Row-major 4x4 matrix for affine transforms of points and vectors.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Mat4 {
+    m: [[f64; 4]; 4],
+}
+
+impl Default for Mat4 {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl Mat4 {
+    pub fn new(m: [[f64; 4]; 4]) -> Self {
+        Mat4 { m }
+    }
+
+    pub fn identity() -> Self {
+        Mat4::new([
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    pub fn transpose(&self) -> Self {
+        let mut m = [[0.0; 4]; 4];
+        for (i, row) in m.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = self.m[j][i];
+            }
+        }
+        Mat4 { m }
+    }
+
+    /// Inverse by Gauss-Jordan elimination with partial pivoting, or `None`
+    /// if the matrix is singular.
+    pub fn inverse(&self) -> Option<Self> {
+        let mut a = self.m;
+        let mut inv = Mat4::identity().m;
+
+        for col in 0..4 {
+            let pivot = (col..4)
+                .max_by(|&i, &j| f64::abs(a[i][col]).total_cmp(&f64::abs(a[j][col])))
+                .unwrap();
+            if f64::abs(a[pivot][col]) < 1e-12 {
+                return None;
+            }
+            a.swap(col, pivot);
+            inv.swap(col, pivot);
+
+            let scale = 1.0 / a[col][col];
+            for j in 0..4 {
+                a[col][j] *= scale;
+                inv[col][j] *= scale;
+            }
+
+            for row in 0..4 {
+                if row == col {
+                    continue;
+                }
+                let factor = a[row][col];
+                if factor == 0.0 {
+                    continue;
+                }
+                for j in 0..4 {
+                    a[row][j] -= factor * a[col][j];
+                    inv[row][j] -= factor * inv[col][j];
+                }
+            }
+        }
+        Some(Mat4 { m: inv })
+    }
+
+    /// Transforms a point, applying translation and the projective divide.
+    pub fn transform_point(&self, p: Point) -> Point {
+        let m = &self.m;
+        let x = m[0][0] * p.x() + m[0][1] * p.y() + m[0][2] * p.z() + m[0][3];
+        let y = m[1][0] * p.x() + m[1][1] * p.y() + m[1][2] * p.z() + m[1][3];
+        let z = m[2][0] * p.x() + m[2][1] * p.y() + m[2][2] * p.z() + m[2][3];
+        let w = m[3][0] * p.x() + m[3][1] * p.y() + m[3][2] * p.z() + m[3][3];
+        if w == 1.0 {
+            Point::new(x, y, z)
+        } else {
+            Point::new(x, y, z) / w
+        }
+    }
+
+    /// Transforms a direction, ignoring translation.
+    pub fn transform_vector(&self, v: Vec3) -> Vec3 {
+        let m = &self.m;
+        Vec3::new(
+            m[0][0] * v.x() + m[0][1] * v.y() + m[0][2] * v.z(),
+            m[1][0] * v.x() + m[1][1] * v.y() + m[1][2] * v.z(),
+            m[2][0] * v.x() + m[2][1] * v.y() + m[2][2] * v.z(),
+        )
+    }
+}
+
+impl Index<(usize, usize)> for Mat4 {
+    type Output = f64;
+
+    fn index(&self, (row, col): (usize, usize)) -> &Self::Output {
+        &self.m[row][col]
+    }
+}
+
+impl Mul for Mat4 {
+    type Output = Mat4;
+    fn mul(self, rhs: Self) -> Self::Output {
+        let mut m = [[0.0; 4]; 4];
+        for (i, row) in m.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = (0..4).map(|k| self.m[i][k] * rhs.m[k][j]).sum();
+            }
+        }
+        Mat4 { m }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::vec3::{Point, Vec3};
+
+    use super::Mat4;
+
+    fn assert_mat_close(a: Mat4, b: Mat4) {
+        for i in 0..4 {
+            for j in 0..4 {
+                assert!(f64::abs(a[(i, j)] - b[(i, j)]) < 1e-9, "{a:?} != {b:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn multiply_and_transpose() {
+        let a = Mat4::new([
+            [1.0, 2.0, 3.0, 4.0],
+            [5.0, 6.0, 7.0, 8.0],
+            [9.0, 10.0, 11.0, 12.0],
+            [13.0, 14.0, 15.0, 16.0],
+        ]);
+        assert_eq!(a * Mat4::identity(), a);
+        assert_eq!(Mat4::identity() * a, a);
+        assert_eq!(a.transpose()[(0, 3)], 13.0);
+        assert_eq!(a.transpose().transpose(), a);
+        assert_eq!((a * a)[(0, 0)], 1.0 + 10.0 + 27.0 + 52.0);
+    }
+
+    #[test]
+    fn inverse() {
+        let a = Mat4::new([
+            [2.0, 0.0, 0.0, 1.0],
+            [0.0, 0.0, -3.0, 2.0],
+            [0.0, 4.0, 0.0, 3.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ]);
+        let inv = a.inverse().unwrap();
+        assert_mat_close(a * inv, Mat4::identity());
+        assert_mat_close(inv * a, Mat4::identity());
+
+        let singular = Mat4::new([[1.0, 2.0, 0.0, 0.0], [2.0, 4.0, 0.0, 0.0], [0.0; 4], [0.0, 0.0, 0.0, 1.0]]);
+        assert!(singular.inverse().is_none());
+    }
+
+    #[test]
+    fn transform_points_and_vectors() {
+        let translate = Mat4::new([
+            [1.0, 0.0, 0.0, 1.0],
+            [0.0, 1.0, 0.0, 2.0],
+            [0.0, 0.0, 1.0, 3.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ]);
+        assert_eq!(translate.transform_point(Point::new(1.0, 1.0, 1.0)), Point::new(2.0, 3.0, 4.0));
+        assert_eq!(translate.transform_vector(Vec3::new(1.0, 1.0, 1.0)), Vec3::new(1.0, 1.0, 1.0));
+    }
+}
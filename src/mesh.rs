@@ -0,0 +1,44 @@
+//! Triangle mesh data, and the surfaces that tessellate into it. Render a
+//! mesh with `hittable::triangle::TriangleMesh`.
+
+pub mod bezier;
+pub mod subdivision;
+
+use crate::vec3::{cross, unit_vector, Point, Vec3};
+
+/// Indexed triangle mesh. `normals` and `uvs` are either empty or hold one
+/// entry per position.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Mesh {
+    pub positions: Vec<Point>,
+    pub normals: Vec<Vec3>,
+    pub uvs: Vec<(f64, f64)>,
+    pub indices: Vec<[usize; 3]>,
+}
+
+impl Mesh {
+    pub fn new(positions: Vec<Point>, indices: Vec<[usize; 3]>) -> Self {
+        Self {
+            positions,
+            indices,
+            ..Default::default()
+        }
+    }
+
+    /// Sets the vertex normals to the area-weighted average of the normals
+    /// of the triangles around each vertex.
+    pub fn compute_normals(&mut self) {
+        let mut normals = vec![Vec3::default(); self.positions.len()];
+        for &[a, b, c] in &self.indices {
+            let (pa, pb, pc) = (self.positions[a], self.positions[b], self.positions[c]);
+            let n = cross(pb - pa, pc - pa);
+            for i in [a, b, c] {
+                normals[i] += n;
+            }
+        }
+        self.normals = normals
+            .into_iter()
+            .map(|n| if n.near_zero() { n } else { unit_vector(n) })
+            .collect();
+    }
+}
@@ -0,0 +1,186 @@
+use std::io::{self, BufRead};
+
+use crate::{
+    mesh::Mesh,
+    vec3::{cross, unit_vector, Point, Vec3},
+};
+
+/// Bicubic Bezier patch with its 4x4 control points stored row by row, rows
+/// running along `v` and columns along `u`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BezierPatch {
+    control_points: [Point; 16],
+}
+
+/// Cubic Bernstein basis at `t` and its derivative.
+fn bernstein(t: f64) -> ([f64; 4], [f64; 4]) {
+    let s = 1.0 - t;
+    (
+        [s * s * s, 3.0 * t * s * s, 3.0 * t * t * s, t * t * t],
+        [-3.0 * s * s, 3.0 * s * (s - 2.0 * t), 3.0 * t * (2.0 * s - t), 3.0 * t * t],
+    )
+}
+
+impl BezierPatch {
+    pub fn new(control_points: [Point; 16]) -> Self {
+        Self { control_points }
+    }
+
+    /// Reads patches in the `.bpt` format of the Utah teapot: the number of
+    /// patches, then for each patch its degrees in `u` and `v` followed by
+    /// its control points. Only bicubic patches are supported.
+    pub fn read_bpt(mut reader: impl BufRead) -> io::Result<Vec<Self>> {
+        let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+
+        let mut text = String::new();
+        reader.read_to_string(&mut text)?;
+        let mut tokens = text.split_whitespace();
+        let mut next = || tokens.next().ok_or_else(|| invalid("unexpected end of patch file"));
+
+        let count: usize = next()?.parse().map_err(|_| invalid("invalid patch count"))?;
+        let mut patches = Vec::with_capacity(count);
+        for _ in 0..count {
+            let degrees = (next()?, next()?);
+            if degrees != ("3", "3") {
+                return Err(invalid("only bicubic patches are supported"));
+            }
+            let mut control_points = [Point::default(); 16];
+            for point in &mut control_points {
+                let mut coordinate = || -> io::Result<f64> { next()?.parse().map_err(|_| invalid("invalid control point")) };
+                *point = Point::new(coordinate()?, coordinate()?, coordinate()?);
+            }
+            patches.push(Self::new(control_points));
+        }
+        Ok(patches)
+    }
+
+    pub fn control_points(&self) -> &[Point; 16] {
+        &self.control_points
+    }
+
+    /// Point on the patch and the unnormalized partial derivatives along `u`
+    /// and `v`.
+    fn evaluate(&self, u: f64, v: f64) -> (Point, Vec3, Vec3) {
+        let (bu, du) = bernstein(u);
+        let (bv, dv) = bernstein(v);
+        let mut p = Point::default();
+        let mut dp_du = Vec3::default();
+        let mut dp_dv = Vec3::default();
+        for row in 0..4 {
+            for col in 0..4 {
+                let cp = self.control_points[row * 4 + col];
+                p += bu[col] * bv[row] * cp;
+                dp_du += du[col] * bv[row] * cp;
+                dp_dv += bu[col] * dv[row] * cp;
+            }
+        }
+        (p, dp_du, dp_dv)
+    }
+
+    pub fn point(&self, u: f64, v: f64) -> Point {
+        self.evaluate(u, v).0
+    }
+
+    /// Surface normal at `(u, v)`. Where the patch is degenerate, such as at
+    /// the pole of the teapot lid, the normal is taken from just inside the
+    /// patch instead.
+    pub fn normal(&self, u: f64, v: f64) -> Vec3 {
+        let (_, dp_du, dp_dv) = self.evaluate(u, v);
+        let n = cross(dp_du, dp_dv);
+        if n.length_squared() > 1e-20 {
+            return unit_vector(n);
+        }
+        let nudge = |t: f64| if t < 0.5 { t + 1e-4 } else { t - 1e-4 };
+        let (_, dp_du, dp_dv) = self.evaluate(nudge(u), nudge(v));
+        unit_vector(cross(dp_du, dp_dv))
+    }
+
+    /// Tessellates the patch into a `divisions` by `divisions` grid of quads,
+    /// each split into two triangles, with exact normals at the vertices.
+    pub fn tessellate(&self, divisions: usize) -> Mesh {
+        tessellate(std::slice::from_ref(self), divisions)
+    }
+}
+
+/// Tessellates every patch into one mesh. See `BezierPatch::tessellate`.
+pub fn tessellate(patches: &[BezierPatch], divisions: usize) -> Mesh {
+    assert!(divisions > 0, "tessellation needs at least one division");
+    let n = divisions + 1;
+    let mut mesh = Mesh::default();
+    for patch in patches {
+        let base = mesh.positions.len();
+        for j in 0..n {
+            for i in 0..n {
+                let (u, v) = (i as f64 / divisions as f64, j as f64 / divisions as f64);
+                mesh.positions.push(patch.point(u, v));
+                mesh.normals.push(patch.normal(u, v));
+                mesh.uvs.push((u, v));
+            }
+        }
+        for j in 0..divisions {
+            for i in 0..divisions {
+                let corner = base + j * n + i;
+                mesh.indices.push([corner, corner + 1, corner + n + 1]);
+                mesh.indices.push([corner, corner + n + 1, corner + n]);
+            }
+        }
+    }
+    mesh
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::vec3::{Point, Vec3};
+
+    use super::BezierPatch;
+
+    fn saddle() -> BezierPatch {
+        let mut control_points = [Point::default(); 16];
+        for row in 0..4 {
+            for col in 0..4 {
+                let (x, y) = (col as f64, row as f64);
+                control_points[row * 4 + col] = Point::new(x, y, (x - 1.5) * (y - 1.5));
+            }
+        }
+        BezierPatch::new(control_points)
+    }
+
+    #[test]
+    fn evaluates_corners_and_normals() {
+        let patch = saddle();
+        assert_eq!(patch.point(0.0, 0.0), Point::new(0.0, 0.0, 2.25));
+        assert_eq!(patch.point(1.0, 1.0), Point::new(3.0, 3.0, 2.25));
+        // The centre of the saddle is flat.
+        assert!((patch.point(0.5, 0.5) - Point::new(1.5, 1.5, 0.0)).length() < 1e-12);
+        assert!((patch.normal(0.5, 0.5) - Vec3::new(0.0, 0.0, 1.0)).length() < 1e-12);
+
+        let mesh = patch.tessellate(4);
+        assert_eq!(mesh.positions.len(), 25);
+        assert_eq!(mesh.indices.len(), 32);
+    }
+
+    #[test]
+    fn degenerate_edge_has_a_normal() {
+        // All points of the first row collapse to a pole, like the teapot lid.
+        let mut control_points = *saddle().control_points();
+        for point in &mut control_points[..4] {
+            *point = Point::new(1.5, 0.0, 2.0);
+        }
+        let normal = BezierPatch::new(control_points).normal(0.3, 0.0);
+        assert!(normal.x().is_finite() && f64::abs(normal.length() - 1.0) < 1e-9);
+    }
+
+    #[test]
+    fn reads_bpt() {
+        let mut text = String::from("1\n3 3\n");
+        for i in 0..16 {
+            text.push_str(&format!("{} {} 0.5\n", i % 4, i / 4));
+        }
+        let patches = BezierPatch::read_bpt(text.as_bytes()).unwrap();
+        assert_eq!(patches.len(), 1);
+        assert_eq!(patches[0].control_points()[5], Point::new(1.0, 1.0, 0.5));
+
+        assert!(BezierPatch::read_bpt("1\n2 3\n".as_bytes()).is_err());
+        assert!(BezierPatch::read_bpt("2\n3 3\n0 0 0\n".as_bytes()).is_err());
+    }
+}
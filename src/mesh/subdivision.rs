@@ -0,0 +1,203 @@
+use std::collections::HashMap;
+
+use crate::{
+    mesh::Mesh,
+    vec3::{Point, Vec3},
+};
+
+/// Mesh of polygonal faces, usually quads, given as indices into
+/// `positions` in counter-clockwise order.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PolygonMesh {
+    positions: Vec<Point>,
+    faces: Vec<Vec<usize>>,
+}
+
+fn average(points: impl Iterator<Item = Point>) -> Point {
+    let (sum, count) = points.fold((Vec3::default(), 0), |(sum, count), p| (sum + p, count + 1));
+    sum / count as f64
+}
+
+impl PolygonMesh {
+    /// Panics if a face has fewer than three vertices or refers to a
+    /// position that does not exist.
+    pub fn new(positions: Vec<Point>, faces: Vec<Vec<usize>>) -> Self {
+        assert!(faces.iter().all(|face| face.len() >= 3), "faces need at least three vertices");
+        assert!(
+            faces.iter().flatten().all(|&i| i < positions.len()),
+            "face vertex index out of range"
+        );
+        Self { positions, faces }
+    }
+
+    pub fn positions(&self) -> &[Point] {
+        &self.positions
+    }
+
+    pub fn faces(&self) -> &[Vec<usize>] {
+        &self.faces
+    }
+
+    /// Applies `levels` steps of Catmull-Clark subdivision. Every face
+    /// becomes quads after the first step. Open boundaries are kept as cubic
+    /// B-spline curves, with the corners of boundary faces held in place.
+    pub fn catmull_clark(&self, levels: usize) -> Self {
+        (0..levels).fold(self.clone(), |mesh, _| mesh.subdivide())
+    }
+
+    fn subdivide(&self) -> Self {
+        let face_points: Vec<Point> = self
+            .faces
+            .iter()
+            .map(|face| average(face.iter().map(|&i| self.positions[i])))
+            .collect();
+
+        // Each edge, keyed by its sorted end points, with its adjacent faces.
+        let mut edge_index = HashMap::new();
+        let mut edges: Vec<((usize, usize), Vec<usize>)> = Vec::new();
+        for (f, face) in self.faces.iter().enumerate() {
+            for k in 0..face.len() {
+                let (a, b) = (face[k], face[(k + 1) % face.len()]);
+                let key = (a.min(b), a.max(b));
+                let e = *edge_index.entry(key).or_insert_with(|| {
+                    edges.push((key, Vec::new()));
+                    edges.len() - 1
+                });
+                edges[e].1.push(f);
+            }
+        }
+        let is_boundary = |faces: &Vec<usize>| faces.len() != 2;
+
+        let edge_points: Vec<Point> = edges
+            .iter()
+            .map(|&((a, b), ref faces)| {
+                let (pa, pb) = (self.positions[a], self.positions[b]);
+                if is_boundary(faces) {
+                    0.5 * (pa + pb)
+                } else {
+                    0.25 * (pa + pb + face_points[faces[0]] + face_points[faces[1]])
+                }
+            })
+            .collect();
+
+        let mut vertex_faces = vec![Vec::new(); self.positions.len()];
+        for (f, face) in self.faces.iter().enumerate() {
+            for &i in face {
+                vertex_faces[i].push(f);
+            }
+        }
+        let mut vertex_edges = vec![Vec::new(); self.positions.len()];
+        for (e, &((a, b), _)) in edges.iter().enumerate() {
+            vertex_edges[a].push(e);
+            vertex_edges[b].push(e);
+        }
+
+        let vertex_points = (0..self.positions.len()).map(|v| {
+            let p = self.positions[v];
+            let other = |e: usize| {
+                let (a, b) = edges[e].0;
+                self.positions[if a == v { b } else { a }]
+            };
+            let boundary: Vec<usize> = vertex_edges[v].iter().copied().filter(|&e| is_boundary(&edges[e].1)).collect();
+            match boundary.len() {
+                0 if !vertex_faces[v].is_empty() => {
+                    let n = vertex_edges[v].len() as f64;
+                    let f = average(vertex_faces[v].iter().map(|&f| face_points[f]));
+                    let r = average(vertex_edges[v].iter().map(|&e| 0.5 * (p + other(e))));
+                    (f + 2.0 * r + (n - 3.0) * p) / n
+                }
+                2 if vertex_faces[v].len() > 1 => (6.0 * p + other(boundary[0]) + other(boundary[1])) / 8.0,
+                // Corners of a single face and non-manifold vertices stay put.
+                _ => p,
+            }
+        });
+        let vertex_points: Vec<Point> = vertex_points.collect();
+
+        let edge_base = self.positions.len();
+        let face_base = edge_base + edges.len();
+        let positions = [vertex_points, edge_points, face_points].concat();
+
+        let edge_of = |a: usize, b: usize| edge_base + edge_index[&(a.min(b), a.max(b))];
+        let faces = self
+            .faces
+            .iter()
+            .enumerate()
+            .flat_map(|(f, face)| {
+                let n = face.len();
+                (0..n).map(move |k| {
+                    let (prev, v, next) = (face[(k + n - 1) % n], face[k], face[(k + 1) % n]);
+                    vec![v, edge_of(v, next), face_base + f, edge_of(prev, v)]
+                })
+            })
+            .collect();
+
+        Self { positions, faces }
+    }
+
+    /// Triangulates each face as a fan and gives it smooth vertex normals.
+    pub fn to_mesh(&self) -> Mesh {
+        let indices = self
+            .faces
+            .iter()
+            .flat_map(|face| (1..face.len() - 1).map(move |k| [face[0], face[k], face[k + 1]]))
+            .collect();
+        let mut mesh = Mesh::new(self.positions.clone(), indices);
+        mesh.compute_normals();
+        mesh
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::vec3::{dot, Point};
+
+    use super::PolygonMesh;
+
+    fn cube() -> PolygonMesh {
+        let positions = (0..8)
+            .map(|i| {
+                let c = |bit: usize| if i & bit == 0 { -1.0 } else { 1.0 };
+                Point::new(c(1), c(2), c(4))
+            })
+            .collect();
+        let faces = vec![
+            vec![0, 2, 3, 1],
+            vec![4, 5, 7, 6],
+            vec![0, 1, 5, 4],
+            vec![2, 6, 7, 3],
+            vec![0, 4, 6, 2],
+            vec![1, 3, 7, 5],
+        ];
+        PolygonMesh::new(positions, faces)
+    }
+
+    #[test]
+    fn subdivided_cube() {
+        let once = cube().catmull_clark(1);
+        assert_eq!(once.positions.len(), 8 + 12 + 6);
+        assert_eq!(once.faces.len(), 24);
+        let corner = 5.0 / 9.0;
+        assert!((once.positions[7] - Point::new(corner, corner, corner)).length() < 1e-12);
+        // Edge points average the edge ends with the two face points.
+        assert!(once.positions[8..20].iter().all(|p| f64::abs(p.length() - f64::sqrt(2.0 * 0.75 * 0.75)) < 1e-12));
+
+        // Further levels shrink towards a smooth, nearly spherical limit.
+        let mesh = cube().catmull_clark(4).to_mesh();
+        assert_eq!(mesh.indices.len(), 6 * 4usize.pow(4) * 2);
+        let radii: Vec<f64> = mesh.positions.iter().map(|p| p.length()).collect();
+        let (lo, hi) = radii.iter().fold((f64::INFINITY, 0.0f64), |(lo, hi), &r| (lo.min(r), hi.max(r)));
+        assert!(hi / lo < 1.1);
+        assert!(mesh.normals.iter().zip(&mesh.positions).all(|(n, p)| dot(*n, *p) > 0.0));
+    }
+
+    #[test]
+    fn flat_open_grid_stays_flat() {
+        let positions = (0..9).map(|i| Point::new((i % 3) as f64, (i / 3) as f64, 0.0)).collect();
+        let faces = vec![vec![0, 1, 4, 3], vec![1, 2, 5, 4], vec![3, 4, 7, 6], vec![4, 5, 8, 7]];
+        let mesh = PolygonMesh::new(positions, faces).catmull_clark(2);
+        assert!(mesh.positions.iter().all(|p| p.z() == 0.0));
+        // Corners are kept and boundary vertices stay on the boundary.
+        assert!(mesh.positions.contains(&Point::new(2.0, 2.0, 0.0)));
+        assert!(mesh.positions.iter().all(|p| (0.0..=2.0).contains(&p.x()) && (0.0..=2.0).contains(&p.y())));
+    }
+}
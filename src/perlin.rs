@@ -0,0 +1,117 @@
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+
+use crate::vec3::{dot, unit_vector, Point, Vec3};
+
+const POINT_COUNT: usize = 256;
+
+/// Gradient noise after Perlin, seeded so that the same seed always gives the
+/// same field.
+pub struct Perlin {
+    gradients: Vec<Vec3>,
+    perm_x: Vec<usize>,
+    perm_y: Vec<usize>,
+    perm_z: Vec<usize>,
+}
+
+impl Perlin {
+    pub fn new(seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let gradients = (0..POINT_COUNT)
+            .map(|_| {
+                let v = Vec3::new(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0));
+                unit_vector(v)
+            })
+            .collect();
+        let mut permutation = || {
+            let mut p: Vec<usize> = (0..POINT_COUNT).collect();
+            p.shuffle(&mut rng);
+            p
+        };
+        let (perm_x, perm_y, perm_z) = (permutation(), permutation(), permutation());
+        Self {
+            gradients,
+            perm_x,
+            perm_y,
+            perm_z,
+        }
+    }
+
+    /// Noise value at `p`, roughly within `[-1, 1]`.
+    pub fn noise(&self, p: Point) -> f64 {
+        let (fx, fy, fz) = (p.x().floor(), p.y().floor(), p.z().floor());
+        let (u, v, w) = (p.x() - fx, p.y() - fy, p.z() - fz);
+        let (i, j, k) = (fx as i64, fy as i64, fz as i64);
+
+        // Hermite smoothing hides the lattice.
+        let (uu, vv, ww) = (u * u * (3.0 - 2.0 * u), v * v * (3.0 - 2.0 * v), w * w * (3.0 - 2.0 * w));
+        let mask = POINT_COUNT as i64 - 1;
+        let mut accum = 0.0;
+        for di in 0..2 {
+            for dj in 0..2 {
+                for dk in 0..2 {
+                    let index = self.perm_x[((i + di) & mask) as usize]
+                        ^ self.perm_y[((j + dj) & mask) as usize]
+                        ^ self.perm_z[((k + dk) & mask) as usize];
+                    let (a, b, c) = (di as f64, dj as f64, dk as f64);
+                    let weight = Vec3::new(u - a, v - b, w - c);
+                    accum += (a * uu + (1.0 - a) * (1.0 - uu))
+                        * (b * vv + (1.0 - b) * (1.0 - vv))
+                        * (c * ww + (1.0 - c) * (1.0 - ww))
+                        * dot(self.gradients[index], weight);
+                }
+            }
+        }
+        accum
+    }
+
+    /// Fractal sum of `octaves` layers of noise, each at double the
+    /// frequency and half the amplitude of the last.
+    pub fn fbm(&self, p: Point, octaves: usize) -> f64 {
+        let mut accum = 0.0;
+        let mut weight = 1.0;
+        let mut p = p;
+        for _ in 0..octaves {
+            accum += weight * self.noise(p);
+            weight *= 0.5;
+            p = 2.0 * p;
+        }
+        accum
+    }
+
+    /// Sum of the absolute value of `octaves` layers of noise, giving the
+    /// turbulent look of marble and ridged terrain.
+    pub fn turbulence(&self, p: Point, octaves: usize) -> f64 {
+        let mut accum = 0.0;
+        let mut weight = 1.0;
+        let mut p = p;
+        for _ in 0..octaves {
+            accum += weight * f64::abs(self.noise(p));
+            weight *= 0.5;
+            p = 2.0 * p;
+        }
+        accum
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::vec3::Point;
+
+    use super::Perlin;
+
+    #[test]
+    fn deterministic_smooth_and_bounded() {
+        let a = Perlin::new(7);
+        let b = Perlin::new(7);
+        for i in 0..200 {
+            let p = Point::new(0.37 * i as f64, 0.11 * i as f64, -0.23 * i as f64);
+            let n = a.noise(p);
+            assert_eq!(n, b.noise(p));
+            assert!((-1.0..=1.0).contains(&n));
+            // Zero at lattice points, continuous in between.
+            assert_eq!(a.noise(Point::new(i as f64, 2.0, -3.0)), 0.0);
+            let step = Point::new(1e-6, 0.0, 0.0);
+            assert!(f64::abs(a.noise(p + step) - n) < 1e-4);
+        }
+    }
+}
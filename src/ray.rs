@@ -1,19 +1,38 @@
-use crate::vec3::{Point, Vec3};
+use crate::{
+    spectrum::SampledWavelengths,
+    vec3::{Point, Vec3},
+};
 
 #[derive(Debug, Default, Clone, Copy)]
 pub struct Ray {
     origin: Point,
-    direction: Vec3
+    direction: Vec3,
+    wavelengths: Option<SampledWavelengths>,
+    time: f64,
 }
 
 impl Ray {
     pub fn new(origin: Point, direction: Vec3) -> Self {
         Ray {
-            origin: origin,
-            direction: direction
+            origin,
+            direction,
+            wavelengths: None,
+            time: 0.0,
         }
     }
 
+    /// Sets the instant the ray is fired at, for motion blur.
+    pub fn with_time(mut self, time: f64) -> Self {
+        self.time = time;
+        self
+    }
+
+    /// Tags the ray with the wavelengths it carries in spectral rendering.
+    pub fn with_wavelengths(mut self, wavelengths: SampledWavelengths) -> Self {
+        self.wavelengths = Some(wavelengths);
+        self
+    }
+
     pub fn origin(&self) -> Point {
         self.origin
     }
@@ -22,6 +41,14 @@ impl Ray {
         self.direction
     }
 
+    pub fn wavelengths(&self) -> Option<SampledWavelengths> {
+        self.wavelengths
+    }
+
+    pub fn time(&self) -> f64 {
+        self.time
+    }
+
     pub fn at(&self, t: f64) -> Point {
         return self.origin + t * self.direction
     }
@@ -0,0 +1,208 @@
+//! Real roots of low degree polynomials, as needed to intersect rays with
+//! quadric and quartic surfaces. Coefficients are given from the highest
+//! degree down and roots are returned in ascending order.
+
+use std::f64::consts::PI;
+
+const EPSILON: f64 = 1e-12;
+
+/// Roots of `a x^2 + b x + c`, falling back to the linear case when `a` is
+/// zero.
+pub fn solve_quadratic(a: f64, b: f64, c: f64) -> Vec<f64> {
+    if f64::abs(a) < EPSILON {
+        if f64::abs(b) < EPSILON {
+            return vec![];
+        }
+        return vec![-c / b];
+    }
+
+    let discriminant = b * b - 4.0 * a * c;
+    if discriminant < 0.0 {
+        return vec![];
+    }
+    // Avoids cancellation between -b and the square root.
+    let q = -0.5 * (b + f64::copysign(f64::sqrt(discriminant), b));
+    let (r0, r1) = if q == 0.0 {
+        (0.0, 0.0)
+    } else {
+        (q / a, c / q)
+    };
+    if r0 < r1 {
+        vec![r0, r1]
+    } else {
+        vec![r1, r0]
+    }
+}
+
+/// Roots of `a x^3 + b x^2 + c x + d`.
+pub fn solve_cubic(a: f64, b: f64, c: f64, d: f64) -> Vec<f64> {
+    if f64::abs(a) < EPSILON {
+        return solve_quadratic(b, c, d);
+    }
+    let (b, c, d) = (b / a, c / a, d / a);
+
+    // Depressed cubic t^3 + p t + q with x = t - b / 3.
+    let p = c - b * b / 3.0;
+    let q = 2.0 * b * b * b / 27.0 - b * c / 3.0 + d;
+    let shift = -b / 3.0;
+
+    let discriminant = (q / 2.0).powi(2) + (p / 3.0).powi(3);
+    let mut roots = if f64::abs(p) < EPSILON && f64::abs(q) < EPSILON {
+        vec![shift]
+    } else if discriminant > 0.0 {
+        let sqrt_disc = f64::sqrt(discriminant);
+        let u = f64::cbrt(-q / 2.0 + sqrt_disc);
+        let v = f64::cbrt(-q / 2.0 - sqrt_disc);
+        vec![u + v + shift]
+    } else {
+        let r = 2.0 * f64::sqrt(-p / 3.0);
+        let phi = f64::acos((3.0 * q / (p * r)).clamp(-1.0, 1.0)) / 3.0;
+        (0..3)
+            .map(|k| r * f64::cos(phi - 2.0 * PI * k as f64 / 3.0) + shift)
+            .collect()
+    };
+
+    for root in roots.iter_mut() {
+        *root = polish(&[1.0, b, c, d], *root);
+    }
+    sort_and_dedup(roots)
+}
+
+/// Roots of `a x^4 + b x^3 + c x^2 + d x + e`, by Ferrari's method followed
+/// by Newton polishing of each root.
+pub fn solve_quartic(a: f64, b: f64, c: f64, d: f64, e: f64) -> Vec<f64> {
+    if f64::abs(a) < EPSILON {
+        return solve_cubic(b, c, d, e);
+    }
+    let (b, c, d, e) = (b / a, c / a, d / a, e / a);
+
+    // Depressed quartic y^4 + p y^2 + q y + r with x = y - b / 4.
+    let b2 = b * b;
+    let p = c - 3.0 * b2 / 8.0;
+    let q = d - b * c / 2.0 + b2 * b / 8.0;
+    let r = e - b * d / 4.0 + b2 * c / 16.0 - 3.0 * b2 * b2 / 256.0;
+    let shift = -b / 4.0;
+
+    let depressed_roots = if f64::abs(q) < EPSILON {
+        // Biquadratic in y^2.
+        solve_quadratic(1.0, p, r)
+            .into_iter()
+            .filter(|&z| z >= 0.0)
+            .flat_map(|z| {
+                let y = f64::sqrt(z);
+                [-y, y]
+            })
+            .collect::<Vec<f64>>()
+    } else {
+        // Split into two quadratics using a positive root m of the resolvent
+        // cubic m^3 + p m^2 + (p^2 / 4 - r) m - q^2 / 8.
+        let m = solve_cubic(1.0, p, p * p / 4.0 - r, -q * q / 8.0)
+            .into_iter()
+            .fold(f64::NEG_INFINITY, f64::max);
+        if m <= 0.0 {
+            return vec![];
+        }
+        let s = f64::sqrt(2.0 * m);
+        let mut roots = solve_quadratic(1.0, s, p / 2.0 + m - q / (2.0 * s));
+        roots.extend(solve_quadratic(1.0, -s, p / 2.0 + m + q / (2.0 * s)));
+        roots
+    };
+
+    let roots = depressed_roots
+        .into_iter()
+        .map(|y| polish(&[1.0, b, c, d, e], y + shift))
+        .collect();
+    sort_and_dedup(roots)
+}
+
+fn evaluate(coefficients: &[f64], x: f64) -> (f64, f64) {
+    coefficients
+        .iter()
+        .fold((0.0, 0.0), |(value, derivative), &c| (value * x + c, derivative * x + value))
+}
+
+fn polish(coefficients: &[f64], mut x: f64) -> f64 {
+    for _ in 0..4 {
+        let (value, derivative) = evaluate(coefficients, x);
+        if derivative == 0.0 {
+            break;
+        }
+        let next = x - value / derivative;
+        if !next.is_finite() {
+            break;
+        }
+        x = next;
+    }
+    x
+}
+
+fn sort_and_dedup(mut roots: Vec<f64>) -> Vec<f64> {
+    roots.sort_by(f64::total_cmp);
+    roots.dedup_by(|a, b| f64::abs(*a - *b) < 1e-9);
+    roots
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_roots(actual: Vec<f64>, expected: &[f64]) {
+        assert_eq!(actual.len(), expected.len(), "{actual:?} != {expected:?}");
+        for (a, e) in actual.iter().zip(expected) {
+            assert!(f64::abs(a - e) < 1e-7, "{actual:?} != {expected:?}");
+        }
+    }
+
+    #[test]
+    fn quadratic() {
+        assert_roots(solve_quadratic(1.0, -3.0, 2.0), &[1.0, 2.0]);
+        assert_roots(solve_quadratic(2.0, 0.0, 2.0), &[]);
+        assert_roots(solve_quadratic(0.0, 2.0, -4.0), &[2.0]);
+        // Large and small roots without cancellation.
+        assert_roots(solve_quadratic(1.0, -1e8, 1.0), &[1e-8, 1e8]);
+    }
+
+    #[test]
+    fn cubic() {
+        // (x - 1)(x - 2)(x + 3)
+        assert_roots(solve_cubic(1.0, 0.0, -7.0, 6.0), &[-3.0, 1.0, 2.0]);
+        // (x - 2)(x^2 + 1)
+        assert_roots(solve_cubic(1.0, -2.0, 1.0, -2.0), &[2.0]);
+        assert_roots(solve_cubic(2.0, -6.0, 6.0, -2.0), &[1.0]);
+    }
+
+    #[test]
+    fn quartic() {
+        // (x - 1)(x - 2)(x - 3)(x - 4)
+        assert_roots(solve_quartic(1.0, -10.0, 35.0, -50.0, 24.0), &[1.0, 2.0, 3.0, 4.0]);
+        // (x^2 - 4)(x^2 - 9), biquadratic
+        assert_roots(solve_quartic(1.0, 0.0, -13.0, 0.0, 36.0), &[-3.0, -2.0, 2.0, 3.0]);
+        // (x - 0.5)(x + 2)(x^2 + 1)
+        assert_roots(solve_quartic(1.0, 1.5, 0.0, 1.5, -1.0), &[-2.0, 0.5]);
+        // x^4 + 1 has no real roots.
+        assert_roots(solve_quartic(1.0, 0.0, 0.0, 0.0, 1.0), &[]);
+        // Scaled leading coefficient.
+        assert_roots(solve_quartic(3.0, -30.0, 105.0, -150.0, 72.0), &[1.0, 2.0, 3.0, 4.0]);
+    }
+
+    #[test]
+    fn random_quartics() {
+        for _ in 0..1000 {
+            let mut expected: Vec<f64> = (0..4).map(|_| crate::utils::random_double(-5.0, 5.0)).collect();
+            expected.sort_by(f64::total_cmp);
+            if expected.windows(2).any(|w| w[1] - w[0] < 1e-3) {
+                continue;
+            }
+            let [r0, r1, r2, r3] = [expected[0], expected[1], expected[2], expected[3]];
+            let b = -(r0 + r1 + r2 + r3);
+            let c = r0 * r1 + r0 * r2 + r0 * r3 + r1 * r2 + r1 * r3 + r2 * r3;
+            let d = -(r0 * r1 * r2 + r0 * r1 * r3 + r0 * r2 * r3 + r1 * r2 * r3);
+            let e = r0 * r1 * r2 * r3;
+            let roots = solve_quartic(1.0, b, c, d, e);
+            assert_eq!(roots.len(), 4, "{roots:?} != {expected:?}");
+            for (a, e) in roots.iter().zip(&expected) {
+                assert!(f64::abs(a - e) < 1e-5, "{roots:?} != {expected:?}");
+            }
+        }
+    }
+}
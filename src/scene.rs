@@ -0,0 +1,18 @@
+//! Importers turning scene files from other tools into the crate's types.
+
+pub mod gltf;
+pub mod pbrt;
+
+use crate::{camera::ProjectiveCamera, hittable::HittableList};
+
+/// An imported scene. Parts of the file the renderer cannot represent are
+/// skipped or approximated, with a message in `warnings` for each.
+#[derive(Default)]
+pub struct Scene {
+    pub world: HittableList,
+    pub camera: Option<ProjectiveCamera>,
+    /// Image width and height, when the file specifies them.
+    pub image_size: Option<(i32, i32)>,
+    pub samples_per_pixel: Option<i32>,
+    pub warnings: Vec<String>,
+}
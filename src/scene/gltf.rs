@@ -0,0 +1,641 @@
+//! glTF 2.0 import, from `.gltf` files with external or embedded buffers and
+//! from binary `.glb` files.
+//!
+//! Triangle meshes, the node hierarchy, perspective cameras, punctual lights
+//! and the factors of metallic-roughness materials are imported. Textures
+//! are not, and animation, skins and morph targets are ignored.
+
+use std::{
+    collections::HashSet,
+    fs,
+    io,
+    path::Path,
+    sync::Arc,
+};
+
+use serde_json::Value;
+
+use crate::{
+    camera::ProjectiveCamera,
+    hittable::{sphere::Sphere, transformed::Transformed, triangle::TriangleMesh, Hittable},
+    material::{
+        dielectric::Dielectric, diffuse_light::DiffuseLight, lambertian::Lambertian, metal::Metal,
+        mix::MixMaterial, Material,
+    },
+    matrix::Mat4,
+    mesh::Mesh,
+    scene::Scene,
+    transform::Transform,
+    vec3::{Color, Point, Vec3},
+};
+
+/// Radius of the spheres standing in for point and spot lights.
+pub const LIGHT_RADIUS: f64 = 0.05;
+
+const GLB_MAGIC: &[u8; 4] = b"glTF";
+const CHUNK_JSON: u32 = 0x4E4F534A;
+const CHUNK_BIN: u32 = 0x004E4942;
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Loads a `.gltf` or `.glb` file, resolving external buffers relative to
+/// it.
+pub fn load(path: impl AsRef<Path>) -> io::Result<Scene> {
+    let path = path.as_ref();
+    let bytes = fs::read(path)?;
+    parse(&bytes, path.parent().unwrap_or_else(|| Path::new(".")))
+}
+
+/// Imports glTF JSON or a GLB container, resolving external buffers
+/// relative to `base_dir`.
+pub fn parse(bytes: &[u8], base_dir: &Path) -> io::Result<Scene> {
+    let (json, bin) = if bytes.starts_with(GLB_MAGIC) {
+        split_glb(bytes)?
+    } else {
+        (bytes, None)
+    };
+    let document: Value =
+        serde_json::from_slice(json).map_err(|e| invalid_data(format!("invalid glTF JSON: {e}")))?;
+    Importer::new(document, bin, base_dir)?.import()
+}
+
+/// Splits a GLB container into its JSON and binary chunks.
+fn split_glb(bytes: &[u8]) -> io::Result<(&[u8], Option<&[u8]>)> {
+    let word = |offset: usize| -> io::Result<u32> {
+        bytes
+            .get(offset..offset + 4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .ok_or_else(|| invalid_data("truncated GLB file".to_string()))
+    };
+    if word(4)? != 2 {
+        return Err(invalid_data(format!("unsupported GLB version {}", word(4)?)));
+    }
+    let length = usize::min(word(8)? as usize, bytes.len());
+
+    let mut json = None;
+    let mut bin = None;
+    let mut offset = 12;
+    while offset + 8 <= length {
+        let (chunk_length, chunk_type) = (word(offset)? as usize, word(offset + 4)?);
+        let data = bytes
+            .get(offset + 8..offset + 8 + chunk_length)
+            .ok_or_else(|| invalid_data("truncated GLB chunk".to_string()))?;
+        match chunk_type {
+            CHUNK_JSON if json.is_none() => json = Some(data),
+            CHUNK_BIN if bin.is_none() => bin = Some(data),
+            _ => {}
+        }
+        offset += 8 + chunk_length;
+    }
+    let json = json.ok_or_else(|| invalid_data("GLB file has no JSON chunk".to_string()))?;
+    Ok((json, bin))
+}
+
+/// Decodes standard base64, as used by `data:` URIs.
+fn decode_base64(text: &str) -> io::Result<Vec<u8>> {
+    let value = |c: u8| match c {
+        b'A'..=b'Z' => Some(c - b'A'),
+        b'a'..=b'z' => Some(c - b'a' + 26),
+        b'0'..=b'9' => Some(c - b'0' + 52),
+        b'+' => Some(62),
+        b'/' => Some(63),
+        _ => None,
+    };
+    let mut bytes = Vec::with_capacity(text.len() * 3 / 4);
+    let mut accum = 0u32;
+    let mut bits = 0;
+    for c in text.bytes().filter(|c| !c.is_ascii_whitespace() && *c != b'=') {
+        let v = value(c).ok_or_else(|| invalid_data("invalid base64 data".to_string()))?;
+        accum = accum << 6 | v as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((accum >> bits) as u8);
+        }
+    }
+    Ok(bytes)
+}
+
+fn number(value: &Value, default: f64) -> f64 {
+    value.as_f64().unwrap_or(default)
+}
+
+fn numbers<const N: usize>(value: &Value, default: [f64; N]) -> [f64; N] {
+    let mut out = default;
+    if let Some(array) = value.as_array() {
+        for (o, v) in out.iter_mut().zip(array) {
+            *o = number(v, *o);
+        }
+    }
+    out
+}
+
+fn index(value: &Value) -> Option<usize> {
+    value.as_u64().map(|i| i as usize)
+}
+
+struct Importer<'a> {
+    document: Value,
+    buffers: Vec<Vec<u8>>,
+    materials: Vec<Arc<dyn Material + Sync + Send>>,
+    default_material: Arc<dyn Material + Sync + Send>,
+    /// Primitives of each mesh, built once and shared by every node using
+    /// it.
+    meshes: Vec<Vec<Arc<dyn Hittable + Sync + Send>>>,
+    scene: Scene,
+    base_dir: &'a Path,
+}
+
+impl<'a> Importer<'a> {
+    fn new(document: Value, bin: Option<&[u8]>, base_dir: &'a Path) -> io::Result<Self> {
+        let mut importer = Self {
+            document,
+            buffers: Vec::new(),
+            materials: Vec::new(),
+            default_material: Arc::new(Lambertian::new(Color::new(0.8, 0.8, 0.8))),
+            meshes: Vec::new(),
+            scene: Scene::default(),
+            base_dir,
+        };
+        let version = importer.document["asset"]["version"].as_str().unwrap_or("");
+        if !version.starts_with("2.") {
+            return Err(invalid_data(format!("unsupported glTF version '{version}'")));
+        }
+        importer.load_buffers(bin)?;
+        Ok(importer)
+    }
+
+    fn warn(&mut self, message: String) {
+        if !self.scene.warnings.contains(&message) {
+            self.scene.warnings.push(message);
+        }
+    }
+
+    fn load_buffers(&mut self, bin: Option<&[u8]>) -> io::Result<()> {
+        let buffers = self.document["buffers"].as_array().cloned().unwrap_or_default();
+        for (i, buffer) in buffers.iter().enumerate() {
+            let data = match buffer["uri"].as_str() {
+                None if i == 0 => bin
+                    .ok_or_else(|| invalid_data("buffer 0 has no URI and there is no GLB binary chunk".to_string()))?
+                    .to_vec(),
+                None => return Err(invalid_data(format!("buffer {i} has no URI"))),
+                Some(uri) if uri.starts_with("data:") => {
+                    let (_, encoded) = uri
+                        .split_once(";base64,")
+                        .ok_or_else(|| invalid_data(format!("buffer {i} has an unsupported data URI")))?;
+                    decode_base64(encoded)?
+                }
+                Some(uri) => fs::read(self.base_dir.join(uri.replace("%20", " ")))?,
+            };
+            let length = buffer["byteLength"].as_u64().unwrap_or(0) as usize;
+            if data.len() < length {
+                return Err(invalid_data(format!("buffer {i} is shorter than its byteLength")));
+            }
+            self.buffers.push(data);
+        }
+        Ok(())
+    }
+
+    /// Reads an accessor as `count` elements of `components` values each,
+    /// converting normalized integers to `[0, 1]` or `[-1, 1]`.
+    fn accessor(&self, i: usize) -> io::Result<(usize, Vec<f64>)> {
+        let accessor = &self.document["accessors"][i];
+        let components = match accessor["type"].as_str() {
+            Some("SCALAR") => 1,
+            Some("VEC2") => 2,
+            Some("VEC3") => 3,
+            Some("VEC4") => 4,
+            other => return Err(invalid_data(format!("accessor {i} has unsupported type {other:?}"))),
+        };
+        let count = accessor["count"].as_u64().unwrap_or(0) as usize;
+        if !accessor["sparse"].is_null() {
+            return Err(invalid_data(format!("accessor {i} is sparse, which is not supported")));
+        }
+        let len = count
+            .checked_mul(components)
+            .ok_or_else(|| invalid_data(format!("accessor {i} has too many elements")))?;
+        let Some(view) = index(&accessor["bufferView"]) else {
+            let mut values = Vec::new();
+            values
+                .try_reserve_exact(len)
+                .map_err(|_| invalid_data(format!("accessor {i} has too many elements")))?;
+            values.resize(len, 0.0);
+            return Ok((components, values));
+        };
+
+        let component_type = accessor["componentType"].as_u64().unwrap_or(0);
+        let size = match component_type {
+            5120 | 5121 => 1,
+            5122 | 5123 => 2,
+            5125 | 5126 => 4,
+            _ => return Err(invalid_data(format!("accessor {i} has invalid component type {component_type}"))),
+        };
+        let normalized = accessor["normalized"].as_bool().unwrap_or(false);
+        let view = &self.document["bufferViews"][view];
+        let buffer = index(&view["buffer"])
+            .and_then(|b| self.buffers.get(b))
+            .ok_or_else(|| invalid_data(format!("accessor {i} refers to a missing buffer")))?;
+        let start = view["byteOffset"].as_u64().unwrap_or(0) as usize + accessor["byteOffset"].as_u64().unwrap_or(0) as usize;
+        let stride = view["byteStride"].as_u64().map_or(components * size, |s| s as usize);
+        if stride < components * size {
+            return Err(invalid_data(format!("accessor {i} has elements that overlap")));
+        }
+
+        // The last element has to end inside the buffer, which also bounds
+        // the allocation below by the size of the file.
+        let past_end = || invalid_data(format!("accessor {i} runs past the end of its buffer"));
+        if count > 0 {
+            let end = (count - 1)
+                .checked_mul(stride)
+                .and_then(|last| last.checked_add(start))
+                .and_then(|last| last.checked_add(components * size))
+                .ok_or_else(past_end)?;
+            if end > buffer.len() {
+                return Err(past_end());
+            }
+        }
+
+        let mut values = Vec::with_capacity(len);
+        for element in 0..count {
+            for c in 0..components {
+                let at = start + element * stride + c * size;
+                let b = &buffer[at..at + size];
+                let value = match component_type {
+                    5120 => (b[0] as i8 as f64, 127.0),
+                    5121 => (b[0] as f64, 255.0),
+                    5122 => (i16::from_le_bytes([b[0], b[1]]) as f64, 32767.0),
+                    5123 => (u16::from_le_bytes([b[0], b[1]]) as f64, 65535.0),
+                    5125 => (u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f64, 1.0),
+                    _ => (f32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f64, 1.0),
+                };
+                values.push(if normalized { f64::max(value.0 / value.1, -1.0) } else { value.0 });
+            }
+        }
+        Ok((components, values))
+    }
+
+    fn material(&mut self, material: &Value) -> Arc<dyn Material + Sync + Send> {
+        let pbr = &material["pbrMetallicRoughness"];
+        for texture in ["baseColorTexture", "metallicRoughnessTexture"] {
+            if !pbr[texture].is_null() {
+                self.warn(format!("{texture} is not supported; using material factors"));
+            }
+        }
+        if !material["normalTexture"].is_null() || !material["emissiveTexture"].is_null() {
+            self.warn("normal and emissive textures are not supported".to_string());
+        }
+
+        let extensions = &material["extensions"];
+        let [r, g, b] = numbers(&material["emissiveFactor"], [0.0; 3]);
+        let strength = number(&extensions["KHR_materials_emissive_strength"]["emissiveStrength"], 1.0);
+        if r > 0.0 || g > 0.0 || b > 0.0 {
+            return Arc::new(DiffuseLight::new(strength * Color::new(r, g, b)));
+        }
+
+        let [r, g, b, _] = numbers(&pbr["baseColorFactor"], [1.0; 4]);
+        let base = Color::new(r, g, b);
+        let metallic = number(&pbr["metallicFactor"], 1.0);
+        let roughness = number(&pbr["roughnessFactor"], 1.0);
+        let diffuse = || -> Arc<dyn Material + Sync + Send> { Arc::new(Lambertian::new(base)) };
+        let metal = || -> Arc<dyn Material + Sync + Send> { Arc::new(Metal::new(base, roughness)) };
+        let surface = if metallic <= 0.0 {
+            diffuse()
+        } else if metallic >= 1.0 {
+            metal()
+        } else {
+            Arc::new(MixMaterial::new(diffuse(), metal(), metallic))
+        };
+
+        let transmission = number(&extensions["KHR_materials_transmission"]["transmissionFactor"], 0.0);
+        if transmission <= 0.0 {
+            return surface;
+        }
+        let ior = number(&extensions["KHR_materials_ior"]["ior"], 1.5);
+        let glass = Arc::new(Dielectric::new(ior));
+        if transmission >= 1.0 {
+            glass
+        } else {
+            Arc::new(MixMaterial::new(surface, glass, transmission))
+        }
+    }
+
+    fn primitive(&mut self, mesh: usize, primitive: &Value) -> io::Result<Option<Arc<dyn Hittable + Sync + Send>>> {
+        if primitive["mode"].as_u64().unwrap_or(4) != 4 {
+            self.warn(format!("mesh {mesh} has non-triangle primitives, which are skipped"));
+            return Ok(None);
+        }
+        let attributes = &primitive["attributes"];
+        let Some(positions) = index(&attributes["POSITION"]) else {
+            return Ok(None);
+        };
+        let (_, positions) = self.accessor(positions)?;
+        let positions: Vec<Point> = positions.chunks_exact(3).map(|p| Point::new(p[0], p[1], p[2])).collect();
+
+        let indices: Vec<usize> = match index(&primitive["indices"]) {
+            Some(i) => self.accessor(i)?.1.into_iter().map(|i| i as usize).collect(),
+            None => (0..positions.len()).collect(),
+        };
+        if indices.iter().any(|&i| i >= positions.len()) {
+            return Err(invalid_data(format!("mesh {mesh} has out of range indices")));
+        }
+        let indices: Vec<[usize; 3]> = indices.chunks_exact(3).map(|t| [t[0], t[1], t[2]]).collect();
+        if indices.is_empty() {
+            return Ok(None);
+        }
+
+        let mut data = Mesh::new(positions, indices);
+        if let Some(normals) = index(&attributes["NORMAL"]) {
+            data.normals = self.accessor(normals)?.1.chunks_exact(3).map(|n| Vec3::new(n[0], n[1], n[2])).collect();
+        }
+        if let Some(uvs) = index(&attributes["TEXCOORD_0"]) {
+            // glTF puts the texture origin at the top left.
+            data.uvs = self.accessor(uvs)?.1.chunks_exact(2).map(|uv| (uv[0], 1.0 - uv[1])).collect();
+        }
+        if data.normals.len() != data.positions.len() {
+            data.normals.clear();
+        }
+        if data.uvs.len() != data.positions.len() {
+            data.uvs.clear();
+        }
+
+        let material = match index(&primitive["material"]) {
+            Some(i) => self
+                .materials
+                .get(i)
+                .cloned()
+                .ok_or_else(|| invalid_data(format!("mesh {mesh} refers to missing material {i}")))?,
+            None => Arc::clone(&self.default_material),
+        };
+        Ok(Some(Arc::new(TriangleMesh::new(data, material))))
+    }
+
+    fn local_transform(node: &Value) -> Mat4 {
+        if node["matrix"].is_array() {
+            let identity = [1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0];
+            let m = numbers(&node["matrix"], identity);
+            // Stored column by column.
+            return Mat4::new([
+                [m[0], m[4], m[8], m[12]],
+                [m[1], m[5], m[9], m[13]],
+                [m[2], m[6], m[10], m[14]],
+                [m[3], m[7], m[11], m[15]],
+            ]);
+        }
+        let [tx, ty, tz] = numbers(&node["translation"], [0.0; 3]);
+        let rotation = numbers(&node["rotation"], [0.0, 0.0, 0.0, 1.0]);
+        let [sx, sy, sz] = numbers(&node["scale"], [1.0; 3]);
+        Transform::translate(Vec3::new(tx, ty, tz)).matrix()
+            * Transform::rotate_quaternion(rotation).matrix()
+            * Transform::scale(Vec3::new(sx, sy, sz)).matrix()
+    }
+
+    fn visit(&mut self, node_index: usize, parent: Mat4, visited: &mut HashSet<usize>) -> io::Result<()> {
+        if !visited.insert(node_index) {
+            return Err(invalid_data(format!("node {node_index} appears more than once in the hierarchy")));
+        }
+        let node = self.document["nodes"][node_index].clone();
+        let world = parent * Self::local_transform(&node);
+
+        if let Some(mesh) = index(&node["mesh"]) {
+            let primitives = self
+                .meshes
+                .get(mesh)
+                .cloned()
+                .ok_or_else(|| invalid_data(format!("node {node_index} refers to missing mesh {mesh}")))?;
+            if world.inverse().is_none() {
+                self.warn(format!("node {node_index} has a singular transform and is skipped"));
+            } else if world == Mat4::identity() {
+                primitives.into_iter().for_each(|p| self.scene.world.add(p));
+            } else {
+                let transform = Transform::new(world);
+                for primitive in primitives {
+                    self.scene.world.add(Arc::new(Transformed::new(primitive, transform)));
+                }
+            }
+        }
+
+        if let Some(camera) = index(&node["camera"]) {
+            self.camera(camera, world);
+        }
+        if let Some(light) = index(&node["extensions"]["KHR_lights_punctual"]["light"]) {
+            self.light(light, world);
+        }
+
+        for child in node["children"].as_array().cloned().unwrap_or_default() {
+            let child = index(&child).ok_or_else(|| invalid_data(format!("node {node_index} has an invalid child")))?;
+            self.visit(child, world, visited)?;
+        }
+        Ok(())
+    }
+
+    /// Uses the first camera found; glTF cameras look down their local -z
+    /// axis with +y up.
+    fn camera(&mut self, camera: usize, world: Mat4) {
+        if self.scene.camera.is_some() {
+            self.warn("only the first camera in the scene is used".to_string());
+            return;
+        }
+        let camera = &self.document["cameras"][camera];
+        if camera["type"].as_str() != Some("perspective") {
+            self.warn(format!("{} cameras are not supported", camera["type"].as_str().unwrap_or("unknown")));
+            return;
+        }
+        let perspective = &camera["perspective"];
+        let vfov = number(&perspective["yfov"], 0.8).to_degrees();
+        let aspect_ratio = number(&perspective["aspectRatio"], 16.0 / 9.0);
+        let look_from = world.transform_point(Point::new(0.0, 0.0, 0.0));
+        let look_at = world.transform_point(Point::new(0.0, 0.0, -1.0));
+        let v_up = world.transform_vector(Vec3::new(0.0, 1.0, 0.0));
+        self.scene.camera = Some(ProjectiveCamera::new(look_from, look_at, v_up, vfov, aspect_ratio, 0.0, 1.0));
+    }
+
+    /// Stands in a small emissive sphere for point and spot lights, with the
+    /// same radiant intensity as the light.
+    fn light(&mut self, light: usize, world: Mat4) {
+        let light = self.document["extensions"]["KHR_lights_punctual"]["lights"][light].clone();
+        match light["type"].as_str() {
+            Some("point") => {}
+            Some("spot") => self.warn("spot light cones are not supported; spot lights emit in all directions".to_string()),
+            other => {
+                self.warn(format!("{} lights are not supported", other.unwrap_or("unknown")));
+                return;
+            }
+        }
+        let [r, g, b] = numbers(&light["color"], [1.0; 3]);
+        let intensity = number(&light["intensity"], 1.0);
+        let radiance = intensity / (std::f64::consts::PI * LIGHT_RADIUS * LIGHT_RADIUS) * Color::new(r, g, b);
+        let center = world.transform_point(Point::new(0.0, 0.0, 0.0));
+        let material = Arc::new(DiffuseLight::new(radiance));
+        self.scene.world.add(Arc::new(Sphere::new(center, LIGHT_RADIUS, material)));
+    }
+
+    fn import(mut self) -> io::Result<Scene> {
+        for material in self.document["materials"].as_array().cloned().unwrap_or_default() {
+            let material = self.material(&material);
+            self.materials.push(material);
+        }
+        for (i, mesh) in self.document["meshes"].as_array().cloned().unwrap_or_default().iter().enumerate() {
+            let mut primitives = Vec::new();
+            for primitive in mesh["primitives"].as_array().cloned().unwrap_or_default() {
+                primitives.extend(self.primitive(i, &primitive)?);
+            }
+            self.meshes.push(primitives);
+        }
+        for key in ["animations", "skins"] {
+            if self.document[key].as_array().is_some_and(|a| !a.is_empty()) {
+                self.warn(format!("{key} are not supported"));
+            }
+        }
+
+        // The default scene, or else every node that is nobody's child.
+        let scene = index(&self.document["scene"]).unwrap_or(0);
+        let roots: Vec<usize> = match self.document["scenes"][scene]["nodes"].as_array() {
+            Some(nodes) => nodes.iter().filter_map(index).collect(),
+            None => {
+                let nodes = self.document["nodes"].as_array().cloned().unwrap_or_default();
+                let children: HashSet<usize> = nodes
+                    .iter()
+                    .flat_map(|n| n["children"].as_array().cloned().unwrap_or_default())
+                    .filter_map(|c| index(&c))
+                    .collect();
+                (0..nodes.len()).filter(|i| !children.contains(i)).collect()
+            }
+        };
+        let mut visited = HashSet::new();
+        for root in roots {
+            self.visit(root, Mat4::identity(), &mut visited)?;
+        }
+        Ok(self.scene)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::{camera::Camera, hittable::Hittable, ray::Ray, vec3::{Point, Vec3}};
+
+    use super::{decode_base64, parse, LIGHT_RADIUS};
+
+    /// A red triangle five units down -z, a camera at the origin with a
+    /// point light above it, and an unsupported directional light.
+    fn glb() -> Vec<u8> {
+        let mut bin: Vec<u8> = [-1.0f32, -1.0, 0.0, 1.0, -1.0, 0.0, 0.0, 1.0, 0.0]
+            .iter()
+            .flat_map(|f| f.to_le_bytes())
+            .collect();
+        bin.extend([0u16, 1, 2, 0].iter().flat_map(|i| i.to_le_bytes()));
+        let json = format!(
+            r#"{{
+                "asset": {{"version": "2.0"}},
+                "scene": 0,
+                "scenes": [{{"nodes": [0, 1]}}],
+                "nodes": [
+                    {{"mesh": 0, "translation": [0, 0, -5]}},
+                    {{"camera": 0, "children": [2, 3]}},
+                    {{"extensions": {{"KHR_lights_punctual": {{"light": 0}}}}, "translation": [0, 2, 0]}},
+                    {{"extensions": {{"KHR_lights_punctual": {{"light": 1}}}}}}
+                ],
+                "cameras": [{{"type": "perspective", "perspective": {{"yfov": 0.5, "aspectRatio": 2.0, "znear": 0.1}}}}],
+                "extensions": {{"KHR_lights_punctual": {{"lights": [
+                    {{"type": "point", "intensity": 3.0}},
+                    {{"type": "directional"}}
+                ]}}}},
+                "materials": [{{"pbrMetallicRoughness": {{"baseColorFactor": [1, 0, 0, 1], "metallicFactor": 0}}}}],
+                "meshes": [{{"primitives": [{{"attributes": {{"POSITION": 0}}, "indices": 1, "material": 0}}]}}],
+                "accessors": [
+                    {{"bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3"}},
+                    {{"bufferView": 1, "componentType": 5123, "count": 3, "type": "SCALAR"}}
+                ],
+                "bufferViews": [
+                    {{"buffer": 0, "byteOffset": 0, "byteLength": 36}},
+                    {{"buffer": 0, "byteOffset": 36, "byteLength": 6}}
+                ],
+                "buffers": [{{"byteLength": {}}}]
+            }}"#,
+            bin.len()
+        );
+        let mut json = json.into_bytes();
+        while json.len() % 4 != 0 {
+            json.push(b' ');
+        }
+
+        let mut glb = b"glTF".to_vec();
+        glb.extend(2u32.to_le_bytes());
+        glb.extend(((12 + 8 + json.len() + 8 + bin.len()) as u32).to_le_bytes());
+        glb.extend((json.len() as u32).to_le_bytes());
+        glb.extend(b"JSON");
+        glb.extend(json);
+        glb.extend((bin.len() as u32).to_le_bytes());
+        glb.extend(b"BIN\0");
+        glb.extend(bin);
+        glb
+    }
+
+    #[test]
+    fn imports_glb() {
+        let scene = parse(&glb(), Path::new(".")).unwrap();
+
+        let camera = scene.camera.expect("the scene has a camera");
+        let ray = camera.get_ray(0.5, 0.5).unwrap();
+        assert!((ray.direction() - Vec3::new(0.0, 0.0, -1.0) * ray.direction().length()).length() < 1e-9);
+        let rec = scene.world.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        assert!(f64::abs(rec.p.z() + 5.0) < 1e-9);
+
+        let down = Ray::new(Point::new(0.0, 5.0, 0.0), Vec3::new(0.0, -1.0, 0.0));
+        let rec = scene.world.hit(&down, 0.001, f64::INFINITY).unwrap();
+        assert!(f64::abs(rec.t - (3.0 - LIGHT_RADIUS)) < 1e-9);
+        let emitted = rec.material.unwrap().emitted(rec.u, rec.v, rec.p);
+        assert!(emitted.x() > 0.0);
+
+        assert_eq!(scene.warnings, vec!["directional lights are not supported".to_string()]);
+    }
+
+    #[test]
+    fn rejects_bad_input() {
+        assert!(parse(br#"{"asset": {"version": "1.0"}}"#, Path::new(".")).is_err());
+        assert!(parse(b"not json", Path::new(".")).is_err());
+        let mut truncated = glb();
+        truncated.truncate(40);
+        assert!(parse(&truncated, Path::new(".")).is_err());
+    }
+
+    #[test]
+    fn rejects_accessors_larger_than_their_buffer() {
+        let document = |accessor: &str| {
+            format!(
+                r#"{{
+                    "asset": {{"version": "2.0"}},
+                    "scenes": [{{"nodes": [0]}}],
+                    "nodes": [{{"mesh": 0}}],
+                    "meshes": [{{"primitives": [{{"attributes": {{"POSITION": 0}}}}]}}],
+                    "accessors": [{accessor}],
+                    "bufferViews": [{{"buffer": 0, "byteLength": 36}}],
+                    "buffers": [{{"byteLength": 36, "uri": "data:application/octet-stream;base64,{}"}}]
+                }}"#,
+                "A".repeat(48)
+            )
+        };
+        let parse_accessor = |accessor: &str| parse(document(accessor).as_bytes(), Path::new("."));
+
+        assert!(parse_accessor(r#"{"bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3"}"#).is_ok());
+        for accessor in [
+            r#"{"bufferView": 0, "componentType": 5126, "count": 4, "type": "VEC3"}"#,
+            r#"{"bufferView": 0, "componentType": 5126, "count": 1000000000000000, "type": "VEC3"}"#,
+            r#"{"bufferView": 0, "componentType": 5126, "count": 18446744073709551615, "type": "VEC3"}"#,
+            r#"{"componentType": 5126, "count": 18446744073709551615, "type": "VEC3"}"#,
+        ] {
+            let error = parse_accessor(accessor).err().expect(accessor);
+            assert_eq!(error.kind(), std::io::ErrorKind::InvalidData, "{accessor}");
+        }
+    }
+
+    #[test]
+    fn decodes_base64() {
+        assert_eq!(decode_base64("aGVsbG8gd29ybGQ=").unwrap(), b"hello world");
+        assert_eq!(decode_base64("AAEC/w==").unwrap(), vec![0, 1, 2, 255]);
+        assert!(decode_base64("a*b").is_err());
+    }
+}
@@ -0,0 +1,731 @@
+//! Import of the common subset of the pbrt-v3 scene format: the camera and
+//! film setup, spheres and triangle meshes, matte, metal and glass
+//! materials, diffuse area lights, and the transform and attribute stacks.
+//! Anything else is skipped with a warning.
+//!
+//! pbrt uses a left-handed coordinate system, so the world is mirrored
+//! across the camera's vertical plane to render the same image with the
+//! crate's right-handed cameras.
+
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use crate::{
+    camera::ProjectiveCamera,
+    hittable::{sphere::Sphere, transformed::Transformed, triangle::TriangleMesh},
+    material::{
+        dielectric::Dielectric, diffuse_light::DiffuseLight, lambertian::Lambertian, metal::Metal, Material,
+    },
+    matrix::Mat4,
+    mesh::Mesh,
+    scene::Scene,
+    transform::Transform,
+    vec3::{cross, unit_vector, Color, Point, Vec3},
+};
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Loads a `.pbrt` file, resolving `Include`d files relative to it.
+pub fn load(path: impl AsRef<Path>) -> io::Result<Scene> {
+    let path = path.as_ref();
+    let text = fs::read_to_string(path)?;
+    let mut importer = Importer::new(path.parent().unwrap_or_else(|| Path::new(".")));
+    importer.including.push(fs::canonicalize(path)?);
+    importer.run(&text)?;
+    Ok(importer.finish())
+}
+
+/// Imports a pbrt-v3 scene description, resolving `Include`d files relative
+/// to `base_dir`.
+pub fn parse(text: &str, base_dir: &Path) -> io::Result<Scene> {
+    let mut importer = Importer::new(base_dir);
+    importer.run(text)?;
+    Ok(importer.finish())
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Str(String),
+    Num(f64),
+    Open,
+    Close,
+}
+
+fn tokenize(text: &str) -> io::Result<Vec<(Token, usize)>> {
+    let mut tokens = Vec::new();
+    for (number, line) in text.lines().enumerate() {
+        let line_number = number + 1;
+        let mut chars = line.char_indices().peekable();
+        while let Some(&(start, c)) = chars.peek() {
+            match c {
+                '#' => break,
+                c if c.is_whitespace() => {
+                    chars.next();
+                }
+                '[' | ']' => {
+                    chars.next();
+                    tokens.push((if c == '[' { Token::Open } else { Token::Close }, line_number));
+                }
+                '"' => {
+                    chars.next();
+                    let mut s = String::new();
+                    loop {
+                        match chars.next() {
+                            Some((_, '"')) => break,
+                            Some((_, c)) => s.push(c),
+                            None => return Err(invalid_data(format!("unterminated string on line {line_number}"))),
+                        }
+                    }
+                    tokens.push((Token::Str(s), line_number));
+                }
+                _ => {
+                    let mut end = line.len();
+                    while let Some(&(i, c)) = chars.peek() {
+                        if c.is_whitespace() || matches!(c, '[' | ']' | '"' | '#') {
+                            end = i;
+                            break;
+                        }
+                        chars.next();
+                    }
+                    let word = &line[start..end];
+                    let token = match word.parse::<f64>() {
+                        Ok(n) => Token::Num(n),
+                        Err(_) => Token::Word(word.to_string()),
+                    };
+                    tokens.push((token, line_number));
+                }
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Arg {
+    Num(f64),
+    Str(String),
+    List(Vec<Arg>),
+}
+
+/// A directive with its arguments, all of which run up to the next
+/// directive name.
+struct Directive {
+    name: String,
+    args: Vec<Arg>,
+    line: usize,
+}
+
+fn directives(tokens: Vec<(Token, usize)>) -> io::Result<Vec<Directive>> {
+    let mut directives: Vec<Directive> = Vec::new();
+    let mut list: Option<Vec<Arg>> = None;
+    for (token, line) in tokens {
+        let arg = match token {
+            Token::Word(word) if list.is_none() && word != "true" && word != "false" => {
+                directives.push(Directive { name: word, args: Vec::new(), line });
+                continue;
+            }
+            Token::Word(word) => Arg::Str(word),
+            Token::Str(s) => Arg::Str(s),
+            Token::Num(n) => Arg::Num(n),
+            Token::Open if list.is_none() => {
+                list = Some(Vec::new());
+                continue;
+            }
+            Token::Close if list.is_some() => Arg::List(list.take().unwrap()),
+            Token::Open | Token::Close => return Err(invalid_data(format!("unbalanced brackets on line {line}"))),
+        };
+        match (&mut list, directives.last_mut()) {
+            (Some(list), _) => list.push(arg),
+            (None, Some(directive)) => directive.args.push(arg),
+            (None, None) => return Err(invalid_data(format!("expected a directive on line {line}"))),
+        }
+    }
+    if list.is_some() {
+        return Err(invalid_data("unterminated list at end of file".to_string()));
+    }
+    Ok(directives)
+}
+
+/// Parameter list of a directive: `"type name" value` pairs.
+struct Params {
+    params: Vec<(String, String, Vec<Arg>)>,
+}
+
+impl Params {
+    fn new(args: &[Arg], line: usize) -> io::Result<Self> {
+        let mut params = Vec::new();
+        for pair in args.chunks(2) {
+            let (Arg::Str(declaration), Some(value)) = (&pair[0], pair.get(1)) else {
+                return Err(invalid_data(format!("malformed parameter list on line {line}")));
+            };
+            let mut words = declaration.split_whitespace();
+            let (Some(ty), Some(name)) = (words.next(), words.next()) else {
+                return Err(invalid_data(format!("malformed parameter '{declaration}' on line {line}")));
+            };
+            let values = match value {
+                Arg::List(values) => values.clone(),
+                value => vec![value.clone()],
+            };
+            params.push((ty.to_string(), name.to_string(), values));
+        }
+        Ok(Self { params })
+    }
+
+    fn find(&self, name: &str) -> Option<(&str, &[Arg])> {
+        self.params
+            .iter()
+            .find(|(_, n, _)| n == name)
+            .map(|(ty, _, values)| (ty.as_str(), values.as_slice()))
+    }
+
+    fn floats(&self, name: &str) -> Option<Vec<f64>> {
+        let (_, values) = self.find(name)?;
+        values
+            .iter()
+            .map(|v| match v {
+                Arg::Num(n) => Some(*n),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn float(&self, name: &str, default: f64) -> f64 {
+        self.floats(name).and_then(|v| v.first().copied()).unwrap_or(default)
+    }
+
+    fn string(&self, name: &str) -> Option<&str> {
+        match self.find(name)?.1.first()? {
+            Arg::Str(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// An RGB colour parameter. Spectral and textured values cannot be
+    /// converted and give an `Err` naming their type.
+    fn color(&self, name: &str) -> Result<Option<Color>, String> {
+        match self.find(name) {
+            None => Ok(None),
+            Some(("rgb" | "color", _)) => match self.floats(name).as_deref() {
+                Some([r, g, b]) => Ok(Some(Color::new(*r, *g, *b))),
+                _ => Err("malformed rgb".to_string()),
+            },
+            Some((ty, _)) => Err(ty.to_string()),
+        }
+    }
+}
+
+fn positional(args: &[Arg], count: usize, directive: &Directive) -> io::Result<Vec<f64>> {
+    // Matrices may be given bare or in brackets.
+    let flattened: Vec<&Arg> = args
+        .iter()
+        .flat_map(|a| match a {
+            Arg::List(values) => values.iter().collect(),
+            a => vec![a],
+        })
+        .collect();
+    let numbers: Option<Vec<f64>> = flattened
+        .iter()
+        .map(|a| match a {
+            Arg::Num(n) => Some(*n),
+            _ => None,
+        })
+        .collect();
+    match numbers {
+        Some(numbers) if numbers.len() == count => Ok(numbers),
+        _ => Err(invalid_data(format!(
+            "{} on line {} expects {count} numbers",
+            directive.name, directive.line
+        ))),
+    }
+}
+
+/// The directive's leading type string, such as the `"sphere"` of `Shape
+/// "sphere"`, and its parameters.
+fn typed(directive: &Directive) -> io::Result<(&str, Params)> {
+    match directive.args.split_first() {
+        Some((Arg::Str(ty), rest)) => Ok((ty, Params::new(rest, directive.line)?)),
+        _ => Err(invalid_data(format!(
+            "{} on line {} expects a type name",
+            directive.name, directive.line
+        ))),
+    }
+}
+
+#[derive(Clone)]
+struct GraphicsState {
+    ctm: Mat4,
+    material: Arc<dyn Material + Sync + Send>,
+    area_light: Option<Color>,
+    reverse_orientation: bool,
+}
+
+struct CameraSetup {
+    camera_to_world: Mat4,
+    fov: f64,
+    lens_radius: f64,
+    focal_distance: f64,
+}
+
+struct Importer<'a> {
+    base_dir: &'a Path,
+    state: GraphicsState,
+    attribute_stack: Vec<GraphicsState>,
+    transform_stack: Vec<Mat4>,
+    named_materials: HashMap<String, Arc<dyn Material + Sync + Send>>,
+    camera: Option<CameraSetup>,
+    /// Files being read, outermost first, to catch `Include` cycles.
+    including: Vec<PathBuf>,
+    /// Mirrors the world across the camera's vertical plane; set at
+    /// `WorldBegin`.
+    mirror: Mat4,
+    scene: Scene,
+}
+
+fn matte(kd: Color) -> Arc<dyn Material + Sync + Send> {
+    Arc::new(Lambertian::new(kd))
+}
+
+impl<'a> Importer<'a> {
+    fn new(base_dir: &'a Path) -> Self {
+        Self {
+            base_dir,
+            state: GraphicsState {
+                ctm: Mat4::identity(),
+                material: matte(Color::new(0.5, 0.5, 0.5)),
+                area_light: None,
+                reverse_orientation: false,
+            },
+            attribute_stack: Vec::new(),
+            transform_stack: Vec::new(),
+            named_materials: HashMap::new(),
+            camera: None,
+            including: Vec::new(),
+            mirror: Mat4::identity(),
+            scene: Scene::default(),
+        }
+    }
+
+    fn warn(&mut self, message: String) {
+        if !self.scene.warnings.contains(&message) {
+            self.scene.warnings.push(message);
+        }
+    }
+
+    fn run(&mut self, text: &str) -> io::Result<()> {
+        for directive in directives(tokenize(text)?)? {
+            self.directive(&directive)?;
+        }
+        Ok(())
+    }
+
+    fn concat(&mut self, m: Mat4) {
+        self.state.ctm = self.state.ctm * m;
+    }
+
+    fn directive(&mut self, d: &Directive) -> io::Result<()> {
+        match d.name.as_str() {
+            "Identity" => self.state.ctm = Mat4::identity(),
+            "Translate" => {
+                let v = positional(&d.args, 3, d)?;
+                self.concat(Transform::translate(Vec3::new(v[0], v[1], v[2])).matrix());
+            }
+            "Scale" => {
+                let v = positional(&d.args, 3, d)?;
+                self.concat(Transform::scale(Vec3::new(v[0], v[1], v[2])).matrix());
+            }
+            "Rotate" => {
+                let v = positional(&d.args, 4, d)?;
+                self.concat(Transform::rotate(Vec3::new(v[1], v[2], v[3]), v[0]).matrix());
+            }
+            "LookAt" => {
+                let v = positional(&d.args, 9, d)?;
+                self.concat(look_at(
+                    Point::new(v[0], v[1], v[2]),
+                    Point::new(v[3], v[4], v[5]),
+                    Vec3::new(v[6], v[7], v[8]),
+                ));
+            }
+            "Transform" | "ConcatTransform" => {
+                // Given column by column.
+                let m = positional(&d.args, 16, d)?;
+                let m = Mat4::new([
+                    [m[0], m[4], m[8], m[12]],
+                    [m[1], m[5], m[9], m[13]],
+                    [m[2], m[6], m[10], m[14]],
+                    [m[3], m[7], m[11], m[15]],
+                ]);
+                if d.name == "Transform" {
+                    self.state.ctm = m;
+                } else {
+                    self.concat(m);
+                }
+            }
+            "TransformBegin" => self.transform_stack.push(self.state.ctm),
+            "TransformEnd" => {
+                self.state.ctm = self.transform_stack.pop().ok_or_else(|| {
+                    invalid_data(format!("TransformEnd without TransformBegin on line {}", d.line))
+                })?;
+            }
+            "AttributeBegin" => self.attribute_stack.push(self.state.clone()),
+            "AttributeEnd" => {
+                self.state = self.attribute_stack.pop().ok_or_else(|| {
+                    invalid_data(format!("AttributeEnd without AttributeBegin on line {}", d.line))
+                })?;
+            }
+            "ReverseOrientation" => self.state.reverse_orientation = !self.state.reverse_orientation,
+            "Camera" => self.camera(d)?,
+            "Film" => {
+                let (_, params) = typed(d)?;
+                let width = params.float("xresolution", 1280.0) as i32;
+                let height = params.float("yresolution", 720.0) as i32;
+                self.scene.image_size = Some((width, height));
+            }
+            "Sampler" => {
+                let (_, params) = typed(d)?;
+                self.scene.samples_per_pixel = Some(params.float("pixelsamples", 16.0) as i32);
+            }
+            "WorldBegin" => self.world_begin(),
+            "WorldEnd" => {}
+            "Material" => {
+                let (ty, params) = typed(d)?;
+                self.state.material = self.material(ty, &params);
+            }
+            "MakeNamedMaterial" => {
+                let (name, params) = typed(d)?;
+                let ty = params.string("type").unwrap_or("matte").to_string();
+                let material = self.material(&ty, &params);
+                self.named_materials.insert(name.to_string(), material);
+            }
+            "NamedMaterial" => {
+                let (name, _) = typed(d)?;
+                match self.named_materials.get(name) {
+                    Some(material) => self.state.material = Arc::clone(material),
+                    None => self.warn(format!("undefined named material \"{name}\" on line {}", d.line)),
+                }
+            }
+            "AreaLightSource" => {
+                let (ty, params) = typed(d)?;
+                if ty != "diffuse" {
+                    self.warn(format!("AreaLightSource \"{ty}\" is not supported"));
+                    return Ok(());
+                }
+                let radiance = params.color("L").unwrap_or_else(|ty| {
+                    self.warn(format!("{ty} values for L are not supported; using white"));
+                    None
+                });
+                self.state.area_light = Some(radiance.unwrap_or(Color::new(1.0, 1.0, 1.0)));
+            }
+            "Shape" => self.shape(d)?,
+            "Include" => {
+                let (file, _) = typed(d)?;
+                let path = fs::canonicalize(self.base_dir.join(file))?;
+                if self.including.contains(&path) {
+                    return Err(invalid_data(format!("Include of \"{file}\" on line {} is circular", d.line)));
+                }
+                let text = fs::read_to_string(&path)?;
+                self.including.push(path);
+                self.run(&text)?;
+                self.including.pop();
+            }
+            "Integrator" | "PixelFilter" | "Accelerator" | "LightSource" | "Texture" | "MakeNamedMedium"
+            | "MediumInterface" | "ObjectBegin" | "ObjectEnd" | "ObjectInstance" | "CoordinateSystem"
+            | "CoordSysTransform" | "ActiveTransform" | "TransformTimes" => {
+                self.warn(format!("{} is not supported", d.name));
+            }
+            name => self.warn(format!("unknown directive {name} on line {}", d.line)),
+        }
+        Ok(())
+    }
+
+    fn camera(&mut self, d: &Directive) -> io::Result<()> {
+        let (ty, params) = typed(d)?;
+        if ty != "perspective" {
+            self.warn(format!("Camera \"{ty}\" is not supported"));
+            return Ok(());
+        }
+        let Some(camera_to_world) = self.state.ctm.inverse() else {
+            return Err(invalid_data(format!("singular camera transform on line {}", d.line)));
+        };
+        // Camera rays span the focal plane, so keep it at unit distance for
+        // pinhole cameras rather than pbrt's default of 1e6.
+        let lens_radius = params.float("lensradius", 0.0);
+        let focal_distance = if lens_radius > 0.0 { params.float("focaldistance", 1e6) } else { 1.0 };
+        self.camera = Some(CameraSetup {
+            camera_to_world,
+            fov: params.float("fov", 90.0),
+            lens_radius,
+            focal_distance,
+        });
+        Ok(())
+    }
+
+    fn world_begin(&mut self) {
+        // Mirror x in camera space: M = camera_to_world * S(-1, 1, 1) * world_to_camera.
+        let camera_to_world = self.camera.as_ref().map_or(Mat4::identity(), |c| c.camera_to_world);
+        let world_to_camera = camera_to_world.inverse().unwrap_or_else(Mat4::identity);
+        self.mirror = camera_to_world * Transform::scale(Vec3::new(-1.0, 1.0, 1.0)).matrix() * world_to_camera;
+        self.state.ctm = Mat4::identity();
+        self.transform_stack.clear();
+    }
+
+    fn material(&mut self, ty: &str, params: &Params) -> Arc<dyn Material + Sync + Send> {
+        let mut color = |name: &str, default: Color| match params.color(name) {
+            Ok(color) => color.unwrap_or(default),
+            Err(ty) => {
+                self.warn(format!("{ty} values for {name} are not supported; using the default"));
+                default
+            }
+        };
+        let material = match ty {
+            "matte" => matte(color("Kd", Color::new(0.5, 0.5, 0.5))),
+            "glass" => Arc::new(Dielectric::new(params.float("index", 1.5))),
+            "metal" => {
+                // Reflectance at normal incidence from the complex index of
+                // refraction; pbrt's default is copper.
+                let eta = color("eta", Color::new(0.2004, 0.9240, 1.1022));
+                let k = color("k", Color::new(3.9129, 2.4528, 2.1421));
+                let f0 = |n: f64, k: f64| ((n - 1.0).powi(2) + k * k) / ((n + 1.0).powi(2) + k * k);
+                let albedo = Color::new(f0(eta.x(), k.x()), f0(eta.y(), k.y()), f0(eta.z(), k.z()));
+                let roughness = params.float("roughness", 0.01);
+                let roughness = if params.string("remaproughness") == Some("false") {
+                    roughness
+                } else {
+                    f64::sqrt(roughness)
+                };
+                Arc::new(Metal::new(albedo, roughness))
+            }
+            _ => matte(color("Kd", Color::new(0.5, 0.5, 0.5))),
+        };
+        if !matches!(ty, "matte" | "glass" | "metal") {
+            self.warn(format!("Material \"{ty}\" is not supported; using matte"));
+        }
+        material
+    }
+
+    fn shape(&mut self, d: &Directive) -> io::Result<()> {
+        let (ty, params) = typed(d)?;
+        let material: Arc<dyn Material + Sync + Send> = match self.state.area_light {
+            Some(radiance) => Arc::new(DiffuseLight::new(radiance)),
+            None => Arc::clone(&self.state.material),
+        };
+        let object_to_world = self.mirror * self.state.ctm;
+        if object_to_world.inverse().is_none() {
+            self.warn(format!("shape with a singular transform on line {} skipped", d.line));
+            return Ok(());
+        }
+        let transform = Transform::new(object_to_world);
+
+        match ty {
+            "sphere" => {
+                let radius = params.float("radius", 1.0);
+                let sphere = Arc::new(Sphere::new(Point::new(0.0, 0.0, 0.0), radius, material));
+                self.scene.world.add(Arc::new(Transformed::new(sphere, transform)));
+            }
+            "trianglemesh" => {
+                let positions = params.floats("P").unwrap_or_default();
+                let indices = params.floats("indices").unwrap_or_else(|| {
+                    // A lone triangle may leave out its indices.
+                    if positions.len() == 9 {
+                        vec![0.0, 1.0, 2.0]
+                    } else {
+                        vec![]
+                    }
+                });
+                let vertex_count = positions.len() / 3;
+                let valid_index = |i: f64| i >= 0.0 && i.fract() == 0.0 && (i as usize) < vertex_count;
+                if positions.len() % 3 != 0 || indices.len() % 3 != 0 || !indices.iter().all(|&i| valid_index(i)) {
+                    return Err(invalid_data(format!("malformed trianglemesh on line {}", d.line)));
+                }
+                if indices.is_empty() {
+                    return Ok(());
+                }
+
+                // Baked into world space. Mirroring flips the winding, which
+                // would turn the geometric normals inside out.
+                let flip = transform.swaps_handedness() != self.state.reverse_orientation;
+                let positions = positions
+                    .chunks_exact(3)
+                    .map(|p| transform.point(Point::new(p[0], p[1], p[2])))
+                    .collect();
+                let indices = indices
+                    .chunks_exact(3)
+                    .map(|t| {
+                        let [a, b, c] = [t[0] as usize, t[1] as usize, t[2] as usize];
+                        if flip {
+                            [a, c, b]
+                        } else {
+                            [a, b, c]
+                        }
+                    })
+                    .collect();
+                let mut mesh = Mesh::new(positions, indices);
+
+                let normals = params.floats("N").unwrap_or_default();
+                if normals.len() == 3 * vertex_count {
+                    mesh.normals = normals
+                        .chunks_exact(3)
+                        .map(|n| unit_vector(transform.normal(Vec3::new(n[0], n[1], n[2]))))
+                        .collect();
+                }
+                let uvs = params.floats("uv").or_else(|| params.floats("st")).unwrap_or_default();
+                if uvs.len() == 2 * vertex_count {
+                    mesh.uvs = uvs.chunks_exact(2).map(|uv| (uv[0], uv[1])).collect();
+                }
+                self.scene.world.add(Arc::new(TriangleMesh::new(mesh, material)));
+            }
+            ty => self.warn(format!("Shape \"{ty}\" is not supported")),
+        }
+        Ok(())
+    }
+
+    fn finish(mut self) -> Scene {
+        if let Some(camera) = self.camera.take() {
+            let (width, height) = self.scene.image_size.unwrap_or((1280, 720));
+            let aspect_ratio = width as f64 / height as f64;
+            // pbrt's field of view spans the shorter image axis.
+            let vertical_fov = if aspect_ratio >= 1.0 {
+                camera.fov
+            } else {
+                2.0 * f64::atan(f64::tan(camera.fov.to_radians() / 2.0) / aspect_ratio).to_degrees()
+            };
+            let c2w = camera.camera_to_world;
+            self.scene.camera = Some(ProjectiveCamera::new(
+                c2w.transform_point(Point::new(0.0, 0.0, 0.0)),
+                c2w.transform_point(Point::new(0.0, 0.0, 1.0)),
+                c2w.transform_vector(Vec3::new(0.0, 1.0, 0.0)),
+                vertical_fov,
+                aspect_ratio,
+                2.0 * camera.lens_radius,
+                camera.focal_distance,
+            ));
+        }
+        self.scene
+    }
+}
+
+/// pbrt's world to camera transform for a camera at `eye` looking at `look`,
+/// with camera space looking down +z with +y up and +x right.
+fn look_at(eye: Point, look: Point, up: Vec3) -> Mat4 {
+    let dir = unit_vector(look - eye);
+    let right = unit_vector(cross(unit_vector(up), dir));
+    let new_up = cross(dir, right);
+    let camera_to_world = Mat4::new([
+        [right.x(), new_up.x(), dir.x(), eye.x()],
+        [right.y(), new_up.y(), dir.y(), eye.y()],
+        [right.z(), new_up.z(), dir.z(), eye.z()],
+        [0.0, 0.0, 0.0, 1.0],
+    ]);
+    camera_to_world.inverse().unwrap_or_else(Mat4::identity)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, io, path::Path};
+
+    use crate::{
+        camera::Camera,
+        hittable::Hittable,
+        ray::Ray,
+        vec3::{Color, Point, Vec3},
+    };
+
+    use super::{load, parse};
+
+    const SCENE: &str = r#"
+        # A red sphere right of centre and an emissive triangle above it.
+        LookAt 0 0 -5  0 0 0  0 1 0
+        Camera "perspective" "float fov" [45]
+        Film "image" "integer xresolution" [200] "integer yresolution" [100]
+            "string filename" "out.exr"
+        Sampler "halton" "integer pixelsamples" 8
+        Integrator "path"
+        WorldBegin
+        AttributeBegin
+            Material "matte" "rgb Kd" [0.8 0.1 0.1]
+            Translate 1 0 0
+            Shape "sphere" "float radius" [0.5]
+        AttributeEnd
+        AttributeBegin
+            AreaLightSource "diffuse" "rgb L" [4 4 4]
+            Shape "trianglemesh" "integer indices" [0 1 2]
+                "point P" [-1 3 -1  1 3 -1  0 3 1]
+        AttributeEnd
+        Shape "disk"
+        Frobnicate 1 2 3
+        WorldEnd
+    "#;
+
+    #[test]
+    fn imports_subset() {
+        let scene = parse(SCENE, Path::new(".")).unwrap();
+        assert_eq!(scene.image_size, Some((200, 100)));
+        assert_eq!(scene.samples_per_pixel, Some(8));
+        assert_eq!(
+            scene.warnings,
+            vec![
+                "Integrator is not supported".to_string(),
+                "Shape \"disk\" is not supported".to_string(),
+                "unknown directive Frobnicate on line 21".to_string(),
+            ]
+        );
+
+        // pbrt shows the sphere right of centre, so it must appear there
+        // through the right-handed camera too.
+        let camera = scene.camera.unwrap();
+        let half_width = f64::tan(22.5f64.to_radians()) * 2.0;
+        let s = 0.5 + 0.2 / (2.0 * half_width);
+        let rec = scene.world.hit(&camera.get_ray(s, 0.5).unwrap(), 0.001, f64::INFINITY).unwrap();
+        assert!(f64::abs((rec.p - Point::new(-1.0, 0.0, 0.0)).length() - 0.5) < 1e-9);
+        assert!(rec.front_face);
+        assert!(scene.world.hit(&camera.get_ray(1.0 - s, 0.5).unwrap(), 0.001, f64::INFINITY).is_none());
+
+        // The light faces down after mirroring and emits.
+        let up = Ray::new(Point::new(0.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0));
+        let rec = scene.world.hit(&up, 0.001, f64::INFINITY).unwrap();
+        assert_eq!(rec.t, 3.0);
+        assert!(rec.front_face);
+        let emitted = rec.material.unwrap().emitted(rec.u, rec.v, rec.p);
+        assert_eq!(emitted, Color::new(4.0, 4.0, 4.0));
+    }
+
+    #[test]
+    fn rejects_malformed_input() {
+        assert!(parse("Translate 1 2", Path::new(".")).is_err());
+        assert!(parse("AttributeEnd", Path::new(".")).is_err());
+        assert!(parse("Shape \"sphere\" \"float radius\" [1", Path::new(".")).is_err());
+        for indices in ["0 1 5", "0 1 -1", "0 1 1.5"] {
+            let mesh = format!("Shape \"trianglemesh\" \"integer indices\" [{indices}] \"point P\" [0 0 0 1 0 0 0 1 0]");
+            assert!(parse(&mesh, Path::new(".")).is_err());
+        }
+        assert!(parse("\"stray\"", Path::new(".")).is_err());
+    }
+
+    #[test]
+    fn film_defaults_to_pbrt_resolution() {
+        let scene = parse("Film \"image\"", Path::new(".")).unwrap();
+        assert_eq!(scene.image_size, Some((1280, 720)));
+    }
+
+    #[test]
+    fn rejects_circular_includes() {
+        let dir = std::env::temp_dir().join(format!("pbrt-include-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("a.pbrt"), "Include \"b.pbrt\"\n").unwrap();
+        fs::write(dir.join("b.pbrt"), "Include \"a.pbrt\"\n").unwrap();
+        fs::write(dir.join("c.pbrt"), "Include \"d.pbrt\"\nInclude \"d.pbrt\"\n").unwrap();
+        fs::write(dir.join("d.pbrt"), "Film \"image\"\n").unwrap();
+
+        let error = load(dir.join("a.pbrt")).err().unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        // Including the same file twice in a row is not a cycle.
+        assert!(load(dir.join("c.pbrt")).is_ok());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
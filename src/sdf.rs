@@ -0,0 +1,343 @@
+//! Signed distance functions: negative inside a shape, positive outside, and
+//! never more than the distance to the surface. Rendered by
+//! `hittable::sdf::SdfObject`.
+
+use std::sync::Arc;
+
+use crate::{
+    utils::clamp,
+    vec3::{dot, Point, Vec3},
+};
+
+pub trait Sdf {
+    fn distance(&self, p: Point) -> f64;
+}
+
+fn max_components(v: Vec3) -> Vec3 {
+    Vec3::new(v.x().max(0.0), v.y().max(0.0), v.z().max(0.0))
+}
+
+fn abs(v: Vec3) -> Vec3 {
+    Vec3::new(v.x().abs(), v.y().abs(), v.z().abs())
+}
+
+pub struct SdfSphere {
+    radius: f64,
+}
+
+impl SdfSphere {
+    pub fn new(radius: f64) -> Self {
+        Self { radius }
+    }
+}
+
+impl Sdf for SdfSphere {
+    fn distance(&self, p: Point) -> f64 {
+        p.length() - self.radius
+    }
+}
+
+/// Box centred on the origin.
+pub struct SdfBox {
+    half_extents: Vec3,
+}
+
+impl SdfBox {
+    pub fn new(half_extents: Vec3) -> Self {
+        Self { half_extents }
+    }
+}
+
+impl Sdf for SdfBox {
+    fn distance(&self, p: Point) -> f64 {
+        let q = abs(p) - self.half_extents;
+        max_components(q).length() + f64::min(q.x().max(q.y()).max(q.z()), 0.0)
+    }
+}
+
+/// Box centred on the origin with its edges rounded off by `radius`, within
+/// the same overall extents.
+pub struct RoundBox {
+    half_extents: Vec3,
+    radius: f64,
+}
+
+impl RoundBox {
+    pub fn new(half_extents: Vec3, radius: f64) -> Self {
+        Self {
+            half_extents,
+            radius,
+        }
+    }
+}
+
+impl Sdf for RoundBox {
+    fn distance(&self, p: Point) -> f64 {
+        let r = Vec3::new(self.radius, self.radius, self.radius);
+        let q = abs(p) - self.half_extents + r;
+        max_components(q).length() + f64::min(q.x().max(q.y()).max(q.z()), 0.0) - self.radius
+    }
+}
+
+/// Torus around the y axis.
+pub struct SdfTorus {
+    major_radius: f64,
+    minor_radius: f64,
+}
+
+impl SdfTorus {
+    pub fn new(major_radius: f64, minor_radius: f64) -> Self {
+        Self {
+            major_radius,
+            minor_radius,
+        }
+    }
+}
+
+impl Sdf for SdfTorus {
+    fn distance(&self, p: Point) -> f64 {
+        let ring = f64::hypot(p.x(), p.z()) - self.major_radius;
+        f64::hypot(ring, p.y()) - self.minor_radius
+    }
+}
+
+/// Segment from `a` to `b` thickened by `radius`.
+pub struct Capsule {
+    a: Point,
+    b: Point,
+    radius: f64,
+}
+
+impl Capsule {
+    pub fn new(a: Point, b: Point, radius: f64) -> Self {
+        Self { a, b, radius }
+    }
+}
+
+impl Sdf for Capsule {
+    fn distance(&self, p: Point) -> f64 {
+        let pa = p - self.a;
+        let ba = self.b - self.a;
+        let h = clamp(dot(pa, ba) / dot(ba, ba), 0.0, 1.0);
+        (pa - h * ba).length() - self.radius
+    }
+}
+
+/// The Mandelbulb fractal, via its distance estimator. It fits inside a
+/// sphere of radius 1.2 around the origin.
+pub struct Mandelbulb {
+    power: f64,
+    iterations: usize,
+}
+
+impl Mandelbulb {
+    pub fn new(power: f64, iterations: usize) -> Self {
+        Self { power, iterations }
+    }
+}
+
+impl Sdf for Mandelbulb {
+    fn distance(&self, p: Point) -> f64 {
+        let mut z = p;
+        let mut dr = 1.0;
+        let mut r = z.length();
+        for _ in 0..self.iterations {
+            r = z.length();
+            // The origin is a fixed point of the iteration, so it is inside.
+            if r > 2.0 || r == 0.0 {
+                break;
+            }
+            let theta = f64::acos(clamp(z.z() / r, -1.0, 1.0)) * self.power;
+            let phi = f64::atan2(z.y(), z.x()) * self.power;
+            dr = r.powf(self.power - 1.0) * self.power * dr + 1.0;
+            let zr = r.powf(self.power);
+            z = zr
+                * Vec3::new(
+                    f64::sin(theta) * f64::cos(phi),
+                    f64::sin(phi) * f64::sin(theta),
+                    f64::cos(theta),
+                )
+                + p;
+        }
+        if r == 0.0 {
+            return 0.0;
+        }
+        0.5 * f64::ln(r) * r / dr
+    }
+}
+
+pub struct Translated {
+    inner: Arc<dyn Sdf + Sync + Send>,
+    offset: Vec3,
+}
+
+impl Translated {
+    pub fn new(inner: Arc<dyn Sdf + Sync + Send>, offset: Vec3) -> Self {
+        Self { inner, offset }
+    }
+}
+
+impl Sdf for Translated {
+    fn distance(&self, p: Point) -> f64 {
+        self.inner.distance(p - self.offset)
+    }
+}
+
+pub struct Union {
+    a: Arc<dyn Sdf + Sync + Send>,
+    b: Arc<dyn Sdf + Sync + Send>,
+}
+
+impl Union {
+    pub fn new(a: Arc<dyn Sdf + Sync + Send>, b: Arc<dyn Sdf + Sync + Send>) -> Self {
+        Self { a, b }
+    }
+}
+
+impl Sdf for Union {
+    fn distance(&self, p: Point) -> f64 {
+        f64::min(self.a.distance(p), self.b.distance(p))
+    }
+}
+
+/// Union that blends the shapes together where they come within `k` of each
+/// other.
+pub struct SmoothUnion {
+    a: Arc<dyn Sdf + Sync + Send>,
+    b: Arc<dyn Sdf + Sync + Send>,
+    k: f64,
+}
+
+impl SmoothUnion {
+    pub fn new(a: Arc<dyn Sdf + Sync + Send>, b: Arc<dyn Sdf + Sync + Send>, k: f64) -> Self {
+        Self { a, b, k }
+    }
+}
+
+impl Sdf for SmoothUnion {
+    fn distance(&self, p: Point) -> f64 {
+        let (d1, d2) = (self.a.distance(p), self.b.distance(p));
+        let h = clamp(0.5 + 0.5 * (d2 - d1) / self.k, 0.0, 1.0);
+        d2 + (d1 - d2) * h - self.k * h * (1.0 - h)
+    }
+}
+
+/// Twists space around the y axis by `rate` radians per unit of height. The
+/// result is no longer an exact distance, so render it with a reduced step
+/// scale.
+pub struct Twist {
+    inner: Arc<dyn Sdf + Sync + Send>,
+    rate: f64,
+}
+
+impl Twist {
+    pub fn new(inner: Arc<dyn Sdf + Sync + Send>, rate: f64) -> Self {
+        Self { inner, rate }
+    }
+}
+
+impl Sdf for Twist {
+    fn distance(&self, p: Point) -> f64 {
+        let (s, c) = f64::sin_cos(self.rate * p.y());
+        let q = Point::new(c * p.x() - s * p.z(), p.y(), s * p.x() + c * p.z());
+        self.inner.distance(q)
+    }
+}
+
+/// Repeats a shape infinitely on a grid with the given spacing. A spacing of
+/// zero along an axis leaves that axis unrepeated.
+pub struct Repeat {
+    inner: Arc<dyn Sdf + Sync + Send>,
+    spacing: Vec3,
+}
+
+impl Repeat {
+    pub fn new(inner: Arc<dyn Sdf + Sync + Send>, spacing: Vec3) -> Self {
+        Self { inner, spacing }
+    }
+}
+
+impl Sdf for Repeat {
+    fn distance(&self, p: Point) -> f64 {
+        let mut q = p;
+        for axis in 0..3 {
+            let s = self.spacing[axis];
+            if s > 0.0 {
+                q[axis] = p[axis] - s * f64::round(p[axis] / s);
+            }
+        }
+        self.inner.distance(q)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::vec3::{Point, Vec3};
+
+    use super::*;
+
+    fn assert_close(a: f64, b: f64) {
+        assert!(f64::abs(a - b) < 1e-9, "{a} != {b}");
+    }
+
+    #[test]
+    fn primitives() {
+        assert_close(SdfSphere::new(1.0).distance(Point::new(0.0, 3.0, 0.0)), 2.0);
+
+        let cube = SdfBox::new(Vec3::new(1.0, 1.0, 1.0));
+        assert_close(cube.distance(Point::new(3.0, 0.0, 0.0)), 2.0);
+        assert_close(cube.distance(Point::new(0.5, 0.0, 0.0)), -0.5);
+        assert_close(cube.distance(Point::new(2.0, 2.0, 1.0)), f64::sqrt(2.0));
+
+        let round = RoundBox::new(Vec3::new(1.0, 1.0, 1.0), 0.25);
+        assert_close(round.distance(Point::new(3.0, 0.0, 0.0)), 2.0);
+        // The rounded corner is cut back from the sharp one.
+        assert!(round.distance(Point::new(1.0, 1.0, 1.0)) > 0.0);
+        assert_close(round.distance(Point::new(2.0, 2.0, 2.0)), 1.25 * f64::sqrt(3.0) - 0.25);
+
+        let torus = SdfTorus::new(2.0, 0.5);
+        assert_close(torus.distance(Point::new(2.0, 0.0, 0.0)), -0.5);
+        assert_close(torus.distance(Point::new(0.0, 0.0, 0.0)), 1.5);
+
+        let capsule = Capsule::new(Point::new(0.0, -1.0, 0.0), Point::new(0.0, 1.0, 0.0), 0.5);
+        assert_close(capsule.distance(Point::new(2.0, 0.5, 0.0)), 1.5);
+        assert_close(capsule.distance(Point::new(0.0, 3.0, 0.0)), 1.5);
+    }
+
+    #[test]
+    fn operators() {
+        let sphere: Arc<dyn Sdf + Sync + Send> = Arc::new(SdfSphere::new(1.0));
+        let moved = Translated::new(sphere.clone(), Vec3::new(3.0, 0.0, 0.0));
+        assert_close(moved.distance(Point::new(3.0, 0.0, 0.0)), -1.0);
+
+        let union = Union::new(sphere.clone(), Arc::new(moved));
+        assert_close(union.distance(Point::new(1.5, 0.0, 0.0)), 0.5);
+
+        // Smooth union never exceeds the hard union and matches it far away.
+        let a: Arc<dyn Sdf + Sync + Send> = Arc::new(SdfSphere::new(1.0));
+        let b: Arc<dyn Sdf + Sync + Send> = Arc::new(Translated::new(a.clone(), Vec3::new(1.5, 0.0, 0.0)));
+        let smooth = SmoothUnion::new(a.clone(), b.clone(), 0.5);
+        let hard = Union::new(a, b);
+        let p = Point::new(0.75, 1.0, 0.0);
+        assert!(smooth.distance(p) < hard.distance(p));
+        let far = Point::new(-5.0, 0.0, 0.0);
+        assert_close(smooth.distance(far), hard.distance(far));
+
+        let repeated = Repeat::new(sphere.clone(), Vec3::new(4.0, 0.0, 0.0));
+        assert_close(repeated.distance(Point::new(8.0, 0.0, 0.0)), -1.0);
+        assert_close(repeated.distance(Point::new(8.0, 3.0, 0.0)), 2.0);
+
+        // Twisting a shape symmetric about the y axis changes nothing.
+        let twisted = Twist::new(sphere, 2.0);
+        assert_close(twisted.distance(Point::new(0.3, 0.4, 0.5)), SdfSphere::new(1.0).distance(Point::new(0.3, 0.4, 0.5)));
+    }
+
+    #[test]
+    fn mandelbulb_is_bounded() {
+        let bulb = Mandelbulb::new(8.0, 12);
+        assert!(bulb.distance(Point::new(0.0, 0.0, 2.0)) > 0.0);
+        assert!(bulb.distance(Point::new(0.0, 0.0, 0.0)) <= 0.0 + 1e-9);
+    }
+}
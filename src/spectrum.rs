@@ -0,0 +1,277 @@
+use std::{
+    ops::{Add, AddAssign, Mul},
+    sync::OnceLock,
+};
+
+use crate::vec3::{Color, Vec3};
+
+pub const LAMBDA_MIN: f64 = 380.0;
+pub const LAMBDA_MAX: f64 = 780.0;
+
+/// Number of wavelengths carried by each path: the hero wavelength and
+/// equally spaced rotations of it across the visible range.
+pub const N_SPECTRUM_SAMPLES: usize = 4;
+
+/// The wavelengths, in nanometres, a single camera path is evaluated at.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct SampledWavelengths {
+    lambda: [f64; N_SPECTRUM_SAMPLES],
+    pdf: [f64; N_SPECTRUM_SAMPLES],
+}
+
+impl SampledWavelengths {
+    /// Hero wavelength sampling: `u` picks the hero wavelength uniformly and
+    /// the others are rotations of it by multiples of the range / N.
+    pub fn sample_uniform(u: f64) -> Self {
+        let range = LAMBDA_MAX - LAMBDA_MIN;
+        let hero = LAMBDA_MIN + u * range;
+        let delta = range / N_SPECTRUM_SAMPLES as f64;
+
+        let mut lambda = [0.0; N_SPECTRUM_SAMPLES];
+        for (i, l) in lambda.iter_mut().enumerate() {
+            let mut wavelength = hero + i as f64 * delta;
+            if wavelength > LAMBDA_MAX {
+                wavelength -= range;
+            }
+            *l = wavelength;
+        }
+        Self {
+            lambda,
+            pdf: [1.0 / range; N_SPECTRUM_SAMPLES],
+        }
+    }
+
+    pub fn hero(&self) -> f64 {
+        self.lambda[0]
+    }
+
+    pub fn lambda(&self, i: usize) -> f64 {
+        self.lambda[i]
+    }
+
+    pub fn pdf(&self, i: usize) -> f64 {
+        self.pdf[i]
+    }
+
+    pub fn secondary_terminated(&self) -> bool {
+        self.pdf[1..].iter().all(|&pdf| pdf == 0.0)
+    }
+
+    /// Drops all but the hero wavelength, for interactions such as dispersion
+    /// that send different wavelengths in different directions.
+    pub fn terminate_secondary(&mut self) {
+        if self.secondary_terminated() {
+            return;
+        }
+        for pdf in self.pdf[1..].iter_mut() {
+            *pdf = 0.0;
+        }
+        self.pdf[0] /= N_SPECTRUM_SAMPLES as f64;
+    }
+}
+
+/// Spectral quantity evaluated at a set of `SampledWavelengths`.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct SampledSpectrum {
+    values: [f64; N_SPECTRUM_SAMPLES],
+}
+
+impl SampledSpectrum {
+    pub fn new(values: [f64; N_SPECTRUM_SAMPLES]) -> Self {
+        Self { values }
+    }
+
+    pub fn constant(value: f64) -> Self {
+        Self::new([value; N_SPECTRUM_SAMPLES])
+    }
+
+    pub fn value(&self, i: usize) -> f64 {
+        self.values[i]
+    }
+
+    /// Upsamples an RGB color to a smooth spectrum and evaluates it at the
+    /// sampled wavelengths. The conversion is linear, so reflectances stay
+    /// within `[0, 1]` and white maps to a constant spectrum.
+    pub fn from_rgb(color: Color, lambda: &SampledWavelengths) -> Self {
+        let mut values = [0.0; N_SPECTRUM_SAMPLES];
+        for (i, v) in values.iter_mut().enumerate() {
+            *v = rgb_to_spectrum(color, lambda.lambda(i));
+        }
+        Self { values }
+    }
+
+    /// Monte Carlo estimate of the linear sRGB color of this spectrum.
+    pub fn to_rgb(&self, lambda: &SampledWavelengths) -> Color {
+        let mut xyz = Vec3::default();
+        for i in 0..N_SPECTRUM_SAMPLES {
+            if lambda.pdf(i) == 0.0 {
+                continue;
+            }
+            xyz += self.values[i] / lambda.pdf(i) * cie_xyz(lambda.lambda(i));
+        }
+        let xyz = xyz / (N_SPECTRUM_SAMPLES as f64 * cie_y_integral());
+        let rgb = xyz_to_linear_srgb(xyz);
+        let white = white_balance();
+        Color::new(
+            (rgb.x() / white.x()).max(0.0),
+            (rgb.y() / white.y()).max(0.0),
+            (rgb.z() / white.z()).max(0.0),
+        )
+    }
+}
+
+impl Add for SampledSpectrum {
+    type Output = SampledSpectrum;
+    fn add(self, rhs: Self) -> Self::Output {
+        let mut values = self.values;
+        for (v, r) in values.iter_mut().zip(rhs.values) {
+            *v += r;
+        }
+        Self { values }
+    }
+}
+
+impl AddAssign for SampledSpectrum {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl Mul for SampledSpectrum {
+    type Output = SampledSpectrum;
+    fn mul(self, rhs: Self) -> Self::Output {
+        let mut values = self.values;
+        for (v, r) in values.iter_mut().zip(rhs.values) {
+            *v *= r;
+        }
+        Self { values }
+    }
+}
+
+impl Mul<f64> for SampledSpectrum {
+    type Output = SampledSpectrum;
+    fn mul(self, rhs: f64) -> Self::Output {
+        Self {
+            values: self.values.map(|v| v * rhs),
+        }
+    }
+}
+
+fn smoothstep(edge0: f64, edge1: f64, x: f64) -> f64 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+/// Evaluates the upsampled spectrum of `color` at `lambda`. The red, green and
+/// blue basis spectra are smooth bands that sum to one at every wavelength.
+pub fn rgb_to_spectrum(color: Color, lambda: f64) -> f64 {
+    let blue = 1.0 - smoothstep(480.0, 510.0, lambda);
+    let red = smoothstep(570.0, 600.0, lambda);
+    let green = 1.0 - blue - red;
+    color.x() * red + color.y() * green + color.z() * blue
+}
+
+fn piecewise_gaussian(x: f64, mu: f64, sigma_low: f64, sigma_high: f64) -> f64 {
+    let sigma = if x < mu { sigma_low } else { sigma_high };
+    let t = (x - mu) / sigma;
+    f64::exp(-0.5 * t * t)
+}
+
+/// CIE 1931 2° colour matching functions, using the multi-lobe analytic fit
+/// of Wyman, Sloan and Shirley (2013).
+pub fn cie_xyz(lambda: f64) -> Vec3 {
+    let x = 1.056 * piecewise_gaussian(lambda, 599.8, 37.9, 31.0)
+        + 0.362 * piecewise_gaussian(lambda, 442.0, 16.0, 26.7)
+        - 0.065 * piecewise_gaussian(lambda, 501.1, 20.4, 26.2);
+    let y = 0.821 * piecewise_gaussian(lambda, 568.8, 46.9, 40.5)
+        + 0.286 * piecewise_gaussian(lambda, 530.9, 16.3, 31.1);
+    let z = 1.217 * piecewise_gaussian(lambda, 437.0, 11.8, 36.0)
+        + 0.681 * piecewise_gaussian(lambda, 459.0, 26.0, 13.8);
+    Vec3::new(x, y, z)
+}
+
+fn integrate_cie(steps: usize) -> Vec3 {
+    let step = (LAMBDA_MAX - LAMBDA_MIN) / steps as f64;
+    (0..steps).fold(Vec3::default(), |acc, i| {
+        acc + step * cie_xyz(LAMBDA_MIN + (i as f64 + 0.5) * step)
+    })
+}
+
+/// Integral of the y matching function over the sampled range, which
+/// normalizes spectra so that a constant spectrum of 1 has luminance 1.
+fn cie_y_integral() -> f64 {
+    static INTEGRAL: OnceLock<f64> = OnceLock::new();
+    *INTEGRAL.get_or_init(|| integrate_cie(4000).y())
+}
+
+/// Linear sRGB of a constant spectrum, used to map the equal energy white
+/// of the upsampled spectra back to (1, 1, 1).
+fn white_balance() -> Color {
+    static WHITE: OnceLock<Color> = OnceLock::new();
+    *WHITE.get_or_init(|| xyz_to_linear_srgb(integrate_cie(4000) / cie_y_integral()))
+}
+
+pub fn xyz_to_linear_srgb(xyz: Vec3) -> Color {
+    Color::new(
+        3.240479 * xyz.x() - 1.537150 * xyz.y() - 0.498535 * xyz.z(),
+        -0.969256 * xyz.x() + 1.875991 * xyz.y() + 0.041556 * xyz.z(),
+        0.055648 * xyz.x() - 0.204043 * xyz.y() + 1.057311 * xyz.z(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{utils::random_double, vec3::Color};
+
+    use super::*;
+
+    fn average_rgb(color: Color, samples: usize) -> Color {
+        (0..samples).fold(Color::default(), |acc, _| {
+            let lambda = SampledWavelengths::sample_uniform(random_double(0.0, 1.0));
+            acc + SampledSpectrum::from_rgb(color, &lambda).to_rgb(&lambda)
+        }) / samples as f64
+    }
+
+    fn assert_close(a: Color, b: Color, epsilon: f64) {
+        assert!((a - b).length() < epsilon, "{a:?} != {b:?}");
+    }
+
+    #[test]
+    fn hero_wavelengths_are_stratified() {
+        let lambda = SampledWavelengths::sample_uniform(0.9);
+        assert!(f64::abs(lambda.hero() - 740.0) < 1e-9);
+        for i in 0..N_SPECTRUM_SAMPLES {
+            assert!(lambda.lambda(i) >= LAMBDA_MIN && lambda.lambda(i) <= LAMBDA_MAX);
+        }
+        // 740 + 100 wraps around to the start of the range.
+        assert!(f64::abs(lambda.lambda(1) - 440.0) < 1e-9);
+        assert!(f64::abs(lambda.lambda(3) - 640.0) < 1e-9);
+    }
+
+    #[test]
+    fn terminate_secondary_keeps_estimate_unbiased() {
+        let mut lambda = SampledWavelengths::sample_uniform(0.3);
+        let pdf = lambda.pdf(0);
+        lambda.terminate_secondary();
+        assert!(lambda.secondary_terminated());
+        assert_eq!(lambda.pdf(0), pdf / N_SPECTRUM_SAMPLES as f64);
+        lambda.terminate_secondary();
+        assert_eq!(lambda.pdf(0), pdf / N_SPECTRUM_SAMPLES as f64);
+    }
+
+    #[test]
+    fn white_round_trips() {
+        assert_close(average_rgb(Color::new(1.0, 1.0, 1.0), 20_000), Color::new(1.0, 1.0, 1.0), 0.02);
+        assert_close(average_rgb(Color::new(0.5, 0.5, 0.5), 20_000), Color::new(0.5, 0.5, 0.5), 0.01);
+    }
+
+    #[test]
+    fn primaries_keep_their_hue() {
+        let red = average_rgb(Color::new(1.0, 0.0, 0.0), 20_000);
+        assert!(red.x() > red.y() && red.x() > red.z());
+        let green = average_rgb(Color::new(0.0, 1.0, 0.0), 20_000);
+        assert!(green.y() > green.x() && green.y() > green.z());
+        let blue = average_rgb(Color::new(0.0, 0.0, 1.0), 20_000);
+        assert!(blue.z() > blue.x() && blue.z() > blue.y());
+    }
+}
@@ -0,0 +1,21 @@
+use crate::vec3::{Color, Point};
+
+pub trait Texture {
+    fn value(&self, u: f64, v: f64, p: Point) -> Color;
+}
+
+pub struct SolidColor {
+    color: Color,
+}
+
+impl SolidColor {
+    pub fn new(color: Color) -> Self {
+        Self { color }
+    }
+}
+
+impl Texture for SolidColor {
+    fn value(&self, _: f64, _: f64, _: Point) -> Color {
+        self.color
+    }
+}
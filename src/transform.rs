@@ -0,0 +1,428 @@
+use std::ops::Mul;
+
+use crate::{
+    aabb::{surrounding_box, Aabb},
+    matrix::Mat4,
+    ray::Ray,
+    vec3::{unit_vector, Point, Vec3},
+};
+
+/// An invertible affine transform, stored together with its inverse.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Transform {
+    m: Mat4,
+    inv: Mat4,
+}
+
+impl Transform {
+    /// Panics if `m` is singular.
+    pub fn new(m: Mat4) -> Self {
+        let inv = m.inverse().expect("transform matrix should be invertible");
+        Self { m, inv }
+    }
+
+    pub fn identity() -> Self {
+        Self::default()
+    }
+
+    pub fn translate(delta: Vec3) -> Self {
+        let m = Mat4::new([
+            [1.0, 0.0, 0.0, delta.x()],
+            [0.0, 1.0, 0.0, delta.y()],
+            [0.0, 0.0, 1.0, delta.z()],
+            [0.0, 0.0, 0.0, 1.0],
+        ]);
+        let inv = Mat4::new([
+            [1.0, 0.0, 0.0, -delta.x()],
+            [0.0, 1.0, 0.0, -delta.y()],
+            [0.0, 0.0, 1.0, -delta.z()],
+            [0.0, 0.0, 0.0, 1.0],
+        ]);
+        Self { m, inv }
+    }
+
+    pub fn scale(factors: Vec3) -> Self {
+        let m = Mat4::new([
+            [factors.x(), 0.0, 0.0, 0.0],
+            [0.0, factors.y(), 0.0, 0.0],
+            [0.0, 0.0, factors.z(), 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ]);
+        let inv = Mat4::new([
+            [1.0 / factors.x(), 0.0, 0.0, 0.0],
+            [0.0, 1.0 / factors.y(), 0.0, 0.0],
+            [0.0, 0.0, 1.0 / factors.z(), 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ]);
+        Self { m, inv }
+    }
+
+    pub fn uniform_scale(factor: f64) -> Self {
+        Self::scale(Vec3::new(factor, factor, factor))
+    }
+
+    /// Rotation by `degrees` counter-clockwise around `axis`.
+    pub fn rotate(axis: Vec3, degrees: f64) -> Self {
+        let a = unit_vector(axis);
+        let (sin, cos) = f64::sin_cos(degrees.to_radians());
+        let t = 1.0 - cos;
+        let m = Mat4::new([
+            [
+                t * a.x() * a.x() + cos,
+                t * a.x() * a.y() - sin * a.z(),
+                t * a.x() * a.z() + sin * a.y(),
+                0.0,
+            ],
+            [
+                t * a.x() * a.y() + sin * a.z(),
+                t * a.y() * a.y() + cos,
+                t * a.y() * a.z() - sin * a.x(),
+                0.0,
+            ],
+            [
+                t * a.x() * a.z() - sin * a.y(),
+                t * a.y() * a.z() + sin * a.x(),
+                t * a.z() * a.z() + cos,
+                0.0,
+            ],
+            [0.0, 0.0, 0.0, 1.0],
+        ]);
+        // Rotations are orthogonal.
+        Self { m, inv: m.transpose() }
+    }
+
+    /// Rotation by the unit quaternion `x i + y j + z k + w`.
+    pub fn rotate_quaternion([x, y, z, w]: [f64; 4]) -> Self {
+        let m = Mat4::new([
+            [1.0 - 2.0 * (y * y + z * z), 2.0 * (x * y - z * w), 2.0 * (x * z + y * w), 0.0],
+            [2.0 * (x * y + z * w), 1.0 - 2.0 * (x * x + z * z), 2.0 * (y * z - x * w), 0.0],
+            [2.0 * (x * z - y * w), 2.0 * (y * z + x * w), 1.0 - 2.0 * (x * x + y * y), 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ]);
+        Self { m, inv: m.transpose() }
+    }
+
+    pub fn rotate_x(degrees: f64) -> Self {
+        Self::rotate(Vec3::new(1.0, 0.0, 0.0), degrees)
+    }
+
+    pub fn rotate_y(degrees: f64) -> Self {
+        Self::rotate(Vec3::new(0.0, 1.0, 0.0), degrees)
+    }
+
+    pub fn rotate_z(degrees: f64) -> Self {
+        Self::rotate(Vec3::new(0.0, 0.0, 1.0), degrees)
+    }
+
+    pub fn matrix(&self) -> Mat4 {
+        self.m
+    }
+
+    pub fn inverse(&self) -> Self {
+        Self {
+            m: self.inv,
+            inv: self.m,
+        }
+    }
+
+    /// Whether the transform mirrors space, turning clockwise triangle
+    /// windings counter-clockwise.
+    pub fn swaps_handedness(&self) -> bool {
+        let m = |row, col| self.m[(row, col)];
+        let det = m(0, 0) * (m(1, 1) * m(2, 2) - m(1, 2) * m(2, 1))
+            - m(0, 1) * (m(1, 0) * m(2, 2) - m(1, 2) * m(2, 0))
+            + m(0, 2) * (m(1, 0) * m(2, 1) - m(1, 1) * m(2, 0));
+        det < 0.0
+    }
+
+    /// `self` followed by `next`.
+    pub fn then(&self, next: &Transform) -> Self {
+        *next * *self
+    }
+
+    pub fn point(&self, p: Point) -> Point {
+        self.m.transform_point(p)
+    }
+
+    pub fn vector(&self, v: Vec3) -> Vec3 {
+        self.m.transform_vector(v)
+    }
+
+    /// Transforms a surface normal by the inverse transpose, so it stays
+    /// perpendicular to the transformed surface. The result is not normalized.
+    pub fn normal(&self, n: Vec3) -> Vec3 {
+        self.inv.transpose().transform_vector(n)
+    }
+
+    /// Transforms a ray without normalizing its direction, so the ray
+    /// parameter `t` of a hit is the same in both spaces. The time and
+    /// wavelengths carry over unchanged.
+    pub fn ray(&self, ray: &Ray) -> Ray {
+        let transformed = Ray::new(self.point(ray.origin()), self.vector(ray.direction())).with_time(ray.time());
+        match ray.wavelengths() {
+            Some(lambda) => transformed.with_wavelengths(lambda),
+            None => transformed,
+        }
+    }
+
+    /// Bounding box of the transformed corners of `bbox`.
+    pub fn aabb(&self, bbox: &Aabb) -> Aabb {
+        let (min, max) = (bbox.min(), bbox.max());
+        let mut corners = (0..8).map(|i| {
+            self.point(Point::new(
+                if i & 1 == 0 { min.x() } else { max.x() },
+                if i & 2 == 0 { min.y() } else { max.y() },
+                if i & 4 == 0 { min.z() } else { max.z() },
+            ))
+        });
+        let first = corners.next().unwrap();
+        corners.fold(Aabb::new(first, first), |acc, p| {
+            surrounding_box(acc, Aabb::new(p, p))
+        })
+    }
+}
+
+impl Mul for Transform {
+    type Output = Transform;
+
+    /// Composition: `(a * b)` applies `b` first, then `a`.
+    fn mul(self, rhs: Self) -> Self::Output {
+        Transform {
+            m: self.m * rhs.m,
+            inv: rhs.inv * self.inv,
+        }
+    }
+}
+
+/// A transform pose at an instant: scale first, then rotation about the x,
+/// y and z axes in that order (in degrees), then translation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Keyframe {
+    pub time: f64,
+    pub translation: Vec3,
+    pub rotation: Vec3,
+    pub scale: Vec3,
+}
+
+impl Keyframe {
+    pub fn new(time: f64, translation: Vec3, rotation: Vec3, scale: Vec3) -> Self {
+        Self {
+            time,
+            translation,
+            rotation,
+            scale,
+        }
+    }
+
+    /// A keyframe that only translates.
+    pub fn translation(time: f64, translation: Vec3) -> Self {
+        Self::new(time, translation, Vec3::default(), Vec3::new(1.0, 1.0, 1.0))
+    }
+
+    pub fn transform(&self) -> Transform {
+        Transform::scale(self.scale)
+            .then(&Transform::rotate_x(self.rotation.x()))
+            .then(&Transform::rotate_y(self.rotation.y()))
+            .then(&Transform::rotate_z(self.rotation.z()))
+            .then(&Transform::translate(self.translation))
+    }
+
+    fn lerp(&self, other: &Keyframe, time: f64) -> Keyframe {
+        let f = (time - self.time) / (other.time - self.time);
+        let mix = |a: Vec3, b: Vec3| (1.0 - f) * a + f * b;
+        Keyframe {
+            time,
+            translation: mix(self.translation, other.translation),
+            rotation: mix(self.rotation, other.rotation),
+            scale: mix(self.scale, other.scale),
+        }
+    }
+}
+
+/// A transform that varies over time by interpolating linearly between
+/// keyframes. Before the first and after the last keyframe it holds still.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnimatedTransform {
+    keyframes: Vec<Keyframe>,
+}
+
+impl AnimatedTransform {
+    /// Panics if `keyframes` is empty.
+    pub fn keyframed(mut keyframes: Vec<Keyframe>) -> Self {
+        assert!(!keyframes.is_empty(), "an animation needs at least one keyframe");
+        keyframes.sort_by(|a, b| a.time.total_cmp(&b.time));
+        Self { keyframes }
+    }
+
+    pub fn linear(start: Keyframe, end: Keyframe) -> Self {
+        Self::keyframed(vec![start, end])
+    }
+
+    pub fn at(&self, time: f64) -> Transform {
+        let first = &self.keyframes[0];
+        let last = &self.keyframes[self.keyframes.len() - 1];
+        if time <= first.time {
+            return first.transform();
+        }
+        if time >= last.time {
+            return last.transform();
+        }
+        let next = self.keyframes.partition_point(|k| k.time <= time);
+        let prev = &self.keyframes[next - 1];
+        prev.lerp(&self.keyframes[next], time).transform()
+    }
+
+    /// Bounds `bbox` over the whole animation. Each segment between
+    /// keyframes is sampled at evenly spaced poses, and the box around each
+    /// pair of neighbouring poses is padded by how far any point of `bbox`
+    /// can stray from the straight line between them, so the bounds are
+    /// conservative however fast the rotation.
+    pub fn aabb(&self, bbox: &Aabb) -> Aabb {
+        const STEPS: usize = 64;
+        let corners = [bbox.min(), bbox.max()];
+        let radius = (0..8)
+            .map(|i| Vec3::new(corners[i & 1].x(), corners[(i >> 1) & 1].y(), corners[(i >> 2) & 1].z()).length())
+            .fold(0.0, f64::max);
+        let max_abs = |v: Vec3| f64::max(f64::max(v.x().abs(), v.y().abs()), v.z().abs());
+
+        let mut bounds = self.keyframes[0].transform().aabb(bbox);
+        for pair in self.keyframes.windows(2) {
+            // Over a segment the angles and scale change linearly. With
+            // `spin` the sum of the angular rates, the angular velocity of
+            // the composed rotation is at most `spin` and, as each axis turns
+            // with the ones after it, changes at up to ab + bc + ca <=
+            // spin^2 / 3. So |R''| <= 4/3 spin^2, and with `growth` the rate
+            // of scaling, |p''| <= radius * (4/3 spin^2 * scale + 2 * spin *
+            // growth) for any point of the box. A curve strays at most
+            // |p''| h^2 / 8 from the chord over a step of length h.
+            let turn = pair[1].rotation - pair[0].rotation;
+            let spin = (turn.x().abs() + turn.y().abs() + turn.z().abs()).to_radians();
+            let scale = f64::max(max_abs(pair[0].scale), max_abs(pair[1].scale));
+            let growth = max_abs(pair[1].scale - pair[0].scale);
+            let curvature = radius * (4.0 / 3.0 * spin * spin * scale + 2.0 * spin * growth);
+            let pad = curvature / (8.0 * (STEPS * STEPS) as f64);
+            let pad = Vec3::new(pad, pad, pad);
+
+            let mut previous = pair[0].transform().aabb(bbox);
+            for step in 1..=STEPS {
+                let time = pair[0].time + (pair[1].time - pair[0].time) * step as f64 / STEPS as f64;
+                let transformed = pair[0].lerp(&pair[1], time).transform().aabb(bbox);
+                let swept = surrounding_box(previous, transformed);
+                bounds = surrounding_box(bounds, Aabb::new(swept.min() - pad, swept.max() + pad));
+                previous = transformed;
+            }
+        }
+        bounds
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        aabb::Aabb,
+        vec3::{dot, Point, Vec3},
+    };
+
+    use super::{AnimatedTransform, Keyframe, Transform};
+
+    fn assert_close(a: Vec3, b: Vec3) {
+        assert!((a - b).length() < 1e-9, "{a:?} != {b:?}");
+    }
+
+    #[test]
+    fn basic_transforms() {
+        let p = Point::new(1.0, 2.0, 3.0);
+        assert_close(Transform::translate(Vec3::new(1.0, 1.0, 1.0)).point(p), Point::new(2.0, 3.0, 4.0));
+        assert_close(Transform::scale(Vec3::new(2.0, 3.0, 4.0)).point(p), Point::new(2.0, 6.0, 12.0));
+        assert_close(Transform::rotate_z(90.0).point(Point::new(1.0, 0.0, 0.0)), Point::new(0.0, 1.0, 0.0));
+        assert_close(Transform::rotate_x(90.0).point(Point::new(0.0, 1.0, 0.0)), Point::new(0.0, 0.0, 1.0));
+        assert_close(Transform::rotate_y(90.0).point(Point::new(0.0, 0.0, 1.0)), Point::new(1.0, 0.0, 0.0));
+
+        let half = f64::sqrt(0.5);
+        let q = Transform::rotate_quaternion([0.0, 0.0, half, half]);
+        assert_close(q.point(p), Transform::rotate_z(90.0).point(p));
+    }
+
+    #[test]
+    fn composition_and_inverse() {
+        let t = Transform::scale(Vec3::new(2.0, 2.0, 2.0)).then(&Transform::translate(Vec3::new(1.0, 0.0, 0.0)));
+        let p = Point::new(1.0, 1.0, 1.0);
+        assert_close(t.point(p), Point::new(3.0, 2.0, 2.0));
+        assert_close(t.inverse().point(t.point(p)), p);
+
+        let r = Transform::rotate(Vec3::new(1.0, 1.0, 0.0), 37.0) * Transform::translate(Vec3::new(0.0, 5.0, 0.0));
+        assert_close(r.inverse().point(r.point(p)), p);
+
+        assert!(!r.swaps_handedness());
+        assert!(Transform::scale(Vec3::new(1.0, -1.0, 1.0)).swaps_handedness());
+    }
+
+    #[test]
+    fn normals_stay_perpendicular() {
+        let t = Transform::scale(Vec3::new(1.0, 4.0, 1.0)) * Transform::rotate_z(30.0);
+        let tangent = Vec3::new(1.0, -1.0, 0.0);
+        let normal = Vec3::new(1.0, 1.0, 0.0);
+        assert!(f64::abs(dot(t.vector(tangent), t.normal(normal))) < 1e-9);
+    }
+
+    #[test]
+    fn transformed_box() {
+        let bbox = Aabb::new(Point::new(-1.0, -1.0, -1.0), Point::new(1.0, 1.0, 1.0));
+        let rotated = Transform::rotate_z(45.0).aabb(&bbox);
+        let s = f64::sqrt(2.0);
+        assert_close(rotated.min(), Point::new(-s, -s, -1.0));
+        assert_close(rotated.max(), Point::new(s, s, 1.0));
+    }
+
+    #[test]
+    fn keyframed_animation() {
+        let animation = AnimatedTransform::keyframed(vec![
+            Keyframe::translation(1.0, Vec3::new(2.0, 0.0, 0.0)),
+            Keyframe::translation(0.0, Vec3::new(0.0, 0.0, 0.0)),
+            Keyframe::new(2.0, Vec3::new(2.0, 2.0, 0.0), Vec3::new(0.0, 0.0, 90.0), Vec3::new(1.0, 1.0, 1.0)),
+        ]);
+        let p = Point::new(1.0, 0.0, 0.0);
+        assert_close(animation.at(-1.0).point(p), p);
+        assert_close(animation.at(0.5).point(p), Point::new(2.0, 0.0, 0.0));
+        assert_close(animation.at(1.5).point(p), Point::new(2.0 + f64::sqrt(0.5), 1.0 + f64::sqrt(0.5), 0.0));
+        assert_close(animation.at(3.0).point(p), Point::new(2.0, 3.0, 0.0));
+    }
+
+    #[test]
+    fn animated_box_covers_motion() {
+        let animation = AnimatedTransform::linear(
+            Keyframe::translation(0.0, Vec3::new(0.0, 0.0, 0.0)),
+            Keyframe::translation(1.0, Vec3::new(0.0, 3.0, 0.0)),
+        );
+        let bbox = Aabb::new(Point::new(-1.0, -1.0, -1.0), Point::new(1.0, 1.0, 1.0));
+        let bounds = animation.aabb(&bbox);
+        assert_close(bounds.min(), Point::new(-1.0, -1.0, -1.0));
+        assert_close(bounds.max(), Point::new(1.0, 4.0, 1.0));
+    }
+
+    #[test]
+    fn animated_box_covers_fast_rotation() {
+        let animation = AnimatedTransform::linear(
+            Keyframe::new(0.0, Vec3::default(), Vec3::default(), Vec3::new(1.0, 1.0, 1.0)),
+            Keyframe::new(1.0, Vec3::new(1.0, 0.0, 0.0), Vec3::new(300.0, 517.0, 1079.0), Vec3::new(2.0, 0.5, 1.0)),
+        );
+        let bbox = Aabb::new(Point::new(0.5, -0.25, 0.0), Point::new(1.5, 0.25, 0.1));
+        let bounds = animation.aabb(&bbox);
+
+        let n = 20_000;
+        for i in 0..=n {
+            let pose = animation.at(i as f64 / n as f64);
+            for corner in 0..8 {
+                let p = Point::new(
+                    if corner & 1 == 0 { 0.5 } else { 1.5 },
+                    if corner & 2 == 0 { -0.25 } else { 0.25 },
+                    if corner & 4 == 0 { 0.0 } else { 0.1 },
+                );
+                let q = pose.point(p);
+                for axis in 0..3 {
+                    assert!(bounds.min()[axis] <= q[axis] && q[axis] <= bounds.max()[axis], "{q:?} outside");
+                }
+            }
+        }
+    }
+}